@@ -0,0 +1,42 @@
+// Embed every `poems/<lang>/*.txt` file as a built-in poem.
+//
+// Generates `$OUT_DIR/embedded_poems.rs`, a `&[(&str, &str)]` of (language code, file text),
+// so adding or removing a poem file never requires editing `poems.rs`.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LANGS: &[&str] = &["en", "zh", "fr", "ja", "es"];
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let poems_dir = manifest_dir.join("poems");
+    println!("cargo:rerun-if-changed={}", poems_dir.display());
+
+    let mut entries: Vec<(&str, PathBuf)> = Vec::new();
+    for lang in LANGS {
+        let dir = poems_dir.join(lang);
+        let Ok(read_dir) = fs::read_dir(&dir) else { continue };
+        let mut files: Vec<PathBuf> = read_dir
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("txt"))
+            .collect();
+        // Stable order regardless of filesystem iteration order.
+        files.sort();
+        entries.extend(files.into_iter().map(|p| (*lang, p)));
+    }
+
+    let mut out = String::from("&[\n");
+    for (lang, path) in &entries {
+        out.push_str(&format!("    ({:?}, include_str!({:?})),\n", lang, path_str(path)));
+    }
+    out.push_str("]\n");
+
+    let dest = PathBuf::from(env::var("OUT_DIR").unwrap()).join("embedded_poems.rs");
+    fs::write(dest, out).unwrap();
+}
+
+fn path_str(path: &Path) -> String {
+    path.to_str().expect("poem paths must be valid UTF-8").to_string()
+}
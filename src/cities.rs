@@ -0,0 +1,122 @@
+use chrono::FixedOffset;
+
+/// A named observing location for `--city`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct City {
+    pub name: &'static str,
+    /// Degrees, north positive.
+    pub lat: f64,
+    /// Degrees, east positive.
+    pub lon: f64,
+    /// Standard-time offset from UTC in minutes (daylight saving isn't modelled).
+    pub utc_offset_minutes: i32,
+    /// Clocks go forward part of the year, so `utc_offset_minutes` is an hour off for months at a time.
+    pub observes_dst: bool,
+}
+
+impl City {
+    /// The city's standard-time offset, year round.
+    pub fn offset(&self) -> FixedOffset {
+        FixedOffset::east_opt(self.utc_offset_minutes * 60).expect("table offsets are within a day")
+    }
+}
+
+const fn city(name: &'static str, lat: f64, lon: f64, utc_offset_minutes: i32, observes_dst: bool) -> City {
+    City { name, lat, lon, utc_offset_minutes, observes_dst }
+}
+
+/// A modest list of major cities, roughly west to east.
+pub const CITIES: &[City] = &[
+    city("Honolulu", 21.31, -157.86, -600, false),
+    city("Anchorage", 61.22, -149.90, -540, true),
+    city("Los Angeles", 34.05, -118.24, -480, true),
+    city("San Francisco", 37.77, -122.42, -480, true),
+    city("Vancouver", 49.28, -123.12, -480, true),
+    city("Denver", 39.74, -104.99, -420, true),
+    city("Mexico City", 19.43, -99.13, -360, false),
+    city("Chicago", 41.88, -87.63, -360, true),
+    city("Toronto", 43.65, -79.38, -300, true),
+    city("New York", 40.71, -74.01, -300, true),
+    city("Lima", -12.05, -77.04, -300, false),
+    city("Santiago", -33.45, -70.67, -240, true),
+    city("Buenos Aires", -34.60, -58.38, -180, false),
+    city("Sao Paulo", -23.55, -46.63, -180, false),
+    city("Reykjavik", 64.15, -21.94, 0, false),
+    city("Lisbon", 38.72, -9.14, 0, true),
+    city("London", 51.51, -0.13, 0, true),
+    city("Madrid", 40.42, -3.70, 60, true),
+    city("Paris", 48.86, 2.35, 60, true),
+    city("Berlin", 52.52, 13.40, 60, true),
+    city("Rome", 41.90, 12.50, 60, true),
+    city("Stockholm", 59.33, 18.07, 60, true),
+    city("Lagos", 6.52, 3.38, 60, false),
+    city("Cairo", 30.04, 31.24, 120, true),
+    city("Athens", 37.98, 23.73, 120, true),
+    city("Johannesburg", -26.20, 28.05, 120, false),
+    city("Istanbul", 41.01, 28.98, 180, false),
+    city("Moscow", 55.76, 37.62, 180, false),
+    city("Nairobi", -1.29, 36.82, 180, false),
+    city("Dubai", 25.20, 55.27, 240, false),
+    city("Karachi", 24.86, 67.01, 300, false),
+    city("Mumbai", 19.08, 72.88, 330, false),
+    city("Delhi", 28.61, 77.21, 330, false),
+    city("Bangkok", 13.76, 100.50, 420, false),
+    city("Jakarta", -6.21, 106.85, 420, false),
+    city("Singapore", 1.35, 103.82, 480, false),
+    city("Hong Kong", 22.32, 114.17, 480, false),
+    city("Beijing", 39.90, 116.41, 480, false),
+    city("Shanghai", 31.23, 121.47, 480, false),
+    city("Perth", -31.95, 115.86, 480, false),
+    city("Seoul", 37.57, 126.98, 540, false),
+    city("Tokyo", 35.68, 139.69, 540, false),
+    city("Sydney", -33.87, 151.21, 600, true),
+    city("Melbourne", -37.81, 144.96, 600, true),
+    city("Auckland", -36.85, 174.76, 720, true),
+];
+
+/// Look a city up by name, ignoring case, spaces, hyphens and underscores
+/// (`tokyo`, `new-york`, `Hong_Kong`).
+pub fn find_city(name: &str) -> Option<&'static City> {
+    let key = |s: &str| -> String {
+        s.chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let wanted = key(name);
+    CITIES.iter().find(|c| key(c.name) == wanted)
+}
+
+/// `--city` value parser.
+pub fn parse_city(s: &str) -> Result<&'static City, String> {
+    find_city(s).ok_or_else(|| {
+        format!("unknown city `{s}`; give the location with --lat/--lon instead (see --list-cities)")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_cities_loosely_by_name() {
+        let tokyo = find_city("tokyo").unwrap();
+        assert_eq!((tokyo.lat, tokyo.lon), (35.68, 139.69));
+        assert_eq!(tokyo.offset(), FixedOffset::east_opt(9 * 3600).unwrap());
+        assert_eq!(find_city("new-york").unwrap().name, "New York");
+        assert_eq!(find_city("HONG_KONG").unwrap().name, "Hong Kong");
+        assert!(find_city("Atlantis").is_none());
+        assert!(find_city("London").unwrap().observes_dst && !tokyo.observes_dst);
+        assert!(parse_city("Atlantis").unwrap_err().contains("--lat/--lon"));
+    }
+
+    #[test]
+    fn table_entries_are_valid_and_unique() {
+        for (i, city) in CITIES.iter().enumerate() {
+            assert!((-90.0..=90.0).contains(&city.lat) && (-180.0..=180.0).contains(&city.lon), "{}", city.name);
+            assert!(city.utc_offset_minutes.abs() < 24 * 60, "{}", city.name);
+            assert_eq!(find_city(city.name).map(|c| c.name), Some(city.name));
+            assert!(CITIES[..i].iter().all(|c| c.name != city.name), "duplicate {}", city.name);
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! The moon behind the `ascii_moon` binary, as a library: the phase math
+//! (`calculate_moon_phase`, `MoonStatus`) and the ratatui renderer (`render_moon`,
+//! `MoonWidget`), for drawing the moon inside another terminal app.
+//!
+//! ```
+//! use ascii_moon::{calculate_moon_phase, render_moon, RenderOptions};
+//! use chrono::Utc;
+//! use ratatui::{buffer::Buffer, layout::Rect};
+//!
+//! let area = Rect::new(0, 0, 40, 20);
+//! let mut buf = Buffer::empty(area);
+//! let status = calculate_moon_phase(Utc::now());
+//! render_moon(&status, area, &mut buf, RenderOptions::default());
+//! ```
+
+mod moon;
+mod render;
+
+pub use moon::*;
+pub use render::*;
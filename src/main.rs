@@ -1,4 +1,4 @@
-use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, TimeZone, Timelike, Utc};
 use clap::Parser;
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
@@ -8,15 +8,26 @@ use crossterm::{
 use ratatui::{
     backend::Backend,
     prelude::*,
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
 };
 use std::io::{self, Write};
 use std::path::PathBuf;
+use std::process::ExitCode;
 use std::time::Instant;
 use unicode_width::UnicodeWidthStr;
 
+mod cities;
 mod poems;
+#[cfg(feature = "serve")]
+mod serve;
 
+use ascii_moon::{
+    art_crop, calculate_moon_phase_with, deg_to_rad, disc_box, julian_day_utc, moon_lit_color, normalize_degrees,
+    source_art_lines, truncate_to_width, try_calculate_moon_phase, CellRatio, DiscBox, Language, Lod, MoonPhase,
+    MoonStatus, MoonWidget, Precision, RenderOptions, ShadowMode, FEATURE_LEGEND, LUNAR_FEATURES, SIDEREAL_MONTH,
+    SUPPORTED_YEARS, SYNODIC_MONTH, ZODIAC_SIGNS,
+};
+use cities::City;
 use poems::{Poem, PoemLibrary};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -38,11 +49,144 @@ impl std::str::FromStr for Theme {
     }
 }
 
+/// How `P` (and `p`/`T`/`M`) choose the next poem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PoemOrder {
+    Random,
+    /// File order within the language, wrapping at the end.
+    Sequential,
+}
+
+impl std::str::FromStr for PoemOrder {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "random" => Ok(PoemOrder::Random),
+            "sequential" => Ok(PoemOrder::Sequential),
+            _ => Err("poem order must be one of: random, sequential".to_string()),
+        }
+    }
+}
+
+/// Frame around the poem panel (`--poem-border`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PoemBorder {
+    Plain,
+    Rounded,
+    Double,
+    None,
+}
+
+impl std::str::FromStr for PoemBorder {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "plain" => Ok(PoemBorder::Plain),
+            "rounded" => Ok(PoemBorder::Rounded),
+            "double" => Ok(PoemBorder::Double),
+            "none" => Ok(PoemBorder::None),
+            _ => Err("poem border must be one of: plain, rounded, double, none".to_string()),
+        }
+    }
+}
+
+impl PoemBorder {
+    /// The ratatui border for this style, or `None` to draw no frame at all.
+    fn border_type(self) -> Option<BorderType> {
+        match self {
+            PoemBorder::Plain => Some(BorderType::Plain),
+            PoemBorder::Rounded => Some(BorderType::Rounded),
+            PoemBorder::Double => Some(BorderType::Double),
+            PoemBorder::None => None,
+        }
+    }
+}
+
+/// Time of day used for a bare calendar date (`--anchor`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    /// Local midnight at the start of the day.
+    Midnight,
+    /// 12:00 UTC (what `--date` has always used).
+    Noon,
+    /// The current local time of day.
+    Now,
+}
+
+impl std::str::FromStr for Anchor {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "midnight" => Ok(Anchor::Midnight),
+            "noon" => Ok(Anchor::Noon),
+            "now" => Ok(Anchor::Now),
+            _ => Err("anchor must be one of: midnight, noon, now".to_string()),
+        }
+    }
+}
+
+/// Which way up the moon is drawn (`--hemisphere`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hemisphere {
+    /// North up: a waxing moon is lit on the right.
+    North,
+    /// Turned 180°, as seen from south of the equator: a waxing moon is lit on the left.
+    South,
+}
+
+impl std::str::FromStr for Hemisphere {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "north" | "n" => Ok(Hemisphere::North),
+            "south" | "s" => Ok(Hemisphere::South),
+            _ => Err("hemisphere must be one of: north, south".to_string()),
+        }
+    }
+}
+
+impl Hemisphere {
+    /// `--hemisphere` if given, else the sign of `--lat`, else north.
+    fn from_args(args: &Args) -> Self {
+        match (args.hemisphere, args.lat) {
+            (Some(hemisphere), _) => hemisphere,
+            (None, Some(lat)) if lat < 0.0 => Hemisphere::South,
+            _ => Hemisphere::North,
+        }
+    }
+}
+
+/// One-shot (non-interactive) output formats selected with `--format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Ascii,
+    Json,
+    Emoji,
+    Name,
+    Table,
+    Describe,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ascii" => Ok(OutputFormat::Ascii),
+            "json" => Ok(OutputFormat::Json),
+            "emoji" => Ok(OutputFormat::Emoji),
+            "name" => Ok(OutputFormat::Name),
+            "table" => Ok(OutputFormat::Table),
+            "describe" => Ok(OutputFormat::Describe),
+            _ => Err("format must be one of: ascii, json, emoji, name, table, describe".to_string()),
+        }
+    }
+}
+
 fn detect_light_terminal_background() -> Option<bool> {
     // Heuristic: some terminals expose ANSI color indices via COLORFGBG="fg;bg" (or "fg:bg").
     // We treat bg 7/15 as "light background".
     let s = std::env::var("COLORFGBG").ok()?;
-    let parts: Vec<&str> = s.split(|c| c == ';' || c == ':').collect();
+    let parts: Vec<&str> = s.split([';', ':']).collect();
     let bg = parts.last()?.trim().parse::<u16>().ok()?;
     Some(bg == 7 || bg == 15)
 }
@@ -76,22 +220,154 @@ fn supports_truecolor() -> bool {
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Date in YYYY-MM-DD format (defaults to today)
-    #[arg(short, long)]
+    /// Date as YYYY-MM-DD, or relative: today, tomorrow, yesterday, +3d, -2w (defaults to today)
+    #[arg(short, long, allow_hyphen_values = true)]
     date: Option<String>,
 
-    /// Render the moon to a specific number of lines (non-interactive)
-    #[arg(long)]
+    /// Time of day for the date: midnight (local), noon (UTC), or now.
+    ///
+    /// Defaults to noon for `--date` and to the live current instant otherwise.
+    /// Given without `--date`, it pins today at that time instead of following the clock.
+    #[arg(long, value_name = "WHEN")]
+    anchor: Option<Anchor>,
+
+    /// Start paused at the current time in Manual mode instead of following now (`n` resumes following)
+    #[arg(long, default_value_t = false)]
+    no_follow: bool,
+
+    /// Reopen the TUI on the date it was left at (or following now, if it was); saved on quit
+    #[arg(long, default_value_t = false)]
+    remember_date: bool,
+
+    /// Start at the next time the Moon enters this phase (e.g. `full`, `first-quarter`), in Manual mode
+    #[arg(long, value_name = "PHASE")]
+    watch_phase: Option<MoonPhase>,
+
+    /// Time zone for calendar days, as a UTC offset (`+09:00`, `-0500`, `UTC`); defaults to the system zone.
+    ///
+    /// Decides where days roll over: the displayed date, `--anchor midnight` and `--anchor now`.
+    #[arg(long, value_name = "OFFSET", value_parser = parse_tz, allow_hyphen_values = true)]
+    tz: Option<FixedOffset>,
+
+    /// Render the moon to a specific number of lines (non-interactive, 1-500)
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..=MAX_PRINT_LINES as i64))]
     lines: Option<u16>,
 
-    /// Auto-refresh period in minutes in interactive mode (0 disables auto-refresh)
-    #[arg(long, default_value_t = 5)]
+    /// Terminal width to fit printed moons into, instead of asking the terminal (which gives 80 when piped)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    assume_cols: Option<u16>,
+
+    /// Auto-refresh period in minutes in interactive mode (0 disables auto-refresh, max one week)
+    ///
+    /// Poem animation runs at its own rate regardless of this setting.
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(0..=MAX_REFRESH_MINUTES))]
     refresh_minutes: u64,
 
+    /// Widest the moon may be drawn, in terminal columns (TUI and print mode)
+    #[arg(long, value_name = "CELLS", value_parser = clap::value_parser!(u16).range(2..))]
+    max_moon_size: Option<u16>,
+
+    /// In Details, show progress through the sidereal month (relative to the stars) beside the synodic age
+    #[arg(long, default_value_t = false)]
+    show_sidereal: bool,
+
+    /// In Details, show the Moon's zodiac sign and a countdown to it entering the next one
+    #[arg(long, default_value_t = false)]
+    zodiac: bool,
+
+    /// Maximally portable output: a pure ASCII brightness ramp instead of the art, no colour, no borders
+    #[arg(long, default_value_t = false)]
+    dumb_terminal: bool,
+
+    /// Inverted video for light backgrounds and printing: dark lit glyphs, shadow cells on a light background
+    #[arg(long, default_value_t = false, conflicts_with = "dumb_terminal")]
+    light_bg: bool,
+
+    /// Level of detail: low (no labels, flat shadow), auto (by moon size, default), or high
+    #[arg(long, default_value = "auto", value_name = "LEVEL")]
+    lod: Lod,
+
+    /// Draw the whole art rectangle instead of cropping it to a disc (lighting still applies)
+    #[arg(long, default_value_t = false)]
+    no_mask: bool,
+
+    /// Show the phase through glyph density: dense characters on the lit side, sparse in shadow
+    #[arg(long, default_value_t = false)]
+    density_by_illum: bool,
+
+    /// Dim the lit side toward the edge of the disc, like a photograph (needs a truecolor terminal)
+    #[arg(long, default_value_t = false)]
+    limb_darkening: bool,
+
+    /// Trace the day/night boundary across the disc in a faint blue line
+    #[arg(long, default_value_t = false)]
+    terminator: bool,
+
+    /// Let the moon slowly grow and shrink by a few percent, like breathing (TUI only)
+    #[arg(long, default_value_t = false)]
+    breathe: bool,
+
+    /// Scatter stars around the moon; they fade as the moon waxes toward full and wash out the sky
+    #[arg(long, default_value_t = false)]
+    stars: bool,
+
+    /// Blank the TUI to one small moon glyph after this many minutes without a keypress (any key wakes it)
+    #[arg(long, value_name = "MINUTES", value_parser = clap::value_parser!(u64).range(1..=MAX_REFRESH_MINUTES))]
+    blank_after: Option<u64>,
+
+    /// Cap on TUI redraws per second; changes in between are coalesced into one frame (1-120)
+    #[arg(long, value_name = "N", default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..=MAX_FPS as i64))]
+    max_fps: u32,
+
+    /// Slideshow: advance the TUI one calendar day every SECONDS, forever (space pauses)
+    #[arg(long, value_name = "SECONDS", value_parser = parse_slide_seconds)]
+    loop_dates: Option<f64>,
+
+    /// Send a desktop notification when the live moon enters New, First Quarter, Full or Last Quarter
+    #[arg(long)]
+    notify: bool,
+
+    /// Ring the terminal bell once when the live moon reaches full (the moon also flashes briefly)
+    #[arg(long)]
+    bell: bool,
+
+    /// Auto-refresh period in (fractional) seconds; overrides `--refresh-minutes`.
+    ///
+    /// Must be 0 (disabled) or at least 1 second.
+    #[arg(long, value_name = "SECONDS", value_parser = parse_tick_seconds, conflicts_with = "refresh_minutes")]
+    tick_seconds: Option<f64>,
+
     /// Hide the unlit (dark) part of the moon (renders shadow pixels as spaces)
     #[arg(long, default_value_t = false)]
     hide_dark: bool,
 
+    /// Leave the unlit part as terminal background but keep a dim outline of the full disc
+    #[arg(long, default_value_t = false, conflicts_with = "hide_dark")]
+    bg_shadow: bool,
+
+    /// Rock the moon's face with the computed libration (a small, monthly wobble)
+    #[arg(long, default_value_t = false)]
+    libration: bool,
+
+    /// Mark the disc center (+) and the subsolar point (*), the middle of the lit region
+    #[arg(long, default_value_t = false)]
+    markers: bool,
+
+    /// Start the TUI on the month calendar of phases instead of the moon (toggle with `c`)
+    #[arg(long, default_value_t = false)]
+    calendar: bool,
+
+    /// Spin the moon by this many degrees to show that the art (and the face we
+    /// see from Earth) is only the near side; beyond ±90° the far side shows blank
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true, value_name = "DEGREES")]
+    rotate: f64,
+
+    /// Terminal cell shape as WIDTH:HEIGHT, used to keep the disc round (default 1:2).
+    ///
+    /// Try 1:2.2 if the moon looks squat with your font, or 1:1.8 if it looks tall.
+    #[arg(long, default_value = "1:2", value_name = "W:H")]
+    cell_ratio: CellRatio,
+
     /// Directory containing poem files.
     ///
     /// If not provided, we try:
@@ -102,286 +378,370 @@ struct Args {
     #[arg(long)]
     poems_dir: Option<PathBuf>,
 
+    /// Never read poems from disk; use only the poems built into the binary.
+    ///
+    /// Skips the `./poems` and install-prefix scan, so an untrusted poems folder in the
+    /// working directory is never opened (for kiosks and shared machines).
+    #[arg(long, default_value_t = false, conflicts_with = "poems_dir")]
+    no_load_poems: bool,
+
+    /// Add custom poems to the built-in ones instead of replacing them.
+    ///
+    /// By default a language with any poem on disk uses only the disk poems. With this flag the
+    /// built-ins stay and custom poems are appended; a custom poem with the same title and author
+    /// as a built-in replaces it.
+    #[arg(long, default_value_t = false, conflicts_with = "no_load_poems")]
+    poems_merge: bool,
+
+    /// Make the poem motion freeze key (`g`) also pause the line-by-line reveal
+    #[arg(long, default_value_t = false)]
+    freeze_reveal: bool,
+
     /// Poem panel theme: auto (default), dark, or light
     #[arg(long, default_value = "auto")]
     theme: Theme,
+
+    /// Lunar series: standard (default) or high (full Meeus tables, sub-arcminute, slower)
+    #[arg(long, default_value = "standard", value_name = "LEVEL")]
+    precision: Precision,
+
+    /// Blend neighbouring art characters (bilinear) for a smoother small moon
+    #[arg(long, default_value_t = false)]
+    smooth: bool,
+
+    /// Tint the lit side by illumination: cool white as a crescent, warm cream at full
+    #[arg(long, default_value_t = false)]
+    warm: bool,
+
+    /// High-contrast poem colors with no glow cycling or twinkles (legibility over ambiance)
+    #[arg(long, default_value_t = false)]
+    readable_poems: bool,
+
+    /// Poem panel frame: plain (default), rounded, double, or none
+    #[arg(long, default_value = "plain", value_name = "STYLE")]
+    poem_border: PoemBorder,
+
+    /// Keep the poem border one steady color instead of glowing with the title
+    #[arg(long, default_value_t = false)]
+    static_poem_border: bool,
+
+    /// Order for picking poems: random (default) or sequential (file order, wrapping)
+    #[arg(long, default_value = "random", value_name = "ORDER")]
+    poem_order: PoemOrder,
+
+    /// List the loaded poems (title and author) grouped by language, then exit.
+    ///
+    /// Optionally restrict the listing to one language: en, zh, fr, ja, es
+    #[arg(long, value_name = "LANG", num_args = 0..=1)]
+    list_poems: Option<Option<Language>>,
+
+    /// Print the moon once and exit: ascii, json, emoji, name, table, or describe.
+    ///
+    /// `ascii` is the `--lines` art render (20 lines unless `--lines` is given).
+    #[arg(long, visible_alias = "output-format", value_name = "FORMAT")]
+    format: Option<OutputFormat>,
+
+    /// Alias for `--format json`
+    #[arg(long, default_value_t = false, conflicts_with_all = ["format", "describe"])]
+    json: bool,
+
+    /// Indent `--json` output (the moon, `--ephemeris`, `--offline-almanac`, `--summary`, `--interval-phases`)
+    /// for reading; the default is one compact line for piping. No effect on other outputs
+    #[arg(long, default_value_t = false)]
+    pretty: bool,
+
+    /// Write a CSV table (one row per day) for `--from`..`--to` and exit
+    #[arg(long, default_value_t = false, requires_all = ["from", "to"])]
+    csv: bool,
+
+    /// First day of the `--csv` range (YYYY-MM-DD)
+    #[arg(long)]
+    from: Option<String>,
+
+    /// Last day of the `--csv` range, inclusive (YYYY-MM-DD)
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Write `--csv` output to this file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    out: Option<PathBuf>,
+
+    /// Days to summarize with `--summary`, as START..END (YYYY-MM-DD, inclusive)
+    #[arg(long, value_name = "START..END", requires = "summary")]
+    date_range: Option<String>,
+
+    /// Print the illumination range, mean and dominant phase over `--date-range`, then exit (`--json` for an object)
+    #[arg(long, default_value_t = false, requires = "date_range")]
+    summary: bool,
+
+    /// List only the days in START..END (YYYY-MM-DD, inclusive) when the phase name changes, then exit
+    #[arg(long, value_name = "START..END")]
+    interval_phases: Option<String>,
+
+    /// List the Moon's monthly declination extremes (highest/lowest in the sky) for a year, then exit
+    #[arg(long, value_name = "YEAR", value_parser = clap::value_parser!(i32).range(1..=9998))]
+    standstills: Option<i32>,
+
+    /// Print a year's moon almanac (phases, perigees/apogees, supermoons, eclipse seasons), then exit.
+    ///
+    /// Times follow `--tz`; add `--json` for a structured dump.
+    #[arg(long, value_name = "YEAR", value_parser = clap::value_parser!(i32).range(1..=9998))]
+    offline_almanac: Option<i32>,
+
+    /// Print every computed value for one instant (UTC `YYYY-MM-DDTHH:MM`, or a date for noon), then exit.
+    ///
+    /// With `--json` it's a single JSON object; its field names are stable for integrations.
+    #[arg(long, value_name = "DATETIME")]
+    ephemeris: Option<String>,
+
+    /// Observer latitude in degrees (north positive), for altitude/azimuth and rise/set
+    #[arg(long, value_parser = parse_latitude, allow_hyphen_values = true, requires = "lon")]
+    lat: Option<f64>,
+
+    /// Observer longitude in degrees (east positive), for altitude/azimuth and rise/set
+    #[arg(long, value_parser = parse_longitude, allow_hyphen_values = true, requires = "lat")]
+    lon: Option<f64>,
+
+    /// Observe from a known city: sets --lat/--lon, and --tz unless given (e.g. `--city Tokyo`).
+    ///
+    /// The table holds standard-time offsets only, so cities with daylight saving leave --tz at the system zone.
+    #[arg(long, value_name = "NAME", value_parser = cities::parse_city, conflicts_with_all = ["lat", "lon"])]
+    city: Option<&'static City>,
+
+    /// Serve `GET /phase?date=...` (the `--ephemeris` JSON) over HTTP on PORT (localhost unless `--bind` says otherwise)
+    #[cfg(feature = "serve")]
+    #[arg(long, value_name = "PORT")]
+    serve: Option<u16>,
+
+    /// Address for `--serve` to listen on, e.g. `0.0.0.0` to accept connections from other machines
+    #[cfg(feature = "serve")]
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1", requires = "serve")]
+    bind: std::net::IpAddr,
+
+    /// List the cities `--city` knows, with their coordinates and UTC offsets, then exit
+    #[arg(long, default_value_t = false)]
+    list_cities: bool,
+
+    /// Orientation of the drawn moon: north (default) or south (turned 180°)
+    ///
+    /// Defaults from the sign of `--lat` when that is given.
+    #[arg(long, value_name = "SIDE")]
+    hemisphere: Option<Hemisphere>,
+
+    /// Draw the northern and southern views side by side, each with a caption (TUI and `--lines`)
+    #[arg(long, default_value_t = false, conflicts_with = "hemisphere")]
+    both_hemispheres: bool,
+
+    /// Check the moon math against built-in reference values, print PASS/FAIL per case, then exit
+    #[arg(long, default_value_t = false)]
+    self_test: bool,
+
+    /// Print the terminal size and the moon's computed draw box and art crop, then exit (for rendering bug reports)
+    #[arg(long, default_value_t = false)]
+    info_size: bool,
+
+    /// Print the built-in moon art with its crop box marked (a template for custom art), then exit
+    #[arg(long, default_value_t = false)]
+    dump_art: bool,
+
+    /// Render this many frames off-screen and print the throughput (for spotting regressions)
+    #[arg(long, hide = true, value_name = "FRAMES")]
+    benchmark: Option<u32>,
+
+    /// Render the print-mode moon (`--lines` tall) and print every cell's character and colour as JSON
+    #[arg(long, hide = true, default_value_t = false)]
+    dump_buffer: bool,
+
+    /// Print mode: redraw the current moon in place every SECONDS until Ctrl-C (a light screensaver)
+    #[arg(long, value_name = "SECONDS", value_parser = parse_repeat_seconds, conflicts_with_all = ["date", "days"])]
+    repeat: Option<f64>,
+
+    /// Print mode: render N consecutive days (the date and the N-1 following), each under a date header
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..=MAX_PRINT_DAYS as i64))]
+    days: Option<u16>,
+
+    /// In print mode, follow the art with the Details fields (date, phase, age, illumination) as plain text
+    #[arg(long, default_value_t = false)]
+    print_details: bool,
+
+    /// Print extra technical values (terminator position, libration) after one-shot output
+    #[arg(long, short, default_value_t = false)]
+    verbose: bool,
+
+    /// Alias for `--format describe`
+    #[arg(long, default_value_t = false, conflicts_with = "format")]
+    describe: bool,
+
+    /// Follow the `--describe` sentence with the phase for each of the next N days (implies `--describe`)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..=MAX_FORECAST_DAYS as i64), conflicts_with = "json")]
+    forecast: Option<u16>,
 }
 
-// Synodic month (new moon to new moon) in days (average; used only to express "age" in days)
-const SYNODIC_MONTH: f64 = 29.53058867;
-
-const MOON_ART_RAW: &str = r#"                                                                                    #@&&%#%&(#&###&%###&&&&#/(@&(###.  %/#,                                                                             
-                                                                            #&%%#&@%(&%##(*%&%##(###&&%&%#(#%&%%%&%###%(%#(#((@&&&(/.                                                                   
-                                                                   .%&&##%###/%%#%%#&,%%&%%%%#%%%%%%&&&&%%%%##%&(#(%&(###%/##&##%(*(&%@#%*%/                                                            
-                                                             /#/%&%#%(@%##%(((#&&&%%%%&%%%%&%&&&&&&&%%%%%%%%%%%#####%#%&#%#%%%%%%%%&&&&%%.%%%%%*(                                                       
-                                                       ,(.@&%((#(@%#&%###(####((%&%%%%%%%&&&&&&&#&&&&&%%%##%###%####(%#%##%#%%%%%%&&%&&(%&&&&%&&%&&&#,                                                  
-                                                   /(*/**,.%#((((*###%###((###%##%(%%%#%%%%%%%%%%%%#%%%%%%##%########%(####%%%%%%%%%&&&&%#%%%&%%&%%%%%%&#&                                              
-                                               /*/((%%(#####((%((((((((#((#(##(###########%#%%#&%###%##(#%%%%#####(#%#(((##&#%##%%&&&%&%%%%%%%%#%/#%(#(/%%%###                                          
-                                           ,*/,(/%/#/((#((((/(((((*//(////((#((#//(/((((#########(#(##(#(##(#(#%%((((#(#####&%###%%%%%&%&&&%%%%%#%%###(((##(*,,,/((##/####                                   
-                                        .,.,///((/(((/(/*((/&*////**/*//********////((((((((#(##(##((#(#(#%%((((#(#####&%###%%%%%&%&&&%%%%%#%%###(((##(*,,,/((##/####                                   
-                                     .,,,**////*********,,,*,**//(//***********//*****/*,**////((/((///((((((((((##(####%#((###%%%%%&&&&%%%&####%&(((((##((%####%((%(#&*                                
-                                  ..,,,*,*,*,.,******//******,,*///////*****/******/********/////*/(/((///////(/(((//(/((((((((((#%%%%%&%&%%&%((#%#%(#(###(((#((#(##((#%%*@                             
-                               ,..,.,,,*,*....,,,*//(*/////((/(((((//(/**/*/***/((((((///**///////////((///////**(////*********(#/###%#%%%%#%&%///(%####(##(//(((((#((#(/(#(*                           
-                             ......,,,,,*,,.,,,****#&(((((#((/////(#//*/((####((//((//(((((((///////////((///////*//*/*/*/*******//((##%#%%#%%(#%%#%%%#(((#%##(##(%(#((((##(%*#*#                        
-                           ........,,,,**,*/*///(((((((%#/////(/(%/////**//##(#*,,,*#/(/(%%%#*//((/////////*/////*////***/******(((((#%##%########%(((##((###%%(#((%(((###%((#((%#                      
-                        ..........,,....,*///((//(((%##((((//(/(/*****,,,,***//(*/((*/(((#(####((#////(////###(#(((///(*///#((///###%####%#(##%####(///((####%###(##/(((####(##%#,%%                    
-                      .........,........,/(//((//#(,,,,,**,**//**,,,,,,,,,**/******//(#%((((((##((/(/*/////(#(/(((//(((((((/////(###(%%%####%%%#%&##((/(/*//((#(*((##(######(((((##(#@                  
-                    ...............,..,***/*////(/*,,,,*,,,,,.....,,,,,,,,************//(#%(#(##(((///((((/(((((((#(((((////////#(((###(#####%###%##((((((((((/#((/((##%&%%##(((%(%/(#(%                
-                   ..................**/////*/(//,,,,.,,,,,.........,,,,*,,,,**,*,********//#/((###(##((((##((###(%#####(///(##(/#((//(((((((((########(#(##(%#((#%((##(#((%####(##%###((%%               
-                 ...............,,,,*//**//*//*,**,.............,,.,,,,,,,***,*#,****/****##(((((###(((((###%##%(((#(((///////#(#((%(#(((((((((#######(///%##(#((###(#(((#((((((#(%#(#(//(#             
-               ..,,............,,,,*******(/,,...,......,..,..,,,,,.,,,,**,,,,,*,***/*****/%(/(#/####((#(##(####((#(/((/(/#//###(((((//(//(#%#####%#%##/##((###%#%#((((#(//((((#/((##(((#((,            
-              ................,.,,,,**,***,,,.,,,,,,................,,,,/,,,,,,********#//////###/(((###%%(((###((((/(/****/(///((/(///*(//(%#((#####((((########%%##(%(((((##((((#####%##((((          
-            /*..............,,..,,.,.,*,,...,...,......,........,.,.,,,,*,,,,,*******//////////////#%%%%####(##((#(((((/**/*////((((((****/(#%###((((#####(%#%##%%&%#%(#((##%/#%(##((##(%%##(##         
-           (.,.........,.,..,,,,...,,*,,....,,,,,,,,.,,,......,,,,,,,,,,**,,,,,,****///*////*(/((/(###(#(/****//(((((((/***(//***(//**/***//(((#%##%%%###(((##((##%%%##((((((###%(#########(#///        
-           ..........,...,,,,,,,,,,,,,,..,,,,,.......,..,...,,.,,,,,,,,,,,,,,,******/**//**/**//(##((**,*,,,,,****/*/(((**,,**///(/****//((///(%%%#%#%#%(#(###%#%%%((((/%(((##&#%#&###%#%%#(*//       
-         ,/.........,.,.,./,**,,,,,,,**,,,,,,,,...........,....,,,,...,,,,,,.,,*(*,***//#///***/**((#((*******,********,,***,****//(((////(####%%%#####&%%#%#(#%####%#(((##(#(#(((#(####%(#%%(##(/      
-        ..............,,*/,,,,*,,,,.,.,,,,,,,,,,,,,,....,,,,,,,,,,,,,,,,,,,,,,,,*****////*****//////,,,,,,,,,,*,*,,,,,**,,,,,,/(##%%((///#%#%%##%%#%##%%%##%%###%&###(######%####/%(((##%###/#((#     
-       ..............,,*//*,,****,,,,,*(,,*,,.,,,,.,,..,,,...,*,,,,,,,,,,,,,,**///**///((///****(***,,,,...,,,,,,,,,,,***,,,,,,*/((%((((####(%%%%%%%%&###%%%%###(###((##%%#(/((/((###%###%&###/(///    
-      ...,..........,/*&&***,***,,,,,,,,,,,.,..,,,(,,,,,,,.,,,,,,,,,,,,,,,,,,/****//////////*******,,,,,,,,,,,,,,,,,,***,,,,,,,,,**//((((#%##%##%##%%####%&%#(###%(//((/#(#((/#&*%/##(((####((##((//   
-     %.............,*,*****,,,*,,.,,..*...,,,..,,*,,,,,,,,..,,,,,,*****,,***///*/*****//(((//*/(/*/,,,*,,,,,,,,,,,,,,,*,*,,,,,....,,///(##%%%%%%%####%(##%((##(###/(((#(##*//**/(//,%((((((##%(((/##/   
-     ...............,,,,,***,,,....,,..,,..,,,,,,,,,,,,,*,..,*,,,,*/***,**/////(///****,,**//((///*,,,,,,.,,,,,,,*,,,,,,,,,,....,,*/(###%&&%%%#%%%%#(*/((((((//((#%/*,*,,*,,*,(//((/(##((((/,,(///  
-    ................,....*,*,,.,,,,,.,,,,,*/*,,.,*,*,,,,*,,,*,,,,,,,,,,,,**//////(//**,***,*/////*,,,,,,.,,,,,,,,,,,**,,,,.,,,,,,,.,**////%(#%%###%####(#/(#((/(((//(*******,,,,.,,.,*//(/%(//((*,,,,*. 
-    .......................,***,,.,,.,,,*,*****,*,***,,,,**,**,,,,*,*,,,,,,,,*/(/////(//*/(/((%(/**.,,.......,,,,,,,*,,,,,,,,,,,,,,.,,*////(((#(//#(%#%#(#/(#((((((((****,*,,,,,.....*.,(/(##//(//,.,,,/ 
-    ,,...............*....,,,*,,,*...,..,,,*/*,*,**,,,/(,*,,,**,,,,,*,,,,,,,,,,,***/(#((%##(((///*,,..........,.,,,**,,,,,,,.,,,,,,,,.(,*/(#((((/**//%&#(/#(##(####(/***,,,,,,,,,....,..*#(##(/,/**...,* 
-    /,*...................,,,,****..,,,.***,,*,,**,***,,,**,*,*,**,,**,,,,*,,,,,,,**(/((#(##(//*/**,,......,,,,,,,,,*,,,,,,,,,,,,,,.,.,**/(((/////**/((#((####%#&#%#((/*,,,,,,,,,.....,,,(((##(#/,//,*,,/*
-    */,...................,,,,****.*,,..,,*******/**/****,**,********,,,*/*,,.,,/*,/((/((///(((////*,*,,,..,/....,,,,,,,,,,,*,,,,,,....,/*//////,,******,*/(((%&&&%&&%%#/*,,,,,..,....,*((/((###/(#(//(**/*
-    (*,...............,,.,,,*****,,..*/***(##///////**/******/*/*//*********,,/(/((////(//(/**//*,**,/*,****,/**,,,,,,,*,,,,,,,...,,,.,...,,/*******,,,,,//(((((((#%%%#/(/*,,*.,,..,....,/##(*((((((((/*,*/*
-    (*,..................,,,******,*,*,,***/(*(/*/(((///(*//*//*/((/**/////////////////****,********,,,,**,,,*,/(/(*.,,(,.,,......,,,,.....,,***,,,,,,,,*//(%((/(#&%#%#/**,,,,,,,,.,.,*(#/((#,(((((/(//***
-    (*#*...................*,*//**/**.,,,*///((((//*(((#((((///*///(///*,*,,,,***/////**,,,,,,,,,,,,,*/((/****,,,/(((#/***/*..,/*..........,....,,..,,...*,/(/((/(##%%#%#(///,,.,,,,,///#((/,*(,/(#/#(/(*,*
-    /(/*,..........,....,.,**///(//**,,,,**((/(#(((///###((((///////*//*****,,,,,,,****,,,,*,,,,,,,,,,/#((/*,,**/**,#**/*,...............,.,,................,,*/((//#####(###//*(/#/(////#,.(,,//(((#//..,
-    *((*................,***//#(////**,,****//(//(((#%%%&%(///(/////*/******,,,,,...,,*,,,,,,,,..,,.....,**,,//*///#///,,............,,*,,................*...,*,**/(######(##(((((((//*,/,.**/,#//(/#/,...
-    ,(#,.................,,***/(((/**,,,,*,,**/(((((#%%%%%%(///////////***,,,,,,.,..,,***,,,,..,,.....,**,,//*///#///,,............,,*,,................*...,,//*/*****/(((((((/(((((((/(//*//,#/*/(/%(,,.,
-    #*,.................,*,,/*////,,**//**#***//(//*/////*//////**//****,,,,**,,***///////***,,,,,.,.,****,((/(/**//*,,.....................,.............*/*,*****,*,***/((%####(((*(/((,(////,**//**....
-    /**..................,**,,,/,,,******/*//*///*////**/*********/*//,,**,,,****//////((#///**//******,**((((((/(/**,.........,,,,......................,,..,**//*//,,*,..*/(/((//(**/#*,/*((////*//*,.,*
-    *,(*,.................***,#/,,,*/**,*,****//////*/****//,,.,,,***,.....,,***((#((///(////((/**//,/((//((#((#(//***,...,*,.,,,.........,,......,.*,,,,,.,,,******,*,........,*.,,,***,,*/*/(/(**//,,,*,
-    ../*/.................*..,,,,,,,,,**,,***///*//(//*****,**,,,,,**,,..,,,,****/////*/(*//(####((#(##((###((#((////,*,.,,,,,..........,..*,,,.,,*,,,,.,,,,*****,,,,..,..........,,/***/,//(/////((*,,,* 
-    ,/%**/.....,......,........,,,,.*,,**/**,****/((///*//**,**,**,,,,,,,,,*,.***/,**//(((/(#((((((##%%%%%#%((/(/*/***,,,...........,....,,*,,*,,,..,,.,,,,*,**..,,,............,,,,,**/*/(///(/////*..* 
-    */(**,......,........,......,,*,.,,,,*/******///*/((///***,,****,*****,**,,*/,******//(((((####(#%%&%%%%#(((///(/*//,,.,.,,,,,......,,,,,(#((%,,/,*,,,*/,,.,,.,,.........,,,,,,,,*(//(//*////////**  
-    //#//,.....*.,(............,,,,,*,,**,***********/((///*****,***,*/*////**(*,,,**/**//((###%%%###%%%%%####(#(////*,**,,,,,,,,,,,,,,*.,*/&%####(/((/*((*,,,,,.,,....,..,,,,**,,**//*//**////////*  
-    ,/%/(*,.,............,,....,,,,,,,,,***,*,,,,,****///(/*//*******///(((((///*****//(((###(%(#%%###%%#######&#((((((##(/*/*,*,,*,,,,,*//(#((((%(#%(//***,,,,,,.,...,...,,*,/(###((///(///(/##/(,   
-     (*/#(,...,...,...,...,,...,,*,.,,*(,,*,,*,,,/(*,////////***(//(((((//(##((((((/(//(((((#####&###################%#%###(#(#(*/*,,,,,*((#((((((((/(#/(((*,,,,.,,.....,...,,*,/(###((///(///(/##/(,   
-      (/(%(*,,**..,..,...,....,.,*,,/////*,,*,,,,/***/(/*//**/,**(##%#(#((###((#((((((((((###%######(####%##%###%##%%%%%%%%%%(((****/*(/((((#((#(((((///**//,/.,,,,,..,..*****/#(%%#/(##/(/////////    
-       ///(//***#,,,.,.,,*,,.....,.,****//,...,,***,*///////(***,**/((###((((###%###((#(((((#####%############%%%##%&&%%&%#((//***/*****/(((((((###%#((((//*,/*,..,.....,,****/((##(/(////(///(///*     
-        (((((//**,,,***/**/*,.,..,,,,,**/,,,,.,,,,,,*,**/((/*,*,.,**///((((#########(((#########%%%%%%%#%%#%%####%%%&&%%%%#((((//////*//((((((((##%&##%%/*(//((/,,,*,,,,,**,****/////(//////(//(/(      
-        .##/(/(/*,*//*///////,,,......**,,....,,,,,,,*,*,*/#*/,*,,,**(//((((####(((((#(((#%(%#(##%%%&%%%###%%######%%%%%%%%####((/(((/////(//(#((#%%##(%#%#%%/*((**,,*,,,******/*/(////(////*(/////       
-          ##((//*///*//*///(/*/,,.,...,,*,*.,...,,,,,,*,*,*/#*/,*,,,**(//((((####(((((#(((#%(%#(##%%%&%%%###%%######%%%%%%%%####((/(((/////(//(#((#%%##(%#%#%%/*((**,,*,,,******/*/(////(////*(/////       
-          ##((/(/////(///(*/*,.*(,.,....,,,#/**(*,*,,******/((,,,,,**(//((((((##(((((((#(##(((%%###########%%%&&%%%&%%%%%#%%%%%%&##(##((((/*****/**/(##((/(/#((((**(#(******//****/****////(**         
-           ##((/(/////(///(*/*,.*(,.,....,,,#/**(*,*,,******/((,,,,,**(//((((((##(((((((#(##(((%%###########%%%&&%%%&%%%%%#%%%%%%&##(##((((/*****/**/(##((/(/#((((**(#(******//****/****////(**         
-            %%(#/////(##(/*//(/,./,,,/*,.,,//*(((*,,,****,,,/**,,,,,,*******(#((#((#((((##((####(((((##%#%%%##%%##%%##%%%%&&&&%%%%####/((/********//(&#%#%&#(/(#(/*///*****,**/*/**//***////**          
-             %###%(/((((/*,*.....,,,,.,***,,/((//*,,*,,,,,,,,*,*,,,***/*/#**((#%((###((((((##((%######%#####%##%%%###%%%%&&&&&%&&%%#(//************(#%%#//*#((//********##((((/**///////*//            
-              .##%(#((((/,,.........**(///***//**,,*,,****,,,***,**,,,,,,**(((#########((((##(######%%%%#%%%###%#####%#%%%%&%%&&&&&%#(/(//***/******(##(#(*((/((//***,*/(#(((//////*////*(/             
-                #(((##(*%/*,,.,.......*(///*///***#(/*,***,*,,,...,,*,,,/(##(((###%###(###&%&%%##(###(#########((###%%#%#%#%%%%%&%%%##((/(/******,**/(((((#(/**/(/**////(#((*(((//(/**///,              
-                  #((#%(#(/,,,,,......,*/**,*,,*/***/******,,,,,,,,,,,**(#(((%#####((((##%###%%(((((#######(#########%##%%%%%%%#%%%%%%/////#/(#%##(//(##(/#(((((/((((((/(%(#((((/****/                
-                   /((####///,*,,,..,.,,,,**,*,,*//*****,,,,*/,,,,,,,,**///#(&%####((((####((##(((((######%##%%#%##%#((##%%%%#%%%%%%%%%#((((###%%##(/((##(#(#%#(#%#((((#%#(##((((/****                  
-                     /(((#&%#(/*/,,,,,,,/****(/,,,*******,,,,,,,,,,,,,,***/*/(###%##(#####%####((#####%#%%%%%%%%#(##########%%%%&&%%#######%####((((#%###%%#%##%#%%%###(%%#((%((////                    
-                       (((((%###(/***(**//(//*(,**,,/***,,,,,,,,,,,,,,*,***/###%##((##%%%%%%######%%%%%%%%%%%%######(###%##%&&&%%%#%%#%%%&%#%#(####(##%%%%%%%%%%%%%%#%%%#(#&((///*.                     
-                         #/(((/((*(/////(//(/,,*/(///*((/*/*****//**//*(/**(###%%#%%%%%#&%%%%###%%%%%&%%%#%(#((#####%###%%%%%%%##%#%%%%%########(###%#%#(##%%%&&%%%%%%###%(////*                        
-                           ///(((/**//((/((#*/,,***//*/#%%#(#/(((****######%%%%%%%&%%%&%%&&%%%##%%%%%%%%###(#(#(###%&&%###(#%%#######%%##(####%###########%%%&%%%%&&#(((///***                          
-                              //(/((***(((//((***((***/(#%%&&#%%#%#(%#%%%&%&&&&&%%%%&&%%%&%&%%%%%##%##(########(#%&%%%%######%%%###(##(##(######%#%####%%%%%%%%%##///////***                            
-                                /,/%(((/(/##((((%#(*(###((%##%%%%%#%%#%&%%&&&&&@@&&&&%%%%%%%%%%%%%#%##%##%%##%%%&%%####%#%%#%%##(#(####((###############%#%%####(///****                               
-                                   ////(((#(#((#(((/##%(%###%##%%##%%%%&&&&&&%%&&&&&%%%&%%%%%&&&#%##%%%%###%%%%##%####(###%###(####(#(##########(##(#(#%#%((/(////***,                                  
-                                      /*////((###(##(##((((##%%%%%&%&%&&&&&&&&&&&&&&&%&%&%%%%%%%###%&&%#%%######%%########(###(#%%%##(####(#####%%####%((((/////***.                                    
-                                         //*//(//((#((#((#%#%%#%%%%%&&&%%%%%%&&&&&&&&&&&&&&&&&&%%%%%##(####((##%######%#%%%%%##(((####%%###%%%#%####(#(((/(///*,                                        
-                                            ./////(((##(#((####%%%%%%%%#&&&&&%%%%&&&&&&%%%&%%%%%%%&%%##%%%%%%%%#((####%##(#(###((##%#######%%%####((////((/**                                           
-                                                ***#/(((#((##((%#%%%%%&%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%####((##((((((####((#####%%%%%##%%%%%##(#(///#((/*,,                                               
-                                                    ,**(((/((((###%#%%%%%&&&&%%&&&%&&&&&&%%&%%%%#%%%%#######((#(((((#(#(((####(######(##(((////////*,                                                   
-                                                         *(((#(((###%%#%%%%%&&%%%%%%%%%%%%#&&%%%%%###%%%#((###(((####((#(###(#######(((((((/////                                                        
-                                                               ((/(####(#%%%%%%#%%%%%%%%%%##%%%%%#%######(########%##((((((((###//(((/////                                                              
-                                                                     .(((##(##%%%#%%%%%%%%%%%#%%##%%%%#((####(((((((((((/((((((////,                                                                    
-                                                                              */(%%%%%%%%%##%##########(/(((/(((((////////.                                                                             
-"#;
-
-fn moon_lit_color(truecolor: bool) -> Color {
-    if truecolor {
-        Color::Rgb(232, 208, 88) // warm moonlight
-    } else {
-        // 256-color gold. Important for terminals that don't parse 24-bit SGR (38;2;...),
-        // which can otherwise degrade to green (color index 2).
-        Color::Indexed(214)
+impl Args {
+    /// The requested one-shot format, folding the alias flags into `--format`.
+    fn output_format(&self) -> Option<OutputFormat> {
+        if self.json {
+            Some(OutputFormat::Json)
+        } else if self.describe || (self.forecast.is_some() && self.format.is_none()) {
+            Some(OutputFormat::Describe)
+        } else {
+            self.format
+        }
     }
-}
 
-fn moon_shadow_color(truecolor: bool) -> Color {
-    if truecolor {
-        Color::Rgb(92, 92, 98) // soft graphite
-    } else {
-        Color::Indexed(242)
+    /// A compact JSON document as it should be printed, indented under `--pretty`.
+    fn json_text(&self, compact: String) -> String {
+        if self.pretty { pretty_json(&compact) } else { compact }
     }
-}
 
-#[derive(Debug, Clone, Copy)]
-enum MoonPhase {
-    New,
-    WaxingCrescent,
-    FirstQuarter,
-    WaxingGibbous,
-    Full,
-    WaningGibbous,
-    LastQuarter,
-    WaningCrescent,
-}
-
-impl MoonPhase {
-    fn name(&self) -> &'static str {
-        match self {
-            MoonPhase::New => "New Moon",
-            MoonPhase::WaxingCrescent => "Waxing Crescent",
-            MoonPhase::FirstQuarter => "First Quarter",
-            MoonPhase::WaxingGibbous => "Waxing Gibbous",
-            MoonPhase::Full => "Full Moon",
-            MoonPhase::WaningGibbous => "Waning Gibbous",
-            MoonPhase::LastQuarter => "Last Quarter",
-            MoonPhase::WaningCrescent => "Waning Crescent",
+    /// The starting shadow mode from `--hide-dark` / `--bg-shadow`.
+    fn shadow_mode(&self) -> ShadowMode {
+        if self.hide_dark {
+            ShadowMode::Hidden
+        } else if self.bg_shadow {
+            ShadowMode::Outline
+        } else {
+            ShadowMode::Full
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Language {
-    English = 0,
-    Chinese = 1,
-    French = 2,
-    Japanese = 3,
-    Spanish = 4,
+/// Failures surfaced to the shell, each with a stable process exit code:
+///
+/// | Code | Meaning                                                       |
+/// |------|---------------------------------------------------------------|
+/// | 0    | Success (including quitting the TUI with `q`)                 |
+/// | 1    | I/O or terminal error                                         |
+/// | 2    | Invalid command-line usage (reported by clap)                 |
+/// | 3    | Invalid date or date range (`--date`, `--from`/`--to`)        |
+/// | 4    | `--self-test` found a wrong result                            |
+#[derive(Debug)]
+enum AppError {
+    Io(io::Error),
+    InvalidDate(String),
+    /// Number of failed `--self-test` cases.
+    SelfTestFailed(usize),
 }
 
-impl Language {
-    fn next(&self) -> Self {
+impl AppError {
+    fn exit_code(&self) -> ExitCode {
         match self {
-            Language::English => Language::Chinese,
-            Language::Chinese => Language::French,
-            Language::French => Language::Japanese,
-            Language::Japanese => Language::Spanish,
-            Language::Spanish => Language::English,
+            AppError::Io(_) => ExitCode::from(1),
+            AppError::InvalidDate(_) => ExitCode::from(3),
+            AppError::SelfTestFailed(_) => ExitCode::from(4),
         }
     }
-    
-    fn name(&self) -> &'static str {
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Language::English => "English",
-            Language::Chinese => "中文",
-            Language::French => "Français",
-            Language::Japanese => "日本語",
-            Language::Spanish => "Español",
+            AppError::Io(err) => write!(f, "{err}"),
+            AppError::InvalidDate(msg) => write!(f, "invalid date: {msg}"),
+            AppError::SelfTestFailed(n) => write!(f, "self-test failed ({n} case(s))"),
         }
     }
 }
 
-struct Feature {
-    names: [&'static str; 5],
-    lat: f64,
-    lon: f64,
+impl From<io::Error> for AppError {
+    fn from(err: io::Error) -> Self {
+        AppError::Io(err)
+    }
 }
 
-const LUNAR_FEATURES: &[Feature] = &[
-    Feature { names: ["Oceanus Procellarum", "风暴洋", "Océan des Tempêtes", "嵐の大洋", "Océano de las Tormentas"], lat: 18.4, lon: -57.4 },
-    Feature { names: ["Mare Imbrium", "雨海", "Mer des Pluies", "雨の海", "Mar de las Lluvias"], lat: 32.8, lon: -25.6 },
-    Feature { names: ["Mare Serenitatis", "澄海", "Mer de la Sérénité", "晴れの海", "Mar de la Serenidad"], lat: 20.0, lon: 13.5 },
-    Feature { names: ["Mare Tranquillitatis", "静海", "Mer de la Tranquillité", "静かの海", "Mar de la Tranquilidad"], lat: 3.5, lon: 22.4 },
-    Feature { names: ["Mare Crisium", "危海", "Mer des Crises", "危難の海", "Mar de las Crisis"], lat: 17.0, lon: 58.5 },
-    Feature { names: ["Tycho", "第谷", "Tycho", "ティコ", "Tycho"], lat: -43.3, lon: -11.2 },
-    Feature { names: ["Copernicus", "哥白尼", "Copernic", "コペルニクス", "Copérnico"], lat: 9.6, lon: -20.1 },
-    Feature { names: ["Kepler", "开普勒", "Kepler", "ケプラー", "Kepler"], lat: 8.1, lon: -38.0 },
-    Feature { names: ["Aristarchus", "阿里斯塔克斯", "Aristarque", "アリスタルコス", "Aristarco"], lat: 23.7, lon: -47.4 },
-    Feature { names: ["Plato", "柏拉图", "Platon", "プラトン", "Platón"], lat: 51.6, lon: -9.3 },
-];
+// Art height used by `--format ascii` when `--lines` isn't given.
+const DEFAULT_PRINT_LINES: u16 = 20;
+// Upper bound for `--lines`; beyond this the art is just upsampled noise.
+const MAX_PRINT_LINES: u16 = 500;
 
-struct MoonStatus {
-    phase: MoonPhase,
-    phase_fraction: f64, // 0.0 to 1.0 (0=New, 0.5=Full, 1.0=New)
-    age_days: f64,
-    illumination: f64,
+/// When the Moon next crosses a 30° longitude boundary into a new sign after `from`, and
+/// the sign it enters. Searched hourly (a sign takes about 2.5 days), then bisected to the minute.
+fn next_sign_ingress(from: DateTime<Utc>, precision: Precision) -> (DateTime<Utc>, usize) {
+    let sign_at = |t| calculate_moon_phase_with(t, precision).zodiac_sign();
+    let sign = sign_at(from);
+    let mut t = from;
+    // Four days is comfortably more than the slowest pass through a sign.
+    while t - from < Duration::days(4) {
+        let next = t + Duration::hours(1);
+        if sign_at(next) != sign {
+            let (mut lo, mut hi) = (t, next);
+            while hi - lo > Duration::minutes(1) {
+                let mid = lo + (hi - lo) / 2;
+                if sign_at(mid) == sign { lo = mid } else { hi = mid }
+            }
+            return (hi, sign_at(hi));
+        }
+        t = next;
+    }
+    (t, sign_at(t))
 }
 
-fn normalize_degrees(mut deg: f64) -> f64 {
-    deg %= 360.0;
-    if deg < 0.0 {
-        deg += 360.0;
+/// A short countdown such as "2d 5h", "6h" or "40m".
+fn format_countdown(d: Duration) -> String {
+    let minutes = d.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{minutes}m"),
+        (0, _) => format!("{hours}h"),
+        _ => format!("{days}d {hours}h"),
     }
-    deg
 }
 
-fn deg_to_rad(deg: f64) -> f64 {
-    deg * std::f64::consts::PI / 180.0
+/// Change in illumination (percentage points) over the next 24 hours.
+fn illumination_delta_per_day(date: DateTime<Utc>, precision: Precision) -> f64 {
+    calculate_moon_phase_with(date + Duration::days(1), precision).illumination
+        - calculate_moon_phase_with(date, precision).illumination
 }
 
-fn julian_day_utc(dt: DateTime<Utc>) -> f64 {
-    // Unix epoch (1970-01-01T00:00:00Z) is JD 2440587.5
-    let unix = dt.timestamp() as f64 + (dt.timestamp_subsec_nanos() as f64) * 1e-9;
-    unix / 86400.0 + 2440587.5
+fn format_illumination_delta(delta: f64) -> String {
+    let arrow = if delta >= 0.0 { '↑' } else { '↓' };
+    format!("{} {:+.1}%/day", arrow, delta)
 }
 
-fn calculate_moon_phase(date: DateTime<Utc>) -> MoonStatus {
-    // This uses a common Meeus-style approximation:
-    // compute Sun and Moon ecliptic longitudes and take their elongation.
-    // This is far more accurate than assuming a constant-length synodic month.
-    let jd = julian_day_utc(date);
-    let d = jd - 2451545.0; // days since J2000.0
+/// Illumination percent as shown everywhere (TUI, print mode, JSON, CSV): one decimal place,
+/// ties to even, so every output agrees on the same figure.
+fn round_illumination(percent: f64) -> f64 {
+    round_half_even(percent, 1)
+}
 
-    // Sun (approx): mean longitude L and mean anomaly g
-    let l0 = normalize_degrees(280.460 + 0.9856474 * d);
-    let g = normalize_degrees(357.528 + 0.9856003 * d);
-    let lambda_sun = normalize_degrees(
-        l0 + 1.915 * deg_to_rad(g).sin() + 0.020 * deg_to_rad(2.0 * g).sin(),
-    );
+/// Moon age in days as shown everywhere: one decimal place, ties to even.
+fn round_age(days: f64) -> f64 {
+    round_half_even(days, 1)
+}
 
-    // Moon (approx): mean longitude l, mean anomaly Mm, mean elongation D, argument of latitude F
-    let l = normalize_degrees(218.316 + 13.176396 * d);
-    let mm = normalize_degrees(134.963 + 13.064993 * d);
-    let d_moon = normalize_degrees(297.850 + 12.190749 * d);
-    let f = normalize_degrees(93.272 + 13.229350 * d);
-
-    // Moon longitude with a set of major periodic terms (degrees)
-    let lambda_moon = normalize_degrees(
-        l + 6.289 * deg_to_rad(mm).sin()
-            + 1.274 * deg_to_rad(2.0 * d_moon - mm).sin()
-            + 0.658 * deg_to_rad(2.0 * d_moon).sin()
-            + 0.214 * deg_to_rad(2.0 * mm).sin()
-            - 0.186 * deg_to_rad(g).sin()
-            - 0.059 * deg_to_rad(2.0 * d_moon - 2.0 * mm).sin()
-            - 0.057 * deg_to_rad(2.0 * d_moon - mm - g).sin()
-            + 0.053 * deg_to_rad(2.0 * d_moon + mm).sin()
-            + 0.046 * deg_to_rad(2.0 * d_moon - g).sin()
-            + 0.041 * deg_to_rad(mm - g).sin()
-            - 0.035 * deg_to_rad(d_moon).sin()
-            - 0.031 * deg_to_rad(mm + g).sin()
-            - 0.015 * deg_to_rad(2.0 * f - 2.0 * d_moon).sin()
-            + 0.011 * deg_to_rad(2.0 * d_moon - 4.0 * mm).sin(),
-    );
+fn round_half_even(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    (value * scale).round_ties_even() / scale
+}
 
-    // Elongation (0..360): 0=new, 180=full
-    let elongation_deg = normalize_degrees(lambda_moon - lambda_sun);
-    let phase_fraction = elongation_deg / 360.0;
-
-    // Express "age" in days using the mean synodic month (good enough for display).
-    let age = phase_fraction * SYNODIC_MONTH;
-
-    let segment = (phase_fraction * 8.0).round() as i32 % 8;
-    let phase = match segment {
-        0 => MoonPhase::New,
-        1 => MoonPhase::WaxingCrescent,
-        2 => MoonPhase::FirstQuarter,
-        3 => MoonPhase::WaxingGibbous,
-        4 => MoonPhase::Full,
-        5 => MoonPhase::WaningGibbous,
-        6 => MoonPhase::LastQuarter,
-        7 => MoonPhase::WaningCrescent,
-        _ => MoonPhase::New,
-    };
+// Wrap a rotation to -180..180 so the Details readout stays readable.
+fn normalize_rotation(deg: f64) -> f64 {
+    let deg = normalize_degrees(deg);
+    if deg > 180.0 { deg - 360.0 } else { deg }
+}
 
-    let illumination = 0.5 * (1.0 - deg_to_rad(elongation_deg).cos());
+// How long a transient status line stays up in the TUI.
+const STATUS_MESSAGE_DURATION: std::time::Duration = std::time::Duration::from_secs(2);
 
-    MoonStatus {
-        phase,
-        phase_fraction,
-        age_days: age,
-        illumination: illumination * 100.0,
+/// Step `date` by whole days, refusing to move further outside `SUPPORTED_YEARS`.
+///
+/// Stepping back toward the range from outside it is always allowed.
+fn step_date(date: DateTime<Utc>, days: i64) -> Result<DateTime<Utc>, &'static str> {
+    let stepped = date + Duration::days(days);
+    let year = stepped.year();
+    if days < 0 && year < *SUPPORTED_YEARS.start() {
+        Err("Reached the earliest supported date")
+    } else if days > 0 && year > *SUPPORTED_YEARS.end() {
+        Err("Reached the latest supported date")
+    } else {
+        Ok(stepped)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ascii_moon::{calculate_moon_phase, render_moon, terminator_fraction, MoonError};
     use chrono::TimeZone;
 
     #[test]
@@ -415,872 +775,4204 @@ mod tests {
             moon.illumination
         );
     }
-}
 
-struct MoonWidget {
-    status: MoonStatus,
-    show_labels: bool,
-    language: Language,
-    hide_dark: bool,
-    truecolor: bool,
-}
+    #[test]
+    fn libration_stays_within_physical_bounds() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        for day in 0..60 {
+            let moon = calculate_moon_phase(start + Duration::days(day));
+            assert!(moon.libration_lon.abs() <= 8.5, "lon {:.2}", moon.libration_lon);
+            assert!(moon.libration_lat.abs() <= 6.7, "lat {:.2}", moon.libration_lat);
+        }
+    }
 
-#[derive(Debug, Clone)]
-struct PoemViewState {
-    poem: Poem,
-    glow_phase: u64,
-    last_anim: Instant,
-    twinkle_seed: u64,
-    twinkles: Vec<Twinkle>,
-    // Fade-in state for poem body lines: 0..=LINE_FADE_STEPS.
-    line_fade: Vec<u8>,
-    fade_idx: usize,
-    fade_step: u8,
-    last_fade: Instant,
-    fade_pause_until: Option<Instant>,
-}
+    #[test]
+    fn range_summary_reports_extremes_and_the_dominant_phase() {
+        let (from, to) = parse_range_arg("2025-03-07..2025-03-16").unwrap();
+        let summary = summarize_range(from, to, Precision::Standard);
+        assert_eq!(summary.days(), 10);
+        assert!(summary.min_illumination < summary.mean_illumination);
+        assert!(summary.mean_illumination < summary.max_illumination && summary.max_illumination <= 100.0);
+        let daily: Vec<f64> = from
+            .iter_days()
+            .take(10)
+            .map(|d| calculate_moon_phase(midday_utc(d)).illumination)
+            .collect();
+        assert_eq!(summary.mean_illumination, daily.iter().sum::<f64>() / 10.0);
+        assert_eq!(summary.phases.first().map(|p| p.0), Some(MoonPhase::FirstQuarter));
+        assert_eq!(summary.dominant_phase(), MoonPhase::Full);
 
-#[derive(Debug, Clone)]
-struct Twinkle {
-    // Stored as coordinates relative to the poem inner area (0..w-1, 0..h-1).
-    x: u16,
-    y: u16,
-    ttl: u16,
-    kind: u8,
-}
+        let json = summary_json(&summary);
+        assert!(json.starts_with("{\"from\":\"2025-03-07\",\"to\":\"2025-03-16\",\"days\":10,"), "{json}");
+        assert!(json.contains("\"dominant_phase\":\"Full Moon\""), "{json}");
+        assert!(summary_text(&summary).contains("mostly Full Moon"));
 
-fn lcg_next_u32(seed: &mut u64) -> u32 {
-    // Simple LCG for deterministic twinkles; good enough for UI.
-    *seed = seed
-        .wrapping_mul(6364136223846793005u64)
-        .wrapping_add(1442695040888963407u64);
-    (*seed >> 32) as u32
-}
+        assert!(matches!(parse_range_arg("2025-03-16..2025-03-07"), Err(AppError::InvalidDate(_))));
+        assert!(matches!(parse_range_arg("2025-03-07"), Err(AppError::InvalidDate(_))));
+        assert!(Args::try_parse_from(["ascii_moon", "--summary"]).is_err());
+        assert!(Args::try_parse_from(["ascii_moon", "--date-range", "2025-03-07..2025-03-09"]).is_err());
+    }
 
-fn soft_palette(glow_phase: u64) -> (Color, Color, Color) {
-    // A calm, romantic palette (lavender / moonlight / blush).
-    // We keep it subtle and avoid rapid cycling.
-    let step = (glow_phase / 12) % 3;
-    match step {
-        0 => (
-            Color::Rgb(245, 223, 235), // title: blush
-            Color::Rgb(206, 204, 235), // body: lavender
-            Color::Rgb(170, 180, 210), // dim: mist
-        ),
-        1 => (
+    #[test]
+    fn interval_phases_lists_only_the_days_the_phase_changes() {
+        let (from, to) = parse_range_arg("2025-03-01..2025-03-31").unwrap();
+        let changes = phase_changes(from, to, Precision::Standard);
+        assert_eq!(changes[0].0, from);
+        assert!(changes.windows(2).all(|w| w[0].1 != w[1].1 && w[0].0 < w[1].0));
+        // Each day between two listed ones has the earlier listed phase.
+        for w in changes.windows(2) {
+            for day in w[0].0.iter_days().take_while(|d| *d < w[1].0) {
+                assert_eq!(calculate_moon_phase(midday_utc(day)).phase, w[0].1, "{day}");
+            }
+        }
+        // A month passes through all eight phases.
+        assert!(changes.len() >= 8, "{changes:?}");
+        assert!(changes.iter().any(|&(d, p)| p == MoonPhase::Full && d.day() == 12), "{changes:?}");
+
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        assert_eq!(phase_changes(day, day, Precision::Standard), vec![(day, MoonPhase::Full)]);
+        assert_eq!(phase_changes_text(&phase_changes(day, day, Precision::Standard)), "2025-03-14  Full Moon");
+        assert_eq!(
+            phase_changes_json(day, day, &phase_changes(day, day, Precision::Standard)),
+            "{\"from\":\"2025-03-14\",\"to\":\"2025-03-14\",\"changes\":[{\"date\":\"2025-03-14\",\"phase\":\"Full Moon\"}]}"
+        );
+    }
+
+    #[test]
+    fn csv_has_header_and_one_row_per_day() {
+        let from = NaiveDate::from_ymd_opt(2025, 12, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+        let mut out = Vec::new();
+        write_csv(&mut out, from, to, Precision::Standard).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let mut rows = text.lines();
+        assert_eq!(
+            rows.next(),
+            Some("date,phase,phase_fraction,age_days,illumination,is_waxing")
+        );
+        let rows: Vec<&str> = rows.collect();
+        assert_eq!(rows.len(), 31);
+        assert!(rows[0].starts_with("2025-12-01,"));
+        assert!(rows[30].starts_with("2025-12-31,"));
+        assert!(rows.iter().all(|r| r.split(',').count() == 6));
+    }
+
+    #[test]
+    fn refresh_period_prefers_tick_seconds_and_zero_disables() {
+        use std::time::Duration as StdDuration;
+        assert_eq!(refresh_period(5, None), Some(StdDuration::from_secs(300)));
+        assert_eq!(refresh_period(0, None), None);
+        assert_eq!(refresh_period(5, Some(1.5)), Some(StdDuration::from_millis(1500)));
+        assert_eq!(refresh_period(5, Some(0.0)), None);
+    }
+
+    #[test]
+    fn slideshow_period_is_bounded_both_ways() {
+        assert_eq!(parse_slide_seconds("10"), Ok(10.0));
+        assert!(parse_slide_seconds("0.1").is_err());
+        assert!(parse_slide_seconds("1e300").is_err());
+        assert!(Args::try_parse_from(["ascii_moon", "--loop-dates", "1e300"]).is_err());
+        let week = (MAX_REFRESH_MINUTES * 60).to_string();
+        assert!(Args::try_parse_from(["ascii_moon", "--loop-dates", week.as_str()]).is_ok());
+    }
+
+    #[test]
+    fn poll_timeout_caps_at_animation_rate_only_while_animating() {
+        use std::time::Duration as StdDuration;
+        let hour = Some(StdDuration::from_secs(3600));
+        assert_eq!(poll_timeout(hour, StdDuration::from_secs(600), false), StdDuration::from_secs(3000));
+        assert_eq!(poll_timeout(hour, StdDuration::from_secs(600), true), ANIM_RATE);
+        assert_eq!(poll_timeout(hour, StdDuration::from_secs(7200), false), StdDuration::ZERO);
+        assert_eq!(poll_timeout(None, StdDuration::ZERO, false), IDLE_POLL);
+    }
+
+    #[test]
+    fn blank_wait_counts_down_from_the_last_keypress() {
+        use std::time::Duration as StdDuration;
+        assert_eq!(blank_wait(None, StdDuration::from_secs(86_400)), None);
+        assert_eq!(blank_wait(Some(5), StdDuration::from_secs(60)), Some(StdDuration::from_secs(240)));
+        assert_eq!(blank_wait(Some(5), StdDuration::from_secs(300)), Some(StdDuration::ZERO));
+        assert_eq!(blank_wait(Some(5), StdDuration::from_secs(900)), Some(StdDuration::ZERO));
+        assert!(Args::try_parse_from(["ascii_moon", "--blank-after", "0"]).is_err());
+    }
+
+    #[test]
+    fn hemisphere_defaults_from_latitude_unless_given() {
+        let hemisphere = |argv: &[&str]| {
+            let args = Args::try_parse_from([&["ascii_moon"], argv].concat()).unwrap();
+            Hemisphere::from_args(&args)
+        };
+        assert_eq!(hemisphere(&[]), Hemisphere::North);
+        assert_eq!(hemisphere(&["--lat", "-33.9", "--lon", "151.2"]), Hemisphere::South);
+        assert_eq!(hemisphere(&["--lat", "51.5", "--lon", "0"]), Hemisphere::North);
+        assert_eq!(hemisphere(&["--lat", "-33.9", "--lon", "151.2", "--hemisphere", "north"]), Hemisphere::North);
+        assert_eq!(hemisphere(&["--hemisphere", "south"]), Hemisphere::South);
+    }
+
+    #[test]
+    fn frame_wait_spaces_draws_by_max_fps() {
+        use std::time::Duration as StdDuration;
+        assert_eq!(frame_wait(None, 30), StdDuration::ZERO);
+        assert_eq!(frame_wait(Some(StdDuration::from_millis(40)), 10), StdDuration::from_millis(60));
+        assert_eq!(frame_wait(Some(StdDuration::from_millis(200)), 10), StdDuration::ZERO);
+        assert!(Args::try_parse_from(["ascii_moon", "--max-fps", "0"]).is_err());
+        assert!(Args::try_parse_from(["ascii_moon", "--max-fps", "60"]).is_ok());
+    }
+
+    #[test]
+    fn refresh_arguments_are_range_checked() {
+        assert!(Args::try_parse_from(["ascii_moon", "--refresh-minutes", "20000"]).is_err());
+        assert!(Args::try_parse_from(["ascii_moon", "--tick-seconds", "0.2"]).is_err());
+        assert!(Args::try_parse_from(["ascii_moon", "--tick-seconds", "NaN"]).is_err());
+        assert!(Args::try_parse_from(["ascii_moon", "--tick-seconds", "2.5"]).is_ok());
+    }
+
+    #[test]
+    fn lines_argument_is_validated() {
+        assert!(Args::try_parse_from(["ascii_moon", "--lines", "0"]).is_err());
+        assert!(Args::try_parse_from(["ascii_moon", "--lines", "501"]).is_err());
+        let args = Args::try_parse_from(["ascii_moon", "--lines", "1"]).unwrap();
+        let dt = Utc.with_ymd_and_hms(2025, 12, 4, 23, 14, 0).unwrap();
+        let buffer = print_buffer(1, print_widget(&args, dt), 80);
+        assert_eq!(buffer.area.height, 1);
+        assert!(buffer.area.width >= 1);
+        // Even a zero-width terminal report yields a sliver.
+        assert_eq!(print_buffer(1, print_widget(&args, dt), 0).area.width, 1);
+    }
+
+    #[test]
+    fn both_hemispheres_print_mirrored_views_side_by_side() {
+        // First quarter: lit on the right from the north, on the left from the south.
+        let quarter = Utc.with_ymd_and_hms(2025, 3, 6, 16, 32, 0).unwrap();
+        let args = Args::parse_from(["ascii_moon", "--both-hemispheres", "--dumb-terminal"]);
+        let mut out = Vec::new();
+        print_hemispheres(&mut out, 12, print_widget(&args, quarter), 80).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let rows: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
+        assert_eq!(rows.len(), 13);
+        let width = rows[0].len();
+        assert!(width <= 80 && rows.iter().all(|r| r.len() == width));
+        let view = (width - HEMISPHERE_GAP as usize) / 2;
+        assert!(text.lines().next().unwrap().contains("Northern hemisphere"));
+        assert!(text.lines().next().unwrap().contains("Southern hemisphere"));
+
+        let lit = |from: usize, to: usize| rows[1..].iter().map(|r| r[from..to].iter().filter(|c| **c != ' ').count()).sum::<usize>();
+        let (north, south) = (view + HEMISPHERE_GAP as usize, width);
+        assert!(lit(view / 2, view) > 3 * lit(0, view / 2));
+        assert!(lit(north, north + view / 2) > 3 * lit(north + view / 2, south));
+
+        assert!(Args::try_parse_from(["ascii_moon", "--both-hemispheres", "--hemisphere", "south"]).is_err());
+    }
+
+    #[test]
+    fn printed_moon_fits_the_assumed_width() {
+        let dt = Utc.with_ymd_and_hms(2025, 12, 4, 23, 14, 0).unwrap();
+        for extra in [&[][..], &["--dumb-terminal"], &["--light-bg"]] {
+            let args = Args::parse_from(["ascii_moon", "--lines", "80", "--assume-cols", "40"].iter().chain(extra));
+            assert_eq!(print_columns(&args), 40);
+            let mut out = Vec::new();
+            print_moon(&mut out, 80, print_widget(&args, dt), print_columns(&args)).unwrap();
+            let text = String::from_utf8(out).unwrap();
+            let mut widest = 0;
+            for line in text.lines() {
+                // Drop the `ESC [ ... m` colour sequences before measuring.
+                let mut visible = String::new();
+                let mut chars = line.chars();
+                while let Some(c) = chars.next() {
+                    if c == '\x1b' {
+                        chars.by_ref().find(|&c| c == 'm');
+                    } else {
+                        visible.push(c);
+                    }
+                }
+                assert_eq!(visible.chars().count(), visible.width(), "{visible:?}");
+                widest = widest.max(visible.width());
+            }
+            assert_eq!(widest, 40, "{extra:?}");
+        }
+    }
+
+    #[test]
+    fn principal_phases_are_reported_once_on_entry() {
+        use MoonPhase::*;
+        assert_eq!(principal_phase_reached(WaxingGibbous, Full), Some(Full));
+        assert_eq!(principal_phase_reached(WaningCrescent, New), Some(New));
+        assert_eq!(principal_phase_reached(Full, Full), None);
+        assert_eq!(principal_phase_reached(Full, WaningGibbous), None);
+    }
+
+    #[test]
+    fn feature_selection_wraps_both_ways() {
+        let n = LUNAR_FEATURES.len();
+        assert_eq!(cycle_index(None, n, true), 0);
+        assert_eq!(cycle_index(None, n, false), n - 1);
+        assert_eq!(cycle_index(Some(n - 1), n, true), 0);
+        assert_eq!(cycle_index(Some(0), n, false), n - 1);
+    }
+
+    #[test]
+    fn sequential_poems_wrap_and_restart_per_language() {
+        let poem = |title: &str, phase: &str| Poem {
+            title: title.to_string(),
+            author: String::new(),
+            lines: vec!["line".to_string()],
+            phases: if phase.is_empty() { Vec::new() } else { vec![phase.to_string()] },
+        };
+        let poems = [poem("a", ""), poem("b", "full"), poem("c", "")];
+        let en = Language::English;
+        assert_eq!(next_in_sequence(&poems, None, en, None), Some(0));
+        assert_eq!(next_in_sequence(&poems, Some((en, 0)), en, None), Some(1));
+        assert_eq!(next_in_sequence(&poems, Some((en, 2)), en, None), Some(0));
+        assert_eq!(next_in_sequence(&poems, Some((Language::French, 1)), en, None), Some(0));
+        assert_eq!(next_in_sequence(&poems, Some((en, 1)), en, Some(MoonPhase::Full)), Some(1));
+        // No tagged poem: just the next one.
+        assert_eq!(next_in_sequence(&poems, Some((en, 0)), en, Some(MoonPhase::New)), Some(1));
+        assert_eq!(next_in_sequence(&[], None, en, None), None);
+    }
+
+    #[test]
+    fn poem_search_cycles_only_among_matches() {
+        let poem = |title: &str, author: &str, line: &str| Poem {
+            title: title.to_string(),
+            author: author.to_string(),
+            lines: vec![line.to_string()],
+            phases: Vec::new(),
+        };
+        let poems = [
+            poem("Quiet Night Thought", "Li Bai", "moonlight before my bed"),
+            poem("To the Moon", "Shelley", "art thou pale"),
+            poem("Drinking Alone", "Li Bai", "a pot of wine"),
+        ];
+        let en = Language::English;
+        assert_eq!(next_match(&poems, None, en, "li bai", true), Some(0));
+        assert_eq!(next_match(&poems, Some((en, 0)), en, "li bai", true), Some(2));
+        assert_eq!(next_match(&poems, Some((en, 2)), en, "li bai", true), Some(0));
+        assert_eq!(next_match(&poems, Some((en, 0)), en, "li bai", false), Some(2));
+        assert_eq!(next_match(&poems, Some((en, 0)), en, "PALE", true), Some(1));
+        // A lone match is found again from itself; no match at all is `None`.
+        assert_eq!(next_match(&poems, Some((en, 1)), en, "shelley", true), Some(1));
+        assert_eq!(next_match(&poems, None, en, "basho", true), None);
+        // The empty query is the whole library, so `O` without a search is just "previous".
+        assert_eq!(next_match(&poems, Some((en, 0)), en, "", false), Some(2));
+    }
+
+    #[test]
+    fn empty_languages_get_a_localized_placeholder() {
+        let empty = PoemLibrary::default();
+        let poem = pick_poem(&empty, Language::French, None);
+        assert_eq!(poem.title, "Lune");
+        assert!(poem.lines[0].starts_with("Aucun poème"), "{:?}", poem.lines);
+        let poem = pick_poem(&empty, Language::English, Some(MoonPhase::Full));
+        assert!(poem.lines[0].contains("poems directory"), "{:?}", poem.lines);
+
+        let mut state = test_state(Utc.with_ymd_and_hms(2025, 12, 4, 12, 0, 0).unwrap()).poem_state;
+        show_next_poem(&mut state, &empty, Language::Japanese, None, PoemOrder::Sequential);
+        assert_eq!(state.poem.lines[0], NO_POEMS[Language::Japanese as usize][1]);
+    }
+
+    #[test]
+    fn full_moon_flash_fires_on_the_upward_crossing_only() {
+        assert!(full_moon_reached(99.85, 99.95));
+        assert!(!full_moon_reached(99.95, 99.99));
+        assert!(!full_moon_reached(99.95, 99.85));
+        assert!(!full_moon_reached(80.0, 90.0));
+    }
+
+    #[test]
+    fn disc_box_is_centered_and_rejects_tiny_areas() {
+        let b = disc_box(Rect::new(0, 0, 80, 20), CellRatio::default(), None).unwrap();
+        assert_eq!((b.draw_w, b.draw_h), (40.0, 20.0));
+        assert_eq!((b.start_x, b.start_y), (20.0, 0.0));
+        assert!(disc_box(Rect::new(0, 0, 1, 1), CellRatio::default(), None).is_none());
+        let capped = disc_box(Rect::new(0, 0, 300, 100), CellRatio::default(), Some(60)).unwrap();
+        assert_eq!((capped.draw_w, capped.draw_h), (60.0, 30.0));
+        assert_eq!((capped.start_x, capped.start_y), (120.0, 35.0));
+    }
+
+    #[test]
+    fn illumination_overlay_sits_below_the_disc_when_there_is_room() {
+        let area = Rect::new(0, 0, 300, 100);
+        let capped = disc_box(area, CellRatio::default(), Some(60)).unwrap();
+        assert_eq!(overlay_row(area, capped), 65);
+        // A disc filling the pane's height: fall back to its bottom row.
+        let full = Rect::new(0, 0, 80, 20);
+        assert_eq!(overlay_row(full, disc_box(full, CellRatio::default(), None).unwrap()), 19);
+    }
+
+    #[test]
+    fn details_move_beside_the_moon_on_ultrawide_screens() {
+        let (main, info, beside) = tui_layout(Rect::new(0, 0, 300, 40), true, CellRatio::default());
+        assert!(beside);
+        assert_eq!(main.height, info.height);
+        assert!(main.width > info.width);
+        let (main, info, beside) = tui_layout(Rect::new(0, 0, 100, 40), true, CellRatio::default());
+        assert!(!beside);
+        assert_eq!(main.width, info.width);
+        assert!(!tui_layout(Rect::new(0, 0, 300, 40), false, CellRatio::default()).2);
+    }
+
+    #[test]
+    fn dump_art_marks_the_crop_box() {
+        let art: Vec<Vec<char>> = ["      ", "  ##  ", " #### ", "      "].iter().map(|l| l.chars().collect()).collect();
+        let mut out = Vec::new();
+        dump_art(&mut out, &art).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[1], "# crop: columns 1..=4, rows 1..=2 (4 x 2 chars, aspect 2.000)");
+        assert_eq!(lines[2], "   v  v ");
+        assert_eq!(&lines[3..7], ["        ", "|   ##  ", "|  #### ", "        "]);
+        assert_eq!(lines[7], "   ^  ^ ");
+    }
+
+    #[test]
+    fn display_rounding_is_one_decimal_ties_to_even() {
+        assert_eq!(round_illumination(72.25), 72.2);
+        assert_eq!(round_illumination(72.75), 72.8);
+        assert_eq!(round_illumination(72.26), 72.3);
+        assert_eq!(round_illumination(99.96), 100.0);
+        assert_eq!(round_age(0.25), 0.2);
+        assert_eq!(round_age(14.75), 14.8);
+        assert_eq!(round_age(29.53), 29.5);
+        // The printed figure is the rounded one, whatever the formatter would do on its own.
+        assert_eq!(format!("{:.1}", round_illumination(0.125)), "0.1");
+    }
+
+    #[test]
+    fn phase_angle_runs_from_new_to_full_and_back() {
+        let full = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 3, 14, 6, 55, 0).unwrap());
+        assert!(full.phase_angle() > 178.0, "{}", full.phase_angle());
+        let new = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 3, 29, 10, 58, 0).unwrap());
+        assert!(new.phase_angle() < 2.0, "{}", new.phase_angle());
+        let first = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 3, 6, 16, 32, 0).unwrap());
+        let last = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 3, 22, 11, 29, 0).unwrap());
+        assert!((first.phase_angle() - 90.0).abs() < 2.0 && (last.phase_angle() - 90.0).abs() < 2.0);
+        assert!((first.elongation_deg / 360.0 - first.phase_fraction).abs() < 1e-12);
+    }
+
+    #[test]
+    fn forecast_names_each_following_day() {
+        let date = Utc.with_ymd_and_hms(2025, 3, 9, 12, 0, 0).unwrap();
+        let forecast = describe_forecast(date, 3, Precision::Standard);
+        let sentences: Vec<&str> = forecast.split("). ").collect();
+        assert_eq!(sentences.len(), 3, "{forecast}");
+        assert!(sentences[0].starts_with("Tomorrow: Waxing Gibbous ("), "{forecast}");
+        assert!(sentences[1].starts_with("In 2 days: "), "{forecast}");
+        assert!(sentences[2].starts_with("In 3 days: Full Moon (") && forecast.ends_with("%)."), "{forecast}");
+
+        let args = Args::parse_from(["ascii_moon", "--forecast", "2"]);
+        assert_eq!(args.output_format(), Some(OutputFormat::Describe));
+        assert!(Args::try_parse_from(["ascii_moon", "--forecast", "2", "--json"]).is_err());
+    }
+
+    #[test]
+    fn phase_from_fraction_has_eighth_cycle_buckets() {
+        let cases = [
+            (0.0, MoonPhase::New),
+            (0.0624, MoonPhase::New),
+            (0.0625, MoonPhase::WaxingCrescent),
+            (0.1875, MoonPhase::FirstQuarter),
+            (0.25, MoonPhase::FirstQuarter),
+            (0.3125, MoonPhase::WaxingGibbous),
+            (0.4375, MoonPhase::Full),
+            (0.5, MoonPhase::Full),
+            (0.5624, MoonPhase::Full),
+            (0.5625, MoonPhase::WaningGibbous),
+            (0.75, MoonPhase::LastQuarter),
+            (0.875, MoonPhase::WaningCrescent),
+            (0.9375, MoonPhase::New),
+            (0.9999, MoonPhase::New),
+            // Out-of-range fractions wrap around the cycle.
+            (1.0, MoonPhase::New),
+            (1.5, MoonPhase::Full),
+            (-0.25, MoonPhase::LastQuarter),
+        ];
+        for (f, phase) in cases {
+            assert_eq!(MoonPhase::from_fraction(f), phase, "{f}");
+        }
+        assert_eq!(MoonPhase::from_fraction(f64::NAN), MoonPhase::New);
+    }
+
+    #[test]
+    fn watch_phase_finds_the_next_entry_into_a_phase() {
+        assert_eq!("Full Moon".parse::<MoonPhase>(), Ok(MoonPhase::Full));
+        assert_eq!("waxing_crescent".parse::<MoonPhase>(), Ok(MoonPhase::WaxingCrescent));
+        assert_eq!("LAST QUARTER".parse::<MoonPhase>(), Ok(MoonPhase::LastQuarter));
+        assert!("blue".parse::<MoonPhase>().unwrap_err().contains("waning-gibbous"));
+
+        // Principal phases land on the moment itself: the 14 March 2025 full moon was at 06:55 UTC.
+        let from = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        let full = next_phase_start(from, MoonPhase::Full, Precision::Standard).unwrap();
+        assert!((full - Utc.with_ymd_and_hms(2025, 3, 14, 6, 55, 0).unwrap()).num_minutes().abs() < 30, "{full}");
+        let next = next_phase_start(full, MoonPhase::Full, Precision::Standard).unwrap();
+        assert!(next - full > Duration::days(28));
+
+        // The others: the moment the Moon enters them, and a month on if it's already there.
+        let crescent = next_phase_start(from, MoonPhase::WaningCrescent, Precision::Standard).unwrap();
+        assert_eq!(calculate_moon_phase(crescent).phase, MoonPhase::WaningCrescent);
+        assert_ne!(calculate_moon_phase(crescent - Duration::minutes(1)).phase, MoonPhase::WaningCrescent);
+        let again = next_phase_start(crescent + Duration::hours(1), MoonPhase::WaningCrescent, Precision::Standard).unwrap();
+        assert!(again - crescent > Duration::days(28));
+    }
+
+    #[test]
+    fn last_date_round_trips_and_resumes_following_now() {
+        let path = std::env::temp_dir().join(format!("ascii_moon_last_date_{}", std::process::id())).join("last_date");
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let studied = Utc.with_ymd_and_hms(1969, 7, 20, 20, 17, 0).unwrap();
+        assert_eq!(load_last_date(&path, now), None);
+        save_last_date(&path, studied, false).unwrap();
+        assert_eq!(load_last_date(&path, now), Some((studied, false)));
+        save_last_date(&path, studied, true).unwrap();
+        assert_eq!(load_last_date(&path, now), Some((now, true)));
+        std::fs::write(&path, "garbage").unwrap();
+        assert_eq!(load_last_date(&path, now), None);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn trend_word_follows_the_label_language() {
+        let waxing = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 11, 24, 12, 0, 0).unwrap());
+        let waning = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 12, 8, 12, 0, 0).unwrap());
+        assert_eq!(waxing.trend_word(Language::English), "waxing");
+        assert_eq!(waning.trend_word(Language::Chinese), "亏");
+        assert_eq!(waning.trend_word(Language::French), "décroissante");
+        assert_eq!(waxing.trend_word(Language::Spanish), "creciente");
+    }
+
+    #[test]
+    fn illumination_delta_follows_waxing_and_waning() {
+        // Waxing crescent a few days after the 2025-11-20 new moon, waning after the 2025-12-04 full moon.
+        let waxing = Utc.with_ymd_and_hms(2025, 11, 24, 12, 0, 0).unwrap();
+        let waning = Utc.with_ymd_and_hms(2025, 12, 8, 12, 0, 0).unwrap();
+        assert!(illumination_delta_per_day(waxing, Precision::Standard) > 0.0);
+        assert!(illumination_delta_per_day(waning, Precision::Standard) < 0.0);
+        assert_eq!(format_illumination_delta(1.84), "↑ +1.8%/day");
+        assert_eq!(format_illumination_delta(-12.0), "↓ -12.0%/day");
+    }
+
+    #[test]
+    fn outline_shadow_draws_only_the_dark_limb() {
+        // Near new moon almost the whole disc is dark, so the ring should be nearly complete
+        // while the interior stays blank.
+        let dt = Utc.with_ymd_and_hms(2025, 11, 20, 6, 47, 0).unwrap();
+        let area = Rect::new(0, 0, 80, 40);
+        let mut buf = Buffer::empty(area);
+        MoonWidget {
+            status: calculate_moon_phase(dt),
+            options: RenderOptions { shadow: ShadowMode::Outline, ..test_options() },
+        }
+        .render(area, &mut buf);
+
+        let ring = (0..area.height)
+            .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| buf.get(x, y).fg == Color::DarkGray)
+            .count();
+        assert!(ring > 40, "only {ring} outline cells");
+        assert_eq!(buf.get(40, 20).symbol(), " ");
+    }
+
+    #[test]
+    fn disc_is_visually_round_for_cell_ratio() {
+        let dt = Utc.with_ymd_and_hms(2025, 12, 4, 23, 14, 0).unwrap();
+        for (ratio, area) in [("1:2", Rect::new(0, 0, 160, 40)), ("2:3", Rect::new(0, 0, 120, 60))] {
+            let cell_ratio: CellRatio = ratio.parse().unwrap();
+            let mut buf = Buffer::empty(area);
+            MoonWidget {
+                status: calculate_moon_phase(dt),
+                options: RenderOptions { cell_ratio, ..test_options() },
+            }
+            .render(area, &mut buf);
+
+            let drawn: Vec<(u16, u16)> = (0..area.height)
+                .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+                .filter(|&(x, y)| buf.get(x, y).fg != Color::Reset)
+                .collect();
+            let cols = drawn.iter().map(|p| p.0).max().unwrap() - drawn.iter().map(|p| p.0).min().unwrap() + 1;
+            let rows = drawn.iter().map(|p| p.1).max().unwrap() - drawn.iter().map(|p| p.1).min().unwrap() + 1;
+
+            // Visual size = cells * cell dimension; the two should agree within a couple of cells.
+            let visual_w = cols as f64 * cell_ratio.w;
+            let visual_h = rows as f64 * cell_ratio.h;
+            assert!(
+                (visual_w - visual_h).abs() <= 2.0 * cell_ratio.h,
+                "{ratio}: {cols}x{rows} cells is not round"
+            );
+        }
+        assert!("0:2".parse::<CellRatio>().is_err());
+        assert!("12".parse::<CellRatio>().is_err());
+    }
+
+    #[test]
+    fn terminator_fraction_tracks_the_drawn_boundary() {
+        // New: the terminator hugs the right limb; quarters: center; full: left limb.
+        assert!((terminator_fraction(0.0) - 1.0).abs() < 1e-9);
+        assert!((terminator_fraction(90.0) - 0.5).abs() < 1e-9);
+        assert!((terminator_fraction(180.0) - 0.0).abs() < 1e-9);
+        assert!((terminator_fraction(270.0) - 0.5).abs() < 1e-9);
+        // Waxing crescent: lit sliver on the right, so the line sits right of center.
+        assert!(terminator_fraction(45.0) > 0.5);
+        // Waning crescent: lit sliver on the left.
+        assert!(terminator_fraction(315.0) < 0.5);
+    }
+
+    #[test]
+    fn date_errors_map_to_their_exit_code() {
+        let err = parse_date_arg("2025-13-01").unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::from(3));
+        let err = parse_date_range("2025-02-01", "2025-01-01").unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::from(3));
+        assert!(parse_date_range("2025-01-01", "2025-01-01").is_ok());
+        for (from, to) in [("0001-01-01", "0001-01-03"), ("2025-01-01", "2500-01-01")] {
+            let err = parse_date_range(from, to).unwrap_err();
+            assert_eq!(err.exit_code(), ExitCode::from(3));
+        }
+        assert!(parse_range_arg("1000-01-01..9000-01-01").is_err());
+        assert!(parse_datetime_arg("9999-12-31").is_err());
+        assert!(parse_datetime_arg("0001-01-01T00:00").is_err());
+        for argv in [
+            &["ascii_moon", "--date", "2500-01-01", "--format", "name"][..],
+            &["ascii_moon", "--date", "1850-01-01", "--json"],
+            &["ascii_moon", "--date", "2100-12-30", "--days", "10"],
+        ] {
+            assert!(matches!(run(Args::parse_from(argv)), Err(AppError::InvalidDate(_))), "{argv:?}");
+        }
+        let err = check_supported_year(1899).unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::from(3));
+        assert!(check_supported_year(2101).is_err());
+        assert_eq!(check_supported_year(2025).unwrap(), 2025);
+        let err = AppError::from(io::Error::other("boom"));
+        assert_eq!(err.exit_code(), ExitCode::from(1));
+    }
+
+    #[test]
+    fn eclipse_reasoning_uses_latitude_near_syzygy() {
+        // Total lunar eclipse of 2025-03-14 (greatest eclipse 06:58 UTC).
+        let eclipse = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 3, 14, 6, 58, 0).unwrap());
+        assert!(eclipse.ecliptic_lat.abs() < LUNAR_ECLIPSE_LIMIT, "lat {:.2}", eclipse.ecliptic_lat);
+        assert!(eclipse_reasoning(&eclipse).starts_with("lunar eclipse possible"));
+        // The 2025-12-04 full moon had no eclipse.
+        let plain = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 12, 4, 23, 14, 0).unwrap());
+        assert!(eclipse_reasoning(&plain).starts_with("no lunar eclipse"), "{}", eclipse_reasoning(&plain));
+        let quarter = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 11, 28, 6, 58, 0).unwrap());
+        assert!(eclipse_reasoning(&quarter).starts_with("none"));
+    }
+
+    #[test]
+    fn declination_range_tracks_the_standstill_cycle() {
+        let year_range = |year: i32| {
+            let start = Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap();
+            let values: Vec<f64> = (0..365 * 4)
+                .map(|i| calculate_moon_phase(start + Duration::hours(6 * i)).declination)
+                .collect();
+            (
+                values.iter().cloned().fold(f64::MAX, f64::min),
+                values.iter().cloned().fold(f64::MIN, f64::max),
+            )
+        };
+        // 2025 is near a major lunar standstill (~±28.7°), 2015 near a minor one (~±18.2°).
+        let (min, max) = year_range(2025);
+        assert!(max > 28.0 && min < -28.0, "2025: {min:.2}..{max:.2}");
+        let (min, max) = year_range(2015);
+        assert!(max < 19.5 && min > -19.5, "2015: {min:.2}..{max:.2}");
+    }
+
+    #[test]
+    fn anchor_picks_the_time_of_day() {
+        let day = NaiveDate::from_ymd_opt(2025, 12, 4).unwrap();
+        let now = Utc.with_ymd_and_hms(2030, 1, 1, 7, 30, 0).unwrap();
+        let tokyo = chrono::FixedOffset::east_opt(9 * 3600).unwrap();
+        assert_eq!(anchored_instant(day, Anchor::Noon, &tokyo, now), Utc.with_ymd_and_hms(2025, 12, 4, 12, 0, 0).unwrap());
+        // Local midnight in UTC+9 is 15:00 UTC the day before.
+        assert_eq!(anchored_instant(day, Anchor::Midnight, &tokyo, now), Utc.with_ymd_and_hms(2025, 12, 3, 15, 0, 0).unwrap());
+        // 07:30 UTC is 16:30 in Tokyo; on the 4th that's 07:30 UTC on the 4th.
+        assert_eq!(anchored_instant(day, Anchor::Now, &tokyo, now), Utc.with_ymd_and_hms(2025, 12, 4, 7, 30, 0).unwrap());
+        assert_eq!(anchored_instant(day, Anchor::Midnight, &Utc, now), Utc.with_ymd_and_hms(2025, 12, 4, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn self_test_passes_on_this_build() {
+        let mut out = Vec::new();
+        assert_eq!(run_self_test(&mut out, Precision::Standard).unwrap(), 0);
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().count(), REFERENCE_CASES.len() + 1);
+        assert!(text.ends_with(&format!("PASS: {0}/{0} cases passed\n", REFERENCE_CASES.len())));
+    }
+
+    #[test]
+    fn tz_decides_where_the_displayed_day_rolls_over() {
+        assert_eq!(parse_tz("+09:00").unwrap().local_minus_utc(), 9 * 3600);
+        assert_eq!(parse_tz("-0530").unwrap().local_minus_utc(), -(5 * 3600 + 30 * 60));
+        assert_eq!(parse_tz("UTC").unwrap().local_minus_utc(), 0);
+        assert!(parse_tz("Europe/Paris").is_err());
+        assert!(parse_tz("+25").is_err());
+
+        // A fixed "now" of 20:00 UTC is already tomorrow in Tokyo and still today in New York.
+        let now = Utc.with_ymd_and_hms(2025, 12, 4, 20, 0, 0).unwrap();
+        let day = |tz: &str| in_display_tz(now, Some(parse_tz(tz).unwrap())).date_naive();
+        assert_eq!(day("+09:00"), NaiveDate::from_ymd_opt(2025, 12, 5).unwrap());
+        assert_eq!(day("-05:00"), NaiveDate::from_ymd_opt(2025, 12, 4).unwrap());
+        // One minute before Tokyo midnight it's still the 4th.
+        let before = Utc.with_ymd_and_hms(2025, 12, 4, 14, 59, 0).unwrap();
+        assert_eq!(in_display_tz(before, parse_tz("+09:00").ok()).date_naive(), NaiveDate::from_ymd_opt(2025, 12, 4).unwrap());
+    }
+
+    #[test]
+    fn ephemeris_values_are_physical() {
+        // Perigee of 2025-12-04 (~356,960 km) and apogee of 2025-12-17 (~406,320 km).
+        let near = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 12, 4, 11, 0, 0).unwrap());
+        let far = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 12, 17, 6, 0, 0).unwrap());
+        assert!((near.distance_km - 356_960.0).abs() < 1_500.0, "{:.0}", near.distance_km);
+        assert!((far.distance_km - 406_320.0).abs() < 1_500.0, "{:.0}", far.distance_km);
+        assert!(near.angular_diameter() > far.angular_diameter());
+
+        let at = Utc.with_ymd_and_hms(2025, 12, 13, 4, 0, 0).unwrap();
+        let json = ephemeris_json(at, Some(Observer { lat: 51.48, lon: 0.0 }), Precision::Standard);
+        for key in ["\"distance_km\"", "\"right_ascension_deg\"", "\"altitude_deg\"", "\"next_rise\""] {
+            assert!(json.contains(key), "{key} missing from {json}");
+        }
+        assert!(ephemeris_json(at, None, Precision::Standard).ends_with("\"observer\":null}"));
+    }
+
+    #[test]
+    fn moonrise_and_moonset_alternate_with_altitude() {
+        let greenwich = Observer { lat: 51.48, lon: 0.0 };
+        let start = Utc.with_ymd_and_hms(2025, 12, 13, 0, 0, 0).unwrap();
+        let rise = next_horizon_crossing(start, greenwich, true, Precision::Standard).unwrap();
+        let set = next_horizon_crossing(start, greenwich, false, Precision::Standard).unwrap();
+        let alt = |t: DateTime<Utc>| horizontal(t, &calculate_moon_phase(t), greenwich).0;
+        assert!(alt(rise - Duration::minutes(10)) < alt(rise + Duration::minutes(10)));
+        assert!(alt(set - Duration::minutes(10)) > alt(set + Duration::minutes(10)));
+        assert!(parse_datetime_arg("2025-12-13T04:00").is_ok());
+        assert!(parse_datetime_arg("2025-12-13T04:00Z").is_ok());
+        assert!(parse_datetime_arg("13/12/2025").is_err());
+    }
+
+    #[test]
+    fn poem_border_styles_draw_their_corners() {
+        let area = Rect::new(0, 0, 6, 3);
+        let corner = |style: PoemBorder| {
+            let mut buf = Buffer::empty(area);
+            poem_block(Borders::ALL, style).render(area, &mut buf);
+            buf.get(0, 0).symbol().to_string()
+        };
+        assert_eq!(corner(PoemBorder::Plain), "┌");
+        assert_eq!(corner(PoemBorder::Rounded), "╭");
+        assert_eq!(corner(PoemBorder::Double), "╔");
+        assert_eq!(corner(PoemBorder::None), " ");
+        assert_eq!(poem_block(Borders::ALL, PoemBorder::None).inner(area), area);
+        assert_eq!("Rounded".parse::<PoemBorder>(), Ok(PoemBorder::Rounded));
+        assert!("dotted".parse::<PoemBorder>().is_err());
+    }
+
+    #[test]
+    fn sign_ingress_crosses_one_boundary() {
+        let from = Utc.with_ymd_and_hms(2025, 3, 14, 0, 0, 0).unwrap();
+        let sign = calculate_moon_phase(from).zodiac_sign();
+        let (at, next) = next_sign_ingress(from, Precision::Standard);
+        assert_eq!(next, (sign + 1) % 12);
+        assert!(at > from && at - from < Duration::days(3));
+        assert_eq!(calculate_moon_phase(at - Duration::minutes(1)).zodiac_sign(), sign);
+        assert_eq!(calculate_moon_phase(at).zodiac_sign(), next);
+
+        assert_eq!(format_countdown(Duration::minutes(40)), "40m");
+        assert_eq!(format_countdown(Duration::minutes(6 * 60 + 20)), "6h");
+        assert_eq!(format_countdown(Duration::hours(53)), "2d 5h");
+    }
+
+    #[test]
+    fn light_bg_prints_background_escapes() {
+        let full = Utc.with_ymd_and_hms(2025, 3, 14, 6, 55, 0).unwrap();
+        let widget = |light_bg| MoonWidget {
+            status: calculate_moon_phase(full),
+            options: RenderOptions { truecolor: false, light_bg, ..test_options() },
+        };
+        let printed = |light_bg| {
+            let mut out = Vec::new();
+            write_ansi(&mut out, &print_buffer(12, widget(light_bg), 80), false).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let light = printed(true);
+        assert!(light.contains("\x1b[30m\x1b[107m"), "lit cells should be black on white");
+        assert!(light.contains("\x1b[49m"), "the background resets off the disc");
+        assert!(!printed(false).contains("\x1b[107m"));
+        assert_eq!(color_to_ansi_bg(Color::Yellow), "\x1b[43m");
+        assert_eq!(color_to_ansi_bg(Color::Rgb(1, 2, 3)), "\x1b[48;2;1;2;3m");
+    }
+
+    #[test]
+    fn no_mask_draws_the_corners_of_the_art_box() {
+        let full = Utc.with_ymd_and_hms(2025, 3, 14, 6, 55, 0).unwrap();
+        let corner_fg = |no_mask| {
+            let area = Rect::new(0, 0, 40, 20);
+            let mut buf = Buffer::empty(area);
+            let mut options = print_widget(&Args::parse_from(["ascii_moon"]), full).options;
+            options.no_mask = no_mask;
+            MoonWidget { status: calculate_moon_phase(full), options }.render(area, &mut buf);
+            buf.get(0, 0).fg
+        };
+        assert_eq!(corner_fg(false), Color::Reset);
+        assert_ne!(corner_fg(true), Color::Reset);
+    }
+
+    #[test]
+    fn relative_dates_count_from_today() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let day = |s| parse_date_input(s, today).ok();
+        assert_eq!(day("today"), Some(today));
+        assert_eq!(day("Tomorrow"), NaiveDate::from_ymd_opt(2025, 3, 15));
+        assert_eq!(day("yesterday"), NaiveDate::from_ymd_opt(2025, 3, 13));
+        assert_eq!(day("+3d"), NaiveDate::from_ymd_opt(2025, 3, 17));
+        assert_eq!(day("-2w"), NaiveDate::from_ymd_opt(2025, 2, 28));
+        assert_eq!(day("2024-02-29"), NaiveDate::from_ymd_opt(2024, 2, 29));
+        for bad in ["3d", "+3m", "+d", "next week", "+99999999999w"] {
+            assert!(day(bad).is_none(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn shadow_edge_is_straight_at_quarter_and_curved_at_crescent() {
+        // A 60x30 disc: per row, the first lit column from the left (both dates are waxing,
+        // lit on the right), for rows away from the poles.
+        let area = Rect::new(0, 0, 60, 30);
+        let lit_edges = |at: DateTime<Utc>| -> Vec<u16> {
+            let mut options = print_widget(&Args::parse_from(["ascii_moon"]), at).options;
+            options.truecolor = false;
+            let mut buf = Buffer::empty(area);
+            MoonWidget { status: calculate_moon_phase(at), options }.render(area, &mut buf);
+            (5..25)
+                .map(|y| (0..60).find(|&x| buf.get(x, y).fg == moon_lit_color(false)).expect("a lit cell in every row"))
+                .collect()
+        };
+
+        // First quarter (6 March 2025, 16:32 UTC): the terminator is the great circle seen
+        // edge-on, a straight line down the middle.
+        let quarter = lit_edges(Utc.with_ymd_and_hms(2025, 3, 6, 16, 32, 0).unwrap());
+        assert!(quarter.iter().all(|&x| (29..=31).contains(&x)), "{quarter:?}");
+
+        // A thin waxing crescent: the terminator is a half-ellipse bulging toward the lit
+        // limb, so the equator row's edge lies well right of the rows near the poles.
+        let crescent = lit_edges(Utc.with_ymd_and_hms(2025, 3, 2, 12, 0, 0).unwrap());
+        let (top, middle, bottom) = (crescent[0], crescent[10], crescent[19]);
+        assert!(middle >= top + 4 && middle >= bottom + 4, "{crescent:?}");
+        assert!(middle > 40, "{crescent:?}");
+    }
+
+    #[test]
+    fn breathing_shrinks_the_disc_and_back() {
+        use std::time::Duration as StdDuration;
+        assert_eq!(breath_shrink(StdDuration::ZERO), 0.0);
+        assert!((breath_shrink(BREATHE_PERIOD / 2) - BREATHE_DEPTH).abs() < 1e-12);
+        assert!(breath_shrink(BREATHE_PERIOD) < 1e-12);
+
+        let full = Utc.with_ymd_and_hms(2025, 3, 14, 6, 55, 0).unwrap();
+        let area = Rect::new(0, 0, 80, 40);
+        let drawn = |shrink| {
+            let mut options = print_widget(&Args::parse_from(["ascii_moon"]), full).options;
+            options.shrink = shrink;
+            let mut buf = Buffer::empty(area);
+            MoonWidget { status: calculate_moon_phase(full), options }.render(area, &mut buf);
+            let rows: Vec<u16> = (0..40).filter(|&y| (0..80).any(|x| buf.get(x, y).symbol() != " ")).collect();
+            let cols: Vec<u16> = (0..80).filter(|&x| (0..40).any(|y| buf.get(x, y).symbol() != " ")).collect();
+            (rows.len(), cols.len(), rows[0] + rows[rows.len() - 1], cols[0] + cols[cols.len() - 1])
+        };
+        let (rows, cols, row_mid, col_mid) = drawn(0.0);
+        let (small_rows, small_cols, small_row_mid, small_col_mid) = drawn(0.1);
+        assert!(small_rows < rows && small_cols < cols);
+        // Still centered.
+        assert_eq!((small_row_mid, small_col_mid), (row_mid, col_mid));
+    }
+
+    #[test]
+    fn stars_fade_as_the_moon_fills() {
+        let area = Rect::new(0, 0, 80, 30);
+        let sky = |at: DateTime<Utc>| {
+            let mut options = print_widget(&Args::parse_from(["ascii_moon", "--stars"]), at).options;
+            options.truecolor = true;
+            let mut buf = Buffer::empty(area);
+            MoonWidget { status: calculate_moon_phase(at), options }.render(area, &mut buf);
+            // Stars sit outside the disc, which is centered and 60 columns wide here.
+            (0..30)
+                .flat_map(|y| [(0..10), (70..80)].into_iter().flatten().map(move |x| (x, y)))
+                .filter_map(|(x, y)| match buf.get(x, y).fg {
+                    Color::Rgb(level, _, _) if buf.get(x, y).symbol() != " " => Some(((x, y), level)),
+                    _ => None,
+                })
+                .collect::<std::collections::BTreeMap<_, _>>()
+        };
+        let new = sky(Utc.with_ymd_and_hms(2025, 3, 29, 10, 58, 0).unwrap());
+        let quarter = sky(Utc.with_ymd_and_hms(2025, 3, 6, 16, 32, 0).unwrap());
+        let full = sky(Utc.with_ymd_and_hms(2025, 3, 14, 6, 55, 0).unwrap());
+        assert!(new.len() > quarter.len() && !quarter.is_empty(), "{} vs {}", new.len(), quarter.len());
+        assert!(full.is_empty(), "{} stars under a full moon", full.len());
+        // The field itself never moves: the half-lit sky shows a dimmer subset of the same stars.
+        for (cell, level) in &quarter {
+            assert!(new.get(cell).is_some_and(|l| l > level), "star at {cell:?} moved or brightened");
+        }
+    }
+
+    #[test]
+    fn repeat_period_parses_and_excludes_a_fixed_date() {
+        assert_eq!(parse_repeat_seconds("60"), Ok(60.0));
+        assert!(parse_repeat_seconds("0").is_err());
+        assert!(parse_repeat_seconds("soon").is_err());
+        assert!(parse_repeat_seconds("1e300").is_err());
+        assert!(Args::try_parse_from(["ascii_moon", "--repeat", "1e300"]).is_err());
+        assert_eq!(Args::try_parse_from(["ascii_moon", "--repeat", "2.5"]).unwrap().repeat, Some(2.5));
+        assert!(Args::try_parse_from(["ascii_moon", "--repeat", "5", "--date", "2025-01-01"]).is_err());
+        assert!(Args::try_parse_from(["ascii_moon", "--repeat", "5", "--days", "3"]).is_err());
+    }
+
+    #[test]
+    fn repeat_screen_restores_the_cursor_and_stays_plain_off_a_terminal() {
+        let mut out = Vec::new();
+        {
+            let mut screen = RepeatScreen::new(&mut out, true).unwrap();
+            screen.begin_frame().unwrap();
+            // Dropped mid-frame, as an early `?` return would.
+        }
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("\x1b[?25l"), "{text:?}");
+        assert!(text.ends_with("\x1b[?25h"), "{text:?}");
+
+        let mut out = Vec::new();
+        {
+            let mut screen = RepeatScreen::new(&mut out, false).unwrap();
+            screen.begin_frame().unwrap();
+            screen.end_frame().unwrap();
+        }
+        assert!(out.is_empty());
+    }
+
+    fn test_state(date: DateTime<Utc>) -> AppState {
+        let args = Args::try_parse_from(["ascii_moon"]).unwrap();
+        AppState::new(&args, date, true, poems::default_poems())
+    }
+
+    /// The options a bare `ascii_moon` prints with, truecolor pinned on so tests don't depend on `COLORTERM`.
+    fn test_options() -> RenderOptions {
+        let args = Args::try_parse_from(["ascii_moon"]).unwrap();
+        RenderOptions { truecolor: true, ..print_widget(&args, Utc::now()).options }
+    }
+
+    #[test]
+    fn precision_flag_reaches_the_print_widget_and_the_tui() {
+        let dt = Utc.with_ymd_and_hms(1992, 4, 11, 23, 59, 1).unwrap();
+        let high = calculate_moon_phase_with(dt, Precision::High);
+        assert_ne!(high.ecliptic_lon, calculate_moon_phase(dt).ecliptic_lon);
+
+        let args = Args::try_parse_from(["ascii_moon", "--precision", "high"]).unwrap();
+        assert_eq!(print_widget(&args, dt).status.ecliptic_lon, high.ecliptic_lon);
+        assert_eq!(AppState::new(&args, dt, false, poems::default_poems()).precision, Precision::High);
+        assert_eq!(test_state(dt).precision, Precision::Standard);
+        let json = ephemeris_json(dt, None, Precision::High);
+        assert!(json.contains(&format!("\"ecliptic_lon_deg\":{:.3}", high.ecliptic_lon)), "{json}");
+    }
+
+    #[test]
+    fn calendar_lays_out_a_monday_first_month() {
+        let shown = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let text = |line: &Line| line.spans.iter().map(|s| s.content.as_ref()).collect::<String>();
+        let lines = calendar_lines(shown, shown, false, Precision::Standard);
+        assert_eq!(text(&lines[0]), "March 2025");
+        // 1 March 2025 is a Saturday; the 31st a Monday: six week rows.
+        assert_eq!(lines.len(), 3 + 6);
+        assert!(text(&lines[3]).starts_with(&" ".repeat(25)));
+        assert!(lines.iter().all(|l| l.width() == 35 || l.width() < 20));
+        let full = lines.iter().flat_map(|l| &l.spans).find(|s| s.content.ends_with("14")).unwrap();
+        assert_eq!(full.content, "🌕14");
+        assert!(full.style.add_modifier.contains(Modifier::REVERSED));
+        let dumb = calendar_lines(shown, shown, true, Precision::Standard);
+        assert!(text(&dumb[5]).contains("FM14"));
+    }
+
+    #[test]
+    fn reset_view_restores_the_startup_toggles_but_not_the_date() {
+        let date = Utc.with_ymd_and_hms(2025, 3, 14, 12, 0, 0).unwrap();
+        let mut state = test_state(date);
+        state.toggle_labels();
+        state.select_feature(true);
+        state.cycle_shadow();
+        state.toggle_info();
+        state.toggle_poem();
+        state.cycle_language();
+        state.rotate(ROTATION_STEP);
+        state.step_day(3);
+        state.reset_view(&Args::parse_from(["ascii_moon"]));
+        assert!(!state.show_labels && state.selected_feature.is_none());
+        assert_eq!(state.shadow, ShadowMode::Full);
+        assert!(state.show_info && !state.show_poem);
+        assert_eq!((state.language, state.rotation), (Language::English, 0.0));
+        assert_eq!(state.date, date + Duration::days(3));
+    }
+
+    #[test]
+    fn app_state_steps_months_in_the_calendar() {
+        let mut state = test_state(Utc.with_ymd_and_hms(2025, 1, 31, 12, 0, 0).unwrap());
+        assert!(state.step_month(true));
+        assert_eq!(state.date.date_naive(), NaiveDate::from_ymd_opt(2025, 2, 28).unwrap());
+        assert!(state.step_month(false));
+        assert_eq!(state.date.date_naive(), NaiveDate::from_ymd_opt(2025, 1, 28).unwrap());
+    }
+
+    #[test]
+    fn app_state_steps_days_and_stops_at_the_range_edge() {
+        let mut state = test_state(Utc.with_ymd_and_hms(2100, 12, 30, 12, 0, 0).unwrap());
+        assert!(state.follow_now);
+        assert!(state.step_day(1));
+        assert!(!state.follow_now);
+        assert_eq!(state.date.day(), 31);
+        assert!(!state.step_day(1));
+        assert_eq!(state.date.day(), 31);
+        assert_eq!(state.status_message.map(|(m, _)| m), Some("Reached the latest supported date"));
+        assert!(state.step_day(-1));
+    }
+
+    #[test]
+    fn app_state_toggles_and_cycles() {
+        let mut state = test_state(Utc.with_ymd_and_hms(2025, 12, 4, 12, 0, 0).unwrap());
+        state.toggle_labels();
+        state.select_feature(false);
+        assert_eq!(state.selected_feature, Some(LUNAR_FEATURES.len() - 1));
+        state.select_feature(true);
+        assert_eq!(state.selected_feature, Some(0));
+
+        for _ in 0..LUNAR_FEATURES.len() {
+            state.adjust_label_count(false);
+        }
+        assert_eq!((state.label_count, state.selected_feature), (1, Some(0)));
+        assert!(!state.adjust_label_count(false));
+        assert!(state.adjust_label_count(true));
+        state.select_feature(false);
+        assert_eq!(state.selected_feature, Some(1));
+        state.adjust_label_count(false);
+        assert_eq!(state.selected_feature, None);
+        state.label_count = LUNAR_FEATURES.len();
+        assert!(!state.adjust_label_count(true));
+
+        state.cycle_language();
+        assert_ne!(state.language, state.poem_language);
+        state.rotate(-ROTATION_STEP);
+        assert_eq!(state.rotation, -ROTATION_STEP);
+        state.cycle_shadow();
+        assert_eq!(state.shadow, ShadowMode::Earthshine);
+
+        state.toggle_poem();
+        assert!(state.show_poem);
+        state.open_search();
+        for c in "zzz no such poem".chars() {
+            state.search_key(KeyCode::Char(c));
+        }
+        state.search_key(KeyCode::Enter);
+        assert!(state.search_input.is_none());
+        assert!(state.poem_filter.is_none());
+        assert!(state.status_message.is_some());
+    }
+
+    #[test]
+    fn details_lines_use_the_display_time_zone() {
+        let dt = Utc.with_ymd_and_hms(2025, 12, 4, 23, 14, 0).unwrap();
+        let moon = calculate_moon_phase(dt);
+        let tokyo = FixedOffset::east_opt(9 * 3600);
+        let lines = details_lines(dt, tokyo, &moon);
+        assert_eq!(lines[0], "Date:         2025-12-05");
+        assert_eq!(lines[1], "Phase:        🌕 Full Moon");
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn almanac_finds_2025_full_moons_supermoons_and_eclipses() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let events = almanac_events(start, end, Precision::Standard);
+        let fulls: Vec<&AlmanacEvent> = events.iter().filter(|e| e.name == "Full Moon").collect();
+        assert_eq!(fulls.len(), 12);
+        assert!(events.windows(2).all(|w| w[0].at <= w[1].at));
+
+        // timeanddate.com: full moon 2025-12-04 23:14 UTC, near perigee.
+        let december = fulls.last().unwrap();
+        let expected = Utc.with_ymd_and_hms(2025, 12, 4, 23, 14, 0).unwrap();
+        assert!((december.at - expected).num_minutes().abs() <= 30, "{}", december.at);
+        assert!(december.supermoon);
+
+        // Total lunar eclipses on 2025-03-14 and 2025-09-07.
+        for (month, day) in [(3, 14), (9, 7)] {
+            let full = fulls.iter().find(|e| e.at.month() == month).unwrap();
+            assert_eq!(full.at.day(), day);
+            assert_eq!(full.eclipse, Some("lunar"));
+        }
+        assert!(events.iter().any(|e| e.name == "Perigee") && events.iter().any(|e| e.name == "Apogee"));
+    }
+
+    #[test]
+    fn find_extrema_finds_turning_points() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        // A 10-day sine: maxima at day 2.5, minima at day 7.5 (+ every 10 days).
+        let f = |t: DateTime<Utc>| {
+            let days = (t - start).num_minutes() as f64 / 1440.0;
+            (days * std::f64::consts::TAU / 10.0).sin()
+        };
+        let extrema = find_extrema(start, start + Duration::days(20), Duration::hours(1), f);
+        assert_eq!(extrema.len(), 4);
+        assert!(extrema[0].is_max && !extrema[1].is_max);
+        assert_eq!(extrema[0].at, start + Duration::hours(60));
+    }
+
+    #[test]
+    fn try_calculate_rejects_unsupported_dates() {
+        let ok = Utc.with_ymd_and_hms(2025, 12, 13, 4, 46, 50).unwrap();
+        assert!(try_calculate_moon_phase(ok).is_ok());
+        let early = Utc.with_ymd_and_hms(1850, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(try_calculate_moon_phase(early).unwrap_err(), MoonError::OutOfRange(early));
+        let late = Utc.with_ymd_and_hms(2101, 1, 1, 0, 0, 0).unwrap();
+        assert!(try_calculate_moon_phase(late).is_err());
+        // The infallible variant still answers.
+        assert!(calculate_moon_phase(early).illumination.is_finite());
+    }
+
+    #[test]
+    fn date_stepping_stops_at_the_supported_range() {
+        let first = Utc.with_ymd_and_hms(1900, 1, 1, 12, 0, 0).unwrap();
+        let last = Utc.with_ymd_and_hms(2100, 12, 31, 12, 0, 0).unwrap();
+        assert!(step_date(first, -1).is_err());
+        assert_eq!(step_date(first, 1).unwrap(), first + Duration::days(1));
+        assert!(step_date(last, 1).is_err());
+        // From outside the range, stepping back toward it still works.
+        let early = Utc.with_ymd_and_hms(1850, 6, 1, 12, 0, 0).unwrap();
+        assert!(step_date(early, 1).is_ok());
+    }
+
+    #[test]
+    fn buffer_json_lists_each_cell_by_row() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 2));
+        buf.get_mut(1, 0).set_char('#').set_fg(Color::Yellow);
+        buf.get_mut(0, 1).set_char('"').set_fg(Color::Rgb(255, 215, 0));
+        assert_eq!(
+            buffer_json(&buf),
+            r##"{"width":2,"height":2,"rows":[[{"char":" ","fg":"Reset"},{"char":"#","fg":"Yellow"}],[{"char":"\"","fg":"#FFD700"},{"char":" ","fg":"Reset"}]]}"##
+        );
+    }
+
+    #[test]
+    fn pretty_json_only_changes_whitespace() {
+        let at = Utc.with_ymd_and_hms(2025, 12, 13, 4, 0, 0).unwrap();
+        let compact = ephemeris_json(at, Some(Observer { lat: 51.48, lon: 0.0 }), Precision::Standard);
+        let pretty = pretty_json(&compact);
+        assert!(pretty.lines().count() > 10, "{pretty}");
+        assert!(pretty.contains("\n  \"date\": \"2025-12-13T04:00:00Z\","), "{pretty}");
+        // Dropping whitespace outside strings (and the space after each colon) gives back the original.
+        assert_eq!(pretty.lines().map(str::trim_start).collect::<String>().replace("\": ", "\":"), compact);
+
+        assert_eq!(
+            pretty_json(r#"{"a":[],"b":{"c":"x, {y}: \"z\""}}"#),
+            "{\n  \"a\": [],\n  \"b\": {\n    \"c\": \"x, {y}: \\\"z\\\"\"\n  }\n}"
+        );
+        let args = Args::parse_from(["ascii_moon", "--json", "--pretty"]);
+        assert_eq!(args.json_text(compact.clone()), pretty);
+        assert_eq!(Args::parse_from(["ascii_moon", "--json"]).json_text(compact.clone()), compact);
+    }
+
+    #[test]
+    fn json_string_escapes_control_and_quote_characters() {
+        assert_eq!(json_string("Full Moon"), "\"Full Moon\"");
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+
+    #[test]
+    fn render_handles_degenerate_areas() {
+        let dt = Utc.with_ymd_and_hms(2025, 12, 4, 23, 14, 0).unwrap();
+        // Each area sits inside a larger buffer so a stray write outside it would show.
+        let full = Rect::new(0, 0, 140, 50);
+        for (w, h) in [(0, 10), (10, 0), (1, 1), (1, 40), (120, 1)] {
+            let area = Rect::new(5, 5, w, h);
+            let mut buf = Buffer::empty(full);
+            MoonWidget {
+                status: calculate_moon_phase(dt),
+                options: RenderOptions { show_labels: true, libration: true, show_markers: true, ..test_options() },
+            }
+            .render(area, &mut buf);
+
+            let drawn: Vec<(u16, u16)> = (0..full.height)
+                .flat_map(|y| (0..full.width).map(move |x| (x, y)))
+                .filter(|&(x, y)| buf.get(x, y).symbol() != " ")
+                .collect();
+            for &(x, y) in &drawn {
+                assert!(area.contains(ratatui::layout::Position { x, y }), "{w}x{h}: drew outside at ({x}, {y})");
+            }
+            assert!(drawn.len() <= usize::from(w) * usize::from(h), "{w}x{h}: {} glyphs", drawn.len());
+        }
+    }
+
+    #[test]
+    fn render_moon_stays_inside_an_embedded_area() {
+        let status = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 12, 4, 23, 14, 0).unwrap());
+        let full = Rect::new(0, 0, 60, 20);
+        let pane = Rect::new(10, 5, 20, 10);
+        let mut buf = Buffer::empty(full);
+        render_moon(&status, pane, &mut buf, test_options());
+        for y in 0..full.height {
+            for x in 0..full.width {
+                if buf.get(x, y).symbol() != " " {
+                    assert!(pane.contains(ratatui::layout::Position { x, y }), "drew outside at ({x}, {y})");
+                }
+            }
+        }
+        assert!((0..pane.height).any(|dy| buf.get(pane.x + pane.width / 2, pane.y + dy).symbol() != " "));
+    }
+}
+
+// Degrees per `,`/`.` keypress in the TUI.
+const ROTATION_STEP: f64 = 15.0;
+
+#[derive(Debug, Clone)]
+struct PoemViewState {
+    poem: Poem,
+    glow_phase: u64,
+    last_anim: Instant,
+    twinkle_seed: u64,
+    twinkles: Vec<Twinkle>,
+    // Fade-in state for poem body lines: 0..=LINE_FADE_STEPS.
+    line_fade: Vec<u8>,
+    fade_idx: usize,
+    fade_step: u8,
+    last_fade: Instant,
+    fade_pause_until: Option<Instant>,
+    // Last poem shown in sequential order: its language and index into `for_language`.
+    sequence: Option<(Language, usize)>,
+}
+
+#[derive(Debug, Clone)]
+struct Twinkle {
+    // Stored as coordinates relative to the poem inner area (0..w-1, 0..h-1).
+    x: u16,
+    y: u16,
+    ttl: u16,
+    kind: u8,
+}
+
+fn lcg_next_u32(seed: &mut u64) -> u32 {
+    // Simple LCG for deterministic twinkles; good enough for UI.
+    *seed = seed
+        .wrapping_mul(6364136223846793005u64)
+        .wrapping_add(1442695040888963407u64);
+    (*seed >> 32) as u32
+}
+
+fn soft_palette(glow_phase: u64) -> (Color, Color, Color) {
+    // A calm, romantic palette (lavender / moonlight / blush).
+    // We keep it subtle and avoid rapid cycling.
+    let step = (glow_phase / 12) % 3;
+    match step {
+        0 => (
+            Color::Rgb(245, 223, 235), // title: blush
+            Color::Rgb(206, 204, 235), // body: lavender
+            Color::Rgb(170, 180, 210), // dim: mist
+        ),
+        1 => (
             Color::Rgb(240, 232, 250),
             Color::Rgb(200, 216, 240),
             Color::Rgb(165, 175, 205),
         ),
-        _ => (
-            Color::Rgb(250, 240, 235),
-            Color::Rgb(210, 198, 238),
-            Color::Rgb(160, 170, 200),
+        _ => (
+            Color::Rgb(250, 240, 235),
+            Color::Rgb(210, 198, 238),
+            Color::Rgb(160, 170, 200),
+        ),
+    }
+}
+
+const LINE_FADE_STEPS: u8 = 6;
+
+// Below this width the moon (Min 18) and poem (Min 28) panes can't both fit,
+// so the poem pane is hidden rather than squeezing the moon to nothing.
+const MIN_POEM_LAYOUT_WIDTH: u16 = 18 + 28;
+
+/// The poem panel's frame: `borders` (none on a dumb terminal) in the `--poem-border` style.
+fn poem_block(borders: Borders, style: PoemBorder) -> Block<'static> {
+    match style.border_type() {
+        Some(border_type) => Block::default().borders(borders).border_type(border_type),
+        None => Block::default().borders(Borders::NONE),
+    }
+}
+
+/// Swap in a new poem and restart its glow, twinkles, and line fade-in.
+fn start_poem(state: &mut PoemViewState, poem: Poem) {
+    state.poem = poem;
+    state.glow_phase = 0;
+    state.last_anim = Instant::now();
+    state.twinkle_seed = rand::random::<u64>();
+    state.twinkles.clear();
+    reset_poem_fade(state);
+}
+
+/// Pick the next poem according to `order` and start showing it.
+fn show_next_poem(
+    state: &mut PoemViewState,
+    lib: &PoemLibrary,
+    lang: Language,
+    phase: Option<MoonPhase>,
+    order: PoemOrder,
+) {
+    let poem = match order {
+        PoemOrder::Random => pick_poem(lib, lang, phase),
+        PoemOrder::Sequential => {
+            let poems = lib.for_language(lang);
+            match next_in_sequence(poems, state.sequence, lang, phase) {
+                Some(i) => {
+                    state.sequence = Some((lang, i));
+                    poems[i].clone()
+                }
+                None => placeholder_poem(lang),
+            }
+        }
+    };
+    start_poem(state, poem);
+}
+
+/// Index of the poem after `last` (restarting from the top on a language change),
+/// skipping poems not tagged for `phase` unless none are.
+fn next_in_sequence(
+    poems: &[Poem],
+    last: Option<(Language, usize)>,
+    lang: Language,
+    phase: Option<MoonPhase>,
+) -> Option<usize> {
+    if poems.is_empty() {
+        return None;
+    }
+    let start = match last {
+        Some((l, i)) if l == lang => (i + 1) % poems.len(),
+        _ => 0,
+    };
+    (0..poems.len())
+        .map(|k| (start + k) % poems.len())
+        .find(|&i| phase.is_none_or(|p| poems[i].matches_phase(p)))
+        .or(Some(start))
+}
+
+/// Index of the next (or previous) poem after `last` containing `query`, wrapping around.
+/// Starts from the top (or bottom) on a language change; `None` when nothing matches.
+fn next_match(
+    poems: &[Poem],
+    last: Option<(Language, usize)>,
+    lang: Language,
+    query: &str,
+    forward: bool,
+) -> Option<usize> {
+    let mut current = match last {
+        Some((l, i)) if l == lang && i < poems.len() => Some(i),
+        _ => None,
+    };
+    for _ in 0..poems.len() {
+        let i = cycle_index(current, poems.len(), forward);
+        if poems[i].matches_query(query) {
+            return Some(i);
+        }
+        current = Some(i);
+    }
+    None
+}
+
+fn reset_poem_fade(state: &mut PoemViewState) {
+    state.line_fade = vec![0; state.poem.lines.len()];
+    state.fade_idx = 0;
+    state.fade_step = 0;
+    state.last_fade = Instant::now();
+    state.fade_pause_until = None;
+}
+
+fn soft_palette_for_theme(glow_phase: u64, theme: Theme, truecolor: bool) -> (Color, Color, Color) {
+    match theme {
+        Theme::Light => {
+            // Higher contrast on light terminals.
+            let step = (glow_phase / 16) % 3;
+            if truecolor {
+                match step {
+                    0 => (
+                        Color::Rgb(92, 40, 88),   // title: plum
+                        Color::Rgb(52, 62, 120),  // body: deep indigo
+                        Color::Rgb(95, 95, 105),  // dim: graphite
+                    ),
+                    1 => (
+                        Color::Rgb(70, 45, 115),
+                        Color::Rgb(40, 75, 130),
+                        Color::Rgb(90, 90, 100),
+                    ),
+                    _ => (
+                        Color::Rgb(110, 40, 70),
+                        Color::Rgb(65, 55, 120),
+                        Color::Rgb(95, 95, 105),
+                    ),
+                }
+            } else {
+                // 256-color approximations for light backgrounds.
+                match step {
+                    0 => (Color::Indexed(90), Color::Indexed(25), Color::Indexed(244)),
+                    1 => (Color::Indexed(91), Color::Indexed(24), Color::Indexed(244)),
+                    _ => (Color::Indexed(89), Color::Indexed(61), Color::Indexed(244)),
+                }
+            }
+        }
+        _ => {
+            if truecolor {
+                soft_palette(glow_phase)
+            } else {
+                // 256-color "romantic" palette for dark terminals.
+                let step = (glow_phase / 16) % 3;
+                match step {
+                    0 => (Color::Indexed(212), Color::Indexed(110), Color::Indexed(245)),
+                    1 => (Color::Indexed(213), Color::Indexed(111), Color::Indexed(245)),
+                    _ => (Color::Indexed(211), Color::Indexed(109), Color::Indexed(245)),
+                }
+            }
+        }
+    }
+}
+
+/// `--readable-poems`: fixed high-contrast colors (title, body, "dim").
+///
+/// There's deliberately no dim shade: the author and fading lines use the body color.
+/// Plain ANSI colors so every terminal maps them to something legible.
+fn readable_palette(theme: Theme) -> (Color, Color, Color) {
+    match theme {
+        Theme::Light => (Color::Blue, Color::Black, Color::Black),
+        _ => (Color::LightYellow, Color::White, Color::White),
+    }
+}
+
+fn poem_palette(glow_phase: u64, theme: Theme, truecolor: bool, readable: bool) -> (Color, Color, Color) {
+    if readable {
+        readable_palette(theme)
+    } else {
+        soft_palette_for_theme(glow_phase, theme, truecolor)
+    }
+}
+
+fn render_poem_lines_soft(
+    poem: &Poem,
+    line_fade: &[u8],
+    glow_phase: u64,
+    theme: Theme,
+    truecolor: bool,
+    readable: bool,
+) -> Vec<Line<'static>> {
+    let (title_c, body_c, dim_c) = poem_palette(glow_phase, theme, truecolor, readable);
+    let mut out: Vec<Line> = Vec::new();
+
+    out.push(Line::from(Span::styled(
+        poem.title.clone(),
+        // Avoid BOLD/ITALIC: Terminal.app can remap "bright colors" unexpectedly.
+        Style::default().fg(title_c),
+    )));
+
+    if poem.author.is_empty() {
+        out.push(Line::from(Span::styled("", Style::default().fg(dim_c))));
+    } else {
+        out.push(Line::from(Span::styled(
+            format!("— {}", poem.author),
+            Style::default().fg(dim_c),
+        )));
+    }
+
+    out.push(Line::from(""));
+
+    for (i, line) in poem.lines.iter().enumerate() {
+        let level = line_fade.get(i).copied().unwrap_or(0).min(LINE_FADE_STEPS);
+        if level == 0 {
+            out.push(Line::from(Span::styled("", Style::default().fg(dim_c))));
+            continue;
+        }
+
+        // Fade without DIM/BOLD/ITALIC: use dim color first, then body color.
+        let style = if level < (LINE_FADE_STEPS / 2).max(1) {
+            Style::default().fg(dim_c)
+        } else {
+            Style::default().fg(body_c)
+        };
+
+        {
+            out.push(Line::from(Span::styled(
+                line.clone(),
+                style,
+            )));
+        }
+    }
+
+    out
+}
+
+fn twinkle_char(kind: u8) -> char {
+    match kind % 4 {
+        0 => '·',
+        1 => '⋅',
+        2 => '.',
+        _ => '˙',
+    }
+}
+
+fn update_twinkles(twinkles: &mut Vec<Twinkle>, seed: &mut u64, area: Rect) {
+    // Maintain a small, persistent field of twinkles that fade slowly.
+    // This avoids the "pop in/pop out" effect.
+    if area.width < 6 || area.height < 6 {
+        twinkles.clear();
+        return;
+    }
+
+    // Decay
+    for t in twinkles.iter_mut() {
+        t.ttl = t.ttl.saturating_sub(1);
+        // Keep within bounds if the pane resizes
+        t.x = t.x.min(area.width.saturating_sub(1));
+        t.y = t.y.min(area.height.saturating_sub(1));
+    }
+    twinkles.retain(|t| t.ttl > 0);
+
+    // Target density: scale gently with pane size so larger terminals feel more "starry"
+    // without becoming noisy.
+    //
+    // Roughly: one twinkle per ~90 cells, clamped to a tasteful range.
+    let cells = area.width as usize * area.height as usize;
+    let base = (cells / 90).clamp(10, 22);
+    let jitter = lcg_next_u32(seed) as usize % 4; // +0..3
+    let target = base + jitter;
+    while twinkles.len() < target {
+        // Spawn away from borders so it feels like "in the sky", not hugging the frame.
+        let x = 1 + (lcg_next_u32(seed) as u16 % area.width.saturating_sub(2));
+        let y = 1 + (lcg_next_u32(seed) as u16 % area.height.saturating_sub(2));
+        let max_ttl = 28 + (lcg_next_u32(seed) as u16 % 26); // 28..53 ticks (~3.4s..6.4s at 120ms)
+        let kind = (lcg_next_u32(seed) % 4) as u8;
+        twinkles.push(Twinkle {
+            x,
+            y,
+            ttl: max_ttl,
+            kind,
+        });
+    }
+}
+
+fn render_twinkles(
+    buf: &mut Buffer,
+    area: Rect,
+    twinkles: &[Twinkle],
+    glow_phase: u64,
+    theme: Theme,
+    truecolor: bool,
+) {
+    // Draw twinkles *only* on blank cells so we don't overwrite poem text.
+    let (_, _, dim_c) = soft_palette_for_theme(glow_phase, theme, truecolor);
+    for t in twinkles {
+        let x = area.left() + t.x;
+        let y = area.top() + t.y;
+        if x <= area.left() || x + 1 >= area.right() || y <= area.top() || y + 1 >= area.bottom()
+        {
+            continue;
+        }
+
+        if buf.get(x, y).symbol() != " " {
+            continue;
+        }
+
+        // Keep it subtle; avoid BOLD/DIM modifiers for Terminal.app.
+        let style = Style::default().fg(dim_c);
+
+        buf.get_mut(x, y).set_char(twinkle_char(t.kind)).set_style(style);
+    }
+}
+
+// The Details panel moves beside the moon once the screen is this many full-height discs wide.
+const SIDE_DETAILS_DISCS: f64 = 2.0;
+
+/// Split the TUI into the main (moon and poem) area and the Details panel. Normally the panel
+/// sits below; on very wide screens it goes beside the moon so the disc keeps the full height.
+/// Also reports whether the panel is beside.
+fn tui_layout(area: Rect, show_info: bool, cell_ratio: CellRatio) -> (Rect, Rect, bool) {
+    let beside = show_info && area.width as f64 >= SIDE_DETAILS_DISCS * area.height as f64 * cell_ratio.aspect();
+    let (direction, constraints) = if beside {
+        (Direction::Horizontal, [Constraint::Percentage(70), Constraint::Percentage(30)])
+    } else if show_info {
+        (Direction::Vertical, [Constraint::Percentage(80), Constraint::Percentage(20)])
+    } else {
+        (Direction::Vertical, [Constraint::Percentage(100), Constraint::Min(0)])
+    };
+    let chunks = Layout::default()
+        .direction(direction)
+        .margin(1)
+        .constraints(constraints)
+        .split(area);
+    (chunks[0], chunks[1], beside)
+}
+
+/// Row for the illumination overlay: just below the disc when the pane has room,
+/// otherwise the disc's own bottom row.
+fn overlay_row(area: Rect, disc: DiscBox) -> u16 {
+    let below = (disc.start_y + disc.draw_h).ceil() as u16;
+    if below < area.bottom() { below } else { area.bottom() - 1 }
+}
+
+/// Step a selection forward or backward through `len` items, wrapping at both ends.
+/// With nothing selected yet, forward starts at the first item and backward at the last.
+fn cycle_index(current: Option<usize>, len: usize, forward: bool) -> usize {
+    match (current, forward) {
+        (None, true) => 0,
+        (None, false) => len - 1,
+        (Some(i), true) => (i + 1) % len,
+        (Some(i), false) => (i + len - 1) % len,
+    }
+}
+
+// Title and message of the stand-in poem when a language has none, indexed like `Feature::names`.
+const NO_POEMS: [[&str; 2]; 5] = [
+    ["Moon", "No poems available for this language — add some to your poems directory."],
+    ["月", "此语言暂无诗歌——请在诗歌目录中添加。"],
+    ["Lune", "Aucun poème disponible dans cette langue — ajoutez-en dans votre dossier de poèmes."],
+    ["月", "この言語の詩はありません — 詩のディレクトリに追加してください。"],
+    ["Luna", "No hay poemas disponibles en este idioma: añade algunos a tu directorio de poemas."],
+];
+
+/// Shown when `lang` has no poems at all. It's an ordinary poem to the panel, so it fades in,
+/// glows and twinkles like the real ones.
+fn placeholder_poem(lang: Language) -> Poem {
+    let [title, message] = NO_POEMS[lang as usize];
+    Poem {
+        title: title.to_string(),
+        author: "".to_string(),
+        lines: vec![message.to_string()],
+        phases: Vec::new(),
+    }
+}
+
+fn pick_poem(lib: &PoemLibrary, lang: Language, phase: Option<MoonPhase>) -> Poem {
+    match phase {
+        Some(phase) => lib.random_poem_for_phase(lang, phase),
+        None => lib.random_poem(lang),
+    }
+    .unwrap_or_else(|| placeholder_poem(lang))
+}
+
+// Poem animation cadence. These are independent of the refresh period: while the poem
+// panel is open the event loop wakes at least every ANIM_RATE, however long the refresh is.
+const ANIM_RATE: std::time::Duration = std::time::Duration::from_millis(120);
+const FADE_RATE: std::time::Duration = std::time::Duration::from_millis(140);
+const LINE_GAP: std::time::Duration = std::time::Duration::from_millis(400);
+
+// `--breathe`: the disc shrinks by up to this fraction and back once per period.
+const BREATHE_DEPTH: f64 = 0.04;
+const BREATHE_PERIOD: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// `--breathe`: how far the disc is shrunk `elapsed` into the animation, easing in and out.
+fn breath_shrink(elapsed: std::time::Duration) -> f64 {
+    let turn = elapsed.as_secs_f64() / BREATHE_PERIOD.as_secs_f64();
+    BREATHE_DEPTH * (1.0 - (turn * std::f64::consts::TAU).cos()) / 2.0
+}
+
+// Upper bound for `--max-fps`; beyond this terminals can't keep up anyway.
+const MAX_FPS: u32 = 120;
+
+/// Time left before another frame may be drawn under `--max-fps`, zero if one is due now.
+fn frame_wait(since_draw: Option<std::time::Duration>, max_fps: u32) -> std::time::Duration {
+    let interval = std::time::Duration::from_secs_f64(1.0 / max_fps as f64);
+    since_draw.map_or(std::time::Duration::ZERO, |since| interval.saturating_sub(since))
+}
+
+// Idle wake-up when there's neither a refresh period nor an animation to drive.
+const IDLE_POLL: std::time::Duration = std::time::Duration::from_millis(250);
+
+// Bounds for the auto-refresh period. The floor keeps a tiny `--tick-seconds`
+// from turning the event loop into a busy loop.
+const MAX_REFRESH_MINUTES: u64 = 7 * 24 * 60;
+const MIN_TICK_SECONDS: f64 = 1.0;
+
+/// `--blank-after`: time left before the screen blanks after `idle` without a keypress
+/// (zero once it's due), or `None` when blanking is off.
+fn blank_wait(blank_after_minutes: Option<u64>, idle: std::time::Duration) -> Option<std::time::Duration> {
+    blank_after_minutes.map(|minutes| std::time::Duration::from_secs(minutes * 60).saturating_sub(idle))
+}
+
+/// Auto-refresh period for follow-now mode, or `None` when disabled.
+///
+/// `--tick-seconds` (fractional) takes precedence over `--refresh-minutes`; 0 disables either.
+fn refresh_period(refresh_minutes: u64, tick_seconds: Option<f64>) -> Option<std::time::Duration> {
+    match tick_seconds {
+        Some(0.0) => None,
+        Some(secs) => Some(std::time::Duration::from_secs_f64(secs)),
+        None if refresh_minutes == 0 => None,
+        None => Some(std::time::Duration::from_secs(refresh_minutes * 60)),
+    }
+}
+
+/// How long the event loop may block waiting for input: until the next refresh tick,
+/// but never longer than one animation frame while the poem is animating.
+fn poll_timeout(
+    tick_rate: Option<std::time::Duration>,
+    since_tick: std::time::Duration,
+    animating: bool,
+) -> std::time::Duration {
+    let base = match tick_rate {
+        Some(rate) => rate.saturating_sub(since_tick),
+        None => IDLE_POLL,
+    };
+    if animating { base.min(ANIM_RATE) } else { base }
+}
+
+fn parse_tick_seconds(s: &str) -> Result<f64, String> {
+    let secs: f64 = s
+        .parse()
+        .map_err(|_| format!("`{s}` is not a number of seconds"))?;
+    if secs == 0.0 || (secs.is_finite() && (MIN_TICK_SECONDS..=MAX_REFRESH_MINUTES as f64 * 60.0).contains(&secs)) {
+        Ok(secs)
+    } else {
+        Err(format!(
+            "tick must be 0 (disabled) or between {MIN_TICK_SECONDS} and {} seconds",
+            MAX_REFRESH_MINUTES * 60
+        ))
+    }
+}
+
+fn parse_slide_seconds(s: &str) -> Result<f64, String> {
+    parse_period_seconds(s, "slideshow period")
+}
+
+fn parse_repeat_seconds(s: &str) -> Result<f64, String> {
+    parse_period_seconds(s, "repeat period")
+}
+
+/// A period in (fractional) seconds between `MIN_TICK_SECONDS` and a week (like `--tick`).
+fn parse_period_seconds(s: &str, what: &str) -> Result<f64, String> {
+    let secs: f64 = s
+        .parse()
+        .map_err(|_| format!("`{s}` is not a number of seconds"))?;
+    if secs.is_finite() && (MIN_TICK_SECONDS..=MAX_REFRESH_MINUTES as f64 * 60.0).contains(&secs) {
+        Ok(secs)
+    } else {
+        Err(format!(
+            "{what} must be between {MIN_TICK_SECONDS} and {} seconds",
+            MAX_REFRESH_MINUTES * 60
+        ))
+    }
+}
+
+/// Everything the TUI lets you change, with one method per action. `run_app` owns the
+/// terminal and the timers; key presses act on this.
+struct AppState {
+    date: DateTime<Utc>,
+    // Following the clock ("Now (auto)") rather than a date picked with the arrows.
+    follow_now: bool,
+    shadow: ShadowMode,
+    rotation: f64,
+    show_labels: bool,
+    selected_feature: Option<usize>,
+    // How many features `[`/`]` leave labelled, most prominent first.
+    label_count: usize,
+    show_markers: bool,
+    show_info: bool,
+    // Illumination percentage drawn under the disc, for when the Details panel is hidden.
+    show_percent: bool,
+    // Month calendar of phases in place of the moon; the arrows then step whole months.
+    show_calendar: bool,
+    language: Language,
+    // Poems cycle languages independently of the feature labels.
+    poem_language: Language,
+    show_poem: bool,
+    match_phase: bool,
+    // Motion freeze for the poem panel: stops the glow cycling and twinkles
+    // (and the line reveal too with `--freeze-reveal`).
+    poem_frozen: bool,
+    poem_order: PoemOrder,
+    poem_library: PoemLibrary,
+    poem_state: PoemViewState,
+    // Poem search: the prompt being typed after `/`, and the query `P`/`O` are limited to.
+    search_input: Option<String>,
+    poem_filter: Option<String>,
+    slide_paused: bool,
+    // Full-moon celebration: a brief brighter moon (and optional bell) once per full moon.
+    flash_until: Option<Instant>,
+    last_full_flash: Option<DateTime<Utc>>,
+    // Transient one-line notice (e.g. date stepping hit the supported range) and when it expires.
+    status_message: Option<(&'static str, Instant)>,
+    // Animation pauses while the terminal window is unfocused (if it reports focus at all).
+    focused: bool,
+    // `--precision`, for every phase the TUI computes.
+    precision: Precision,
+}
+
+impl AppState {
+    fn new(args: &Args, date: DateTime<Utc>, follow_now: bool, poem_library: PoemLibrary) -> Self {
+        let language = Language::English;
+        let mut poem_state = PoemViewState {
+            poem: pick_poem(&poem_library, language, None),
+            glow_phase: 0,
+            last_anim: Instant::now(),
+            twinkle_seed: rand::random::<u64>(),
+            twinkles: Vec::new(),
+            line_fade: Vec::new(),
+            fade_idx: 0,
+            fade_step: 0,
+            last_fade: Instant::now(),
+            fade_pause_until: None,
+            sequence: None,
+        };
+        reset_poem_fade(&mut poem_state);
+        AppState {
+            date,
+            // A slideshow drives the date itself.
+            follow_now: follow_now && args.loop_dates.is_none(),
+            shadow: args.shadow_mode(),
+            rotation: args.rotate,
+            show_labels: false,
+            selected_feature: None,
+            label_count: LUNAR_FEATURES.len(),
+            show_markers: args.markers,
+            show_info: true,
+            show_percent: false,
+            show_calendar: args.calendar,
+            language,
+            poem_language: language,
+            show_poem: false,
+            match_phase: false,
+            poem_frozen: false,
+            poem_order: args.poem_order,
+            poem_library,
+            poem_state,
+            search_input: None,
+            poem_filter: None,
+            slide_paused: false,
+            flash_until: None,
+            last_full_flash: None,
+            status_message: None,
+            focused: true,
+            precision: args.precision,
+        }
+    }
+
+    fn show_message(&mut self, message: &'static str) {
+        self.status_message = Some((message, Instant::now() + STATUS_MESSAGE_DURATION));
+    }
+
+    /// Drop the full-moon flash and the status message once they've run their time.
+    /// Returns whether anything went away.
+    fn expire_transients(&mut self) -> bool {
+        let now = Instant::now();
+        let mut expired = false;
+        if self.flash_until.is_some_and(|until| now >= until) {
+            self.flash_until = None;
+            expired = true;
+        }
+        if self.status_message.is_some_and(|(_, until)| now >= until) {
+            self.status_message = None;
+            expired = true;
+        }
+        expired
+    }
+
+    /// Catch up with the clock, announcing principal phases (`--notify`) and celebrating
+    /// the full moon (`--bell`) on the way.
+    fn refresh_now(&mut self, args: &Args) {
+        let previous = calculate_moon_phase_with(self.date, self.precision);
+        self.date = Utc::now();
+        let current = calculate_moon_phase_with(self.date, self.precision);
+        if args.notify
+            && let Some(phase) = principal_phase_reached(previous.phase, current.phase)
+        {
+            notify_desktop(&format!("{} now", phase.name()));
+        }
+        if full_moon_reached(previous.illumination, current.illumination)
+            && self.last_full_flash.is_none_or(|at| self.date - at > Duration::days(FULL_FLASH_MIN_GAP_DAYS))
+        {
+            self.last_full_flash = Some(self.date);
+            self.flash_until = Some(Instant::now() + FULL_FLASH_DURATION);
+            if args.bell {
+                let _ = write!(io::stdout(), "\x07").and_then(|_| io::stdout().flush());
+            }
+        }
+    }
+
+    /// Back to following the clock (`n`).
+    fn follow_clock(&mut self) {
+        self.follow_now = true;
+        self.date = Utc::now();
+    }
+
+    /// Move the date by whole days and switch to manual. At the edge of the supported range
+    /// the date stays put with a notice; returns whether it moved.
+    fn step_day(&mut self, days: i64) -> bool {
+        self.follow_now = false;
+        match step_date(self.date, days) {
+            Ok(stepped) => {
+                self.date = stepped;
+                true
+            }
+            Err(message) => {
+                self.show_message(message);
+                false
+            }
+        }
+    }
+
+    /// `r`: back to the view the app started with (labels, shadow, rotation, overlays, panels
+    /// and languages), keeping the date, the poem and its search.
+    fn reset_view(&mut self, args: &Args) {
+        self.show_labels = false;
+        self.selected_feature = None;
+        self.label_count = LUNAR_FEATURES.len();
+        self.shadow = args.shadow_mode();
+        self.rotation = args.rotate;
+        self.show_markers = args.markers;
+        self.show_info = true;
+        self.show_percent = false;
+        self.show_calendar = args.calendar;
+        self.show_poem = false;
+        self.language = Language::English;
+        self.poem_language = Language::English;
+    }
+
+    fn toggle_labels(&mut self) {
+        self.show_labels = !self.show_labels;
+    }
+
+    /// Tab cycles through the labelled features only.
+    fn select_feature(&mut self, forward: bool) {
+        self.selected_feature = Some(cycle_index(self.selected_feature, self.label_count, forward));
+    }
+
+    /// `]`/`[`: label one more / one fewer feature (at least one). Returns whether the count changed.
+    fn adjust_label_count(&mut self, more: bool) -> bool {
+        let count = if more { self.label_count + 1 } else { self.label_count.saturating_sub(1) };
+        let count = count.clamp(1, LUNAR_FEATURES.len());
+        if self.selected_feature.is_some_and(|i| i >= count) {
+            self.selected_feature = None;
+        }
+        std::mem::replace(&mut self.label_count, count) != count
+    }
+
+    /// Label language only; the poem keeps its own language (see `cycle_poem_language`).
+    fn cycle_language(&mut self) {
+        self.language = self.language.next();
+    }
+
+    fn cycle_poem_language(&mut self) {
+        self.poem_language = self.poem_language.next();
+        if self.show_poem {
+            self.next_poem();
+        }
+    }
+
+    fn toggle_markers(&mut self) {
+        self.show_markers = !self.show_markers;
+    }
+
+    fn toggle_info(&mut self) {
+        self.show_info = !self.show_info;
+    }
+
+    fn toggle_calendar(&mut self) {
+        self.show_calendar = !self.show_calendar;
+    }
+
+    /// Move to the same day of the next or previous month (clamped to its length), like `step_day`.
+    fn step_month(&mut self, forward: bool) -> bool {
+        let day = self.date.date_naive();
+        let target = if forward {
+            day.checked_add_months(Months::new(1))
+        } else {
+            day.checked_sub_months(Months::new(1))
+        };
+        let days = target.map_or(if forward { 31 } else { -31 }, |t| (t - day).num_days());
+        self.step_day(days)
+    }
+
+    fn toggle_percent(&mut self) {
+        self.show_percent = !self.show_percent;
+    }
+
+    fn cycle_shadow(&mut self) {
+        self.shadow = self.shadow.next();
+    }
+
+    fn rotate(&mut self, degrees: f64) {
+        self.rotation = normalize_rotation(self.rotation + degrees);
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        if focused {
+            // Resume where the animation left off rather than catching up in a burst.
+            self.poem_state.last_anim = Instant::now();
+            self.poem_state.last_fade = Instant::now();
+        }
+    }
+
+    /// The phase poems are matched to while `M` is on.
+    fn poem_phase(&self) -> Option<MoonPhase> {
+        self.match_phase.then(|| calculate_moon_phase_with(self.date, self.precision).phase)
+    }
+
+    /// A new poem in `--poem-order` (`P`).
+    fn next_poem(&mut self) {
+        let phase = self.poem_phase();
+        show_next_poem(&mut self.poem_state, &self.poem_library, self.poem_language, phase, self.poem_order);
+    }
+
+    fn toggle_poem(&mut self) {
+        self.show_poem = !self.show_poem;
+        if self.show_poem {
+            self.next_poem();
+        }
+    }
+
+    fn toggle_match_phase(&mut self) {
+        self.match_phase = !self.match_phase;
+        if self.show_poem && self.match_phase {
+            self.next_poem();
+        }
+    }
+
+    fn toggle_poem_freeze(&mut self) {
+        self.poem_frozen = !self.poem_frozen;
+    }
+
+    /// With a search active, step through its matches; without one, through the whole
+    /// library in list order (`P`/`O`).
+    fn step_poem(&mut self, forward: bool) {
+        let poems = self.poem_library.for_language(self.poem_language);
+        let query = self.poem_filter.as_deref().unwrap_or("");
+        match next_match(poems, self.poem_state.sequence, self.poem_language, query, forward) {
+            Some(i) => {
+                self.poem_state.sequence = Some((self.poem_language, i));
+                start_poem(&mut self.poem_state, poems[i].clone());
+            }
+            None => self.show_message("No poems match that search"),
+        }
+    }
+
+    fn open_search(&mut self) {
+        self.search_input = Some(String::new());
+    }
+
+    /// Edit the open search prompt. Enter applies the query and jumps to its first match
+    /// (an empty query restores the full set); Esc closes the prompt unchanged.
+    fn search_key(&mut self, code: KeyCode) {
+        let Some(input) = self.search_input.as_mut() else { return };
+        match code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Esc => self.search_input = None,
+            KeyCode::Enter => {
+                let query = input.trim().to_string();
+                self.search_input = None;
+                if query.is_empty() {
+                    self.poem_filter = None;
+                    return;
+                }
+                let poems = self.poem_library.for_language(self.poem_language);
+                match next_match(poems, None, self.poem_language, &query, true) {
+                    Some(i) => {
+                        self.poem_state.sequence = Some((self.poem_language, i));
+                        start_poem(&mut self.poem_state, poems[i].clone());
+                        self.poem_filter = Some(query);
+                    }
+                    None => self.show_message("No poems match that search"),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    args: &Args,
+    date: DateTime<Utc>,
+    follow_now: bool,
+) -> io::Result<()> {
+    let mut state = AppState::new(args, date, follow_now, load_poem_library(args));
+    let libration = args.libration;
+    let theme = resolve_theme(args.theme);
+    let truecolor = supports_truecolor();
+    let tick_rate = refresh_period(args.refresh_minutes, args.tick_seconds);
+    let mut last_tick = Instant::now();
+
+    // Slideshow (`--loop-dates`): step forward one calendar day per period.
+    let slide_period = args.loop_dates.map(std::time::Duration::from_secs_f64);
+    let mut last_slide = Instant::now();
+    // Redraws are coalesced so no two frames are closer than 1/--max-fps apart.
+    let mut last_draw: Option<Instant> = None;
+    let mut needs_redraw = true;
+    // `--breathe` advances on the poem animation's cadence.
+    let breath_start = Instant::now();
+    let mut last_breath = Instant::now();
+    // `--blank-after`: keypresses reset the idle timer; blanked, only the phase glyph is drawn.
+    let mut last_key = Instant::now();
+    let mut blanked = false;
+    loop {
+        if !blanked && blank_wait(args.blank_after, last_key.elapsed()).is_some_and(|wait| wait.is_zero()) {
+            blanked = true;
+            needs_redraw = true;
+        }
+        // Animations rest while the window is in the background or the screen is blanked.
+        let awake = state.focused && !blanked;
+
+        // Poem animation: slow, romantic, peaceful.
+        // - Gentle breathing glow (slow phase increment)
+        // - Fade-in by line
+        let reveal_frozen = (state.poem_frozen && args.freeze_reveal) || !awake;
+        if state.show_poem && awake && !state.poem_frozen && !args.readable_poems && state.poem_state.last_anim.elapsed() >= ANIM_RATE {
+            state.poem_state.last_anim = Instant::now();
+            state.poem_state.glow_phase = state.poem_state.glow_phase.wrapping_add(1);
+            needs_redraw = true;
+        }
+
+        let breathing = args.breathe && awake && !state.show_calendar;
+        if breathing && last_breath.elapsed() >= ANIM_RATE {
+            last_breath = Instant::now();
+            needs_redraw = true;
+        }
+
+        // Advance fade for the current line on its own cadence, with a pause between lines.
+        if state.show_poem && !reveal_frozen && state.poem_state.last_fade.elapsed() >= FADE_RATE {
+            // Respect inter-line pause if set.
+            if let Some(until) = state.poem_state.fade_pause_until {
+                if Instant::now() < until {
+                    // Still pausing.
+                } else {
+                    state.poem_state.fade_pause_until = None;
+                }
+            }
+
+            if state.poem_state.fade_pause_until.is_none() && state.poem_state.fade_idx < state.poem_state.poem.lines.len() {
+                state.poem_state.last_fade = Instant::now();
+                state.poem_state.fade_step = state.poem_state.fade_step.saturating_add(1);
+                let level = state.poem_state.fade_step.min(LINE_FADE_STEPS);
+                if let Some(slot) = state.poem_state.line_fade.get_mut(state.poem_state.fade_idx) {
+                    *slot = level;
+                }
+                if state.poem_state.fade_step >= LINE_FADE_STEPS {
+                    state.poem_state.fade_idx += 1;
+                    state.poem_state.fade_step = 0;
+                    state.poem_state.fade_pause_until = Some(Instant::now() + LINE_GAP);
+                }
+                needs_redraw = true;
+            }
+        }
+
+        if needs_redraw && frame_wait(last_draw.map(|t| t.elapsed()), args.max_fps).is_zero() {
+            last_draw = Some(Instant::now());
+            terminal.draw(|f| {
+                // Clear the whole frame first so style modifiers from the previous layout
+                // can't affect the new layout (macOS Terminal is especially sensitive to this).
+                f.render_widget(Clear, f.size());
+
+                if blanked {
+                    // Just a dim phase glyph mid-screen, so an always-on display doesn't burn in.
+                    let glyph = if args.dumb_terminal { "o" } else { calculate_moon_phase_with(state.date, state.precision).phase.symbol() };
+                    let middle = Rect { y: f.size().height / 2, height: 1.min(f.size().height), ..f.size() };
+                    f.render_widget(
+                        Paragraph::new(Span::styled(glyph, Style::default().fg(Color::DarkGray).add_modifier(Modifier::DIM)))
+                            .alignment(Alignment::Center),
+                        middle,
+                    );
+                    return;
+                }
+
+                // `--dumb-terminal` drops the box-drawing borders; titles still mark the panels.
+                let borders = if args.dumb_terminal { Borders::NONE } else { Borders::ALL };
+                let (main_area, info_area, info_beside) = tui_layout(f.size(), state.show_info, args.cell_ratio);
+                let chunks = [main_area, info_area];
+
+                let moon = calculate_moon_phase_with(state.date, state.precision);
+
+                // Drop the poem pane when there's no room for both minimums.
+                let poem_visible = state.show_poem && chunks[0].width >= MIN_POEM_LAYOUT_WIDTH;
+
+                // Main content area: Moon on the left, optional poem panel on the right.
+                let main_cols = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints(if poem_visible {
+                        // Ensure both panes have a minimum; moon will "shrink" naturally.
+                        vec![Constraint::Min(18), Constraint::Min(28)]
+                    } else {
+                        vec![Constraint::Percentage(100), Constraint::Min(0)]
+                    })
+                    .split(chunks[0]);
+
+                // With labels on, reserve the bottom row of the moon pane for a legend
+                // so it never overlaps the disc.
+                let moon_area = if state.show_labels && !state.show_calendar && main_cols[0].height >= 2 {
+                    let rows = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Min(1), Constraint::Length(1)])
+                        .split(main_cols[0]);
+                    f.render_widget(
+                        Paragraph::new(Line::from(vec![
+                            Span::styled("x", Style::default().fg(Color::Red)),
+                            Span::styled(
+                                format!(" = {}", FEATURE_LEGEND[state.language as usize]),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                        ]))
+                        .alignment(Alignment::Center),
+                        rows[1],
+                    );
+                    rows[0]
+                } else {
+                    main_cols[0]
+                };
+
+                if state.show_calendar {
+                    let today = in_display_tz(Utc::now(), args.tz).date_naive();
+                    let shown = in_display_tz(state.date, args.tz).date_naive();
+                    f.render_widget(
+                        Paragraph::new(calendar_lines(shown, today, args.dumb_terminal, state.precision)).alignment(Alignment::Center),
+                        moon_area,
+                    );
+                } else {
+                    // Render Custom Moon Widget
+                    let widget = MoonWidget {
+                        status: moon,
+                        options: RenderOptions {
+                            show_labels: state.show_labels,
+                            language: state.language,
+                            shadow: state.shadow,
+                            truecolor,
+                            libration,
+                            rotation: state.rotation,
+                            cell_ratio: args.cell_ratio,
+                            show_markers: state.show_markers,
+                            selected_feature: state.selected_feature.filter(|_| state.show_labels),
+                            label_limit: Some(state.label_count),
+                            smooth: args.smooth,
+                            flash: state.flash_until.is_some(),
+                            warm: args.warm,
+                            southern: Hemisphere::from_args(args) == Hemisphere::South,
+                            max_width: args.max_moon_size,
+                            dumb: args.dumb_terminal,
+                            light_bg: args.light_bg,
+                            lod: args.lod,
+                            no_mask: args.no_mask,
+                            density_by_illum: args.density_by_illum,
+                            limb_darkening: args.limb_darkening,
+                            terminator: args.terminator,
+                            shrink: if args.breathe { breath_shrink(breath_start.elapsed()) } else { 0.0 },
+                            stars: args.stars,
+                        },
+                    };
+                    if args.both_hemispheres && moon_area.height >= 2 {
+                        // Northern view on the left, southern on the right, each under its caption.
+                        let halves = Layout::default()
+                            .direction(Direction::Horizontal)
+                            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                            .split(moon_area);
+                        for ((half, southern), caption) in halves.iter().zip([false, true]).zip(HEMISPHERE_CAPTIONS) {
+                            f.render_widget(
+                                Paragraph::new(Span::styled(caption, Style::default().fg(Color::DarkGray)))
+                                    .alignment(Alignment::Center),
+                                Rect { height: 1, ..*half },
+                            );
+                            let options = RenderOptions { southern, ..widget.options };
+                            f.render_widget(
+                                MoonWidget { options, ..widget },
+                                Rect { y: half.y + 1, height: half.height - 1, ..*half },
+                            );
+                        }
+                    } else {
+                        f.render_widget(widget, moon_area);
+                    }
+                    if state.show_percent
+                        && let Some(disc) = disc_box(moon_area, args.cell_ratio, args.max_moon_size)
+                    {
+                        let row = Rect { y: overlay_row(moon_area, disc), height: 1, ..moon_area };
+                        f.render_widget(
+                            Paragraph::new(Span::styled(
+                                format!(" {:.1}% ", round_illumination(moon.illumination)),
+                                Style::default().fg(Color::Black).bg(moon_lit_color(truecolor)).add_modifier(Modifier::BOLD),
+                            ))
+                            .alignment(Alignment::Center),
+                            row,
+                        );
+                    }
+                }
+                if let Some((msg, _)) = state.status_message {
+                    let row = Rect { height: 1.min(moon_area.height), ..moon_area };
+                    f.render_widget(
+                        Paragraph::new(Span::styled(msg, Style::default().fg(Color::Black).bg(Color::Yellow)))
+                            .alignment(Alignment::Center),
+                        row,
+                    );
+                }
+
+                if poem_visible {
+                    let (title_c, _, dim_c) =
+                        poem_palette(state.poem_state.glow_phase, theme, truecolor, args.readable_poems);
+                    let border_style = if args.static_poem_border {
+                        Style::default().fg(poem_palette(0, theme, truecolor, args.readable_poems).0)
+                    } else {
+                        Style::default().fg(title_c)
+                    };
+                    // The phase glyph follows the state.date even while the poem stays put.
+                    let search = match (&state.search_input, &state.poem_filter) {
+                        (Some(input), _) => format!("/{input}_ "),
+                        (None, Some(query)) => format!("/{query} "),
+                        (None, None) => String::new(),
+                    };
+                    let glyph = if args.dumb_terminal { String::new() } else { format!("{} ", moon.phase.symbol()) };
+                    let block = poem_block(borders, args.poem_border)
+                        .title(format!(" {glyph}Moon Poem {search}"))
+                        .border_style(border_style);
+                    let inner = block.inner(main_cols[1]);
+                    f.render_widget(block, main_cols[1]);
+
+                    if inner.width >= 2 && inner.height >= 2 {
+                        let poem_lines = render_poem_lines_soft(
+                            &state.poem_state.poem,
+                            &state.poem_state.line_fade,
+                            state.poem_state.glow_phase,
+                            theme,
+                            truecolor,
+                            args.readable_poems,
+                        );
+                        let paragraph = Paragraph::new(poem_lines)
+                            .alignment(Alignment::Left)
+                            .style(Style::default().fg(dim_c))
+                            .wrap(ratatui::widgets::Wrap { trim: false });
+                        f.render_widget(paragraph, inner);
+
+                        // Persistent twinkles on blank space (none in readable mode).
+                        // We update based on the current pane size, then render after poem text.
+                        // Frozen twinkles keep their last positions.
+                        if !args.readable_poems && !args.dumb_terminal {
+                            if !state.poem_frozen {
+                                update_twinkles(&mut state.poem_state.twinkles, &mut state.poem_state.twinkle_seed, inner);
+                            }
+                            let buf = f.buffer_mut();
+                            render_twinkles(
+                                buf,
+                                inner,
+                                &state.poem_state.twinkles,
+                                state.poem_state.glow_phase,
+                                theme,
+                                truecolor,
+                            );
+                        }
+                    }
+                }
+
+                // Info Area
+                if state.show_info {
+                    let local_date = in_display_tz(state.date, args.tz);
+                    let mode = match (slide_period.is_some(), state.slide_paused, state.follow_now) {
+                        (true, false, _) => "Slideshow",
+                        (true, true, _) => "Slideshow (paused)",
+                        (false, _, true) => "Now (auto)",
+                        (false, _, false) => "Manual",
+                    };
+                    let mut info_text = vec![
+                        Line::from(vec![
+                            Span::raw("Date: "),
+                            Span::styled(
+                                local_date.format("%Y-%m-%d").to_string(),
+                                Style::default().add_modifier(Modifier::BOLD),
+                            ),
+                        ]),
+                        Line::from(vec![
+                            Span::raw("Mode: "),
+                            Span::styled(mode, Style::default().fg(Color::Green)),
+                        ]),
+                        Line::from(vec![
+                            Span::raw("Phase: "),
+                            Span::styled(moon.phase.name(), Style::default().fg(Color::Cyan)),
+                            Span::raw(format!(" (phase angle {:.0}°)", moon.phase_angle())),
+                        ]),
+                        Line::from(if args.show_sidereal {
+                            format!(
+                                "Age: {:.1} of {:.2} days synodic (Sun)  {:.1} of {:.2} sidereal (stars)",
+                                round_age(moon.synodic_age_days()),
+                                SYNODIC_MONTH,
+                                round_age(moon.sidereal_age_days()),
+                                SIDEREAL_MONTH
+                            )
+                        } else {
+                            format!("Age: {:.1} days", round_age(moon.age_days))
+                        }),
+                    ];
+                    // Optional rows only take a line while their feature is on: the panel is short.
+                    if args.zodiac {
+                        let (ingress, next_sign) = next_sign_ingress(state.date, state.precision);
+                        info_text.push(Line::from(format!(
+                            "Zodiac: {} (enters {} in {})",
+                            ZODIAC_SIGNS[moon.zodiac_sign()],
+                            ZODIAC_SIGNS[next_sign],
+                            format_countdown(ingress - state.date)
+                        )));
+                    }
+                    if state.rotation != 0.0 {
+                        info_text.push(Line::from(format!("Rotation: {:+.0}°", state.rotation)));
+                    }
+                    if state.show_labels && state.label_count < LUNAR_FEATURES.len() {
+                        info_text.push(Line::from(format!("Labels: {} of {}", state.label_count, LUNAR_FEATURES.len())));
+                    }
+                    if let Some(i) = state.selected_feature.filter(|_| state.show_labels) {
+                        let feature = &LUNAR_FEATURES[i];
+                        info_text.push(Line::from(format!(
+                            "Feature: {} ({:.1}°{}, {:.1}°{})",
+                            feature.names[state.language as usize],
+                            feature.lat.abs(),
+                            if feature.lat >= 0.0 { 'N' } else { 'S' },
+                            feature.lon.abs(),
+                            if feature.lon >= 0.0 { 'E' } else { 'W' },
+                        )));
+                    }
+                    info_text.extend([
+                        Line::from(format!(
+                            "Illumination: {:.1}% ({}, {})",
+                            round_illumination(moon.illumination),
+                            format_illumination_delta(illumination_delta_per_day(state.date, state.precision)),
+                            moon.trend_word(state.language)
+                        )),
+                        Line::from(format!("Shadow: {}", state.shadow.name())),
+                        Line::from(vec![
+                            Span::raw("Language: "),
+                            Span::styled(state.language.name(), Style::default().fg(Color::Green)),
+                            Span::raw("  Poem language: "),
+                            Span::styled(state.poem_language.name(), Style::default().fg(Color::Green)),
+                        ]),
+                        Line::from(""),
+                        Line::from(Span::styled(
+                            "Use <Left>/<Right> date (switches to Manual). <n> now (auto). <l> labels. <Tab>/<S-Tab> select feature. <[>/<]> fewer/more labels. <x> markers. <L> label language. <T> poem language. <d> shadow (full/earthshine/outline/hidden). <,>/<.> rotate. <p> poem. <P>/<O> next/previous poem. </> search poems. <g> freeze poem motion. <M> match poem to phase. <i> toggle info. <%> illumination on the moon. <c> month calendar (<Left>/<Right> step months). <r> reset the view. <q> quit.",
+                            Style::default().fg(Color::DarkGray),
+                        )),
+                    ]);
+
+                    let mut info_block = Paragraph::new(info_text)
+                        .block(Block::default().title(" Details ").borders(borders))
+                        .alignment(Alignment::Center);
+                    // The side panel is narrow but tall: wrap the long help line instead of cutting it.
+                    if info_beside {
+                        info_block = info_block.wrap(ratatui::widgets::Wrap { trim: true });
+                    }
+                    f.render_widget(info_block, chunks[1]);
+                }
+            })?;
+            needs_redraw = false;
+        }
+
+        // Timer tick: refresh "now" periodically
+        if let Some(tick_rate) = tick_rate
+            && last_tick.elapsed() >= tick_rate
+        {
+            last_tick = Instant::now();
+            if state.follow_now {
+                state.refresh_now(args);
+            }
+            needs_redraw = true;
+        }
+
+        if let Some(period) = slide_period
+            && !state.slide_paused
+            && last_slide.elapsed() >= period
+        {
+            last_slide = Instant::now();
+            if !state.step_day(1) {
+                state.slide_paused = true;
+            }
+            needs_redraw = true;
+        }
+
+        // Wait for input/resize up to the next tick
+        if state.expire_transients() {
+            needs_redraw = true;
+        }
+
+        let mut timeout = poll_timeout(tick_rate, last_tick.elapsed(), (state.show_poem && !reveal_frozen) || breathing);
+        for until in [state.flash_until, state.status_message.map(|(_, until)| until)].into_iter().flatten() {
+            timeout = timeout.min(until.saturating_duration_since(Instant::now()));
+        }
+        if let Some(period) = slide_period
+            && !state.slide_paused
+        {
+            timeout = timeout.min(period.saturating_sub(last_slide.elapsed()));
+        }
+        if !blanked && let Some(wait) = blank_wait(args.blank_after, last_key.elapsed()) {
+            timeout = timeout.min(wait);
+        }
+        // A frame held back by the cap is drawn as soon as it's allowed.
+        if needs_redraw {
+            timeout = timeout.min(frame_wait(last_draw.map(|t| t.elapsed()), args.max_fps));
+        }
+
+        if event::poll(timeout)? {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    last_key = Instant::now();
+                    // The key that wakes a blanked screen does nothing else.
+                    if blanked {
+                        blanked = false;
+                        needs_redraw = true;
+                        continue;
+                    }
+                    // While the search prompt is open, keys edit the query instead.
+                    if state.search_input.is_some() {
+                        state.search_key(key.code);
+                        needs_redraw = true;
+                        continue;
+                    }
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => {
+                            // Best effort: failing to save shouldn't stop the app from quitting.
+                            if args.remember_date
+                                && let Some(path) = last_date_path()
+                            {
+                                let _ = save_last_date(&path, state.date, state.follow_now);
+                            }
+                            return Ok(());
+                        }
+                        KeyCode::Char('/') if state.show_poem => state.open_search(),
+                        KeyCode::Char('l') => state.toggle_labels(),
+                        KeyCode::Tab if state.show_labels => state.select_feature(true),
+                        KeyCode::BackTab if state.show_labels => state.select_feature(false),
+                        KeyCode::Char(c @ ('[' | ']')) if state.show_labels => {
+                            state.adjust_label_count(c == ']');
+                        }
+                        KeyCode::Char('L') => state.cycle_language(),
+                        KeyCode::Char('T') => state.cycle_poem_language(),
+                        KeyCode::Char('M') => state.toggle_match_phase(),
+                        KeyCode::Char('g') if state.show_poem => state.toggle_poem_freeze(),
+                        KeyCode::Char(',') => state.rotate(-ROTATION_STEP),
+                        KeyCode::Char('.') => state.rotate(ROTATION_STEP),
+                        KeyCode::Char(' ') if slide_period.is_some() => {
+                            state.slide_paused = !state.slide_paused;
+                            last_slide = Instant::now();
+                        }
+                        KeyCode::Char('x') => state.toggle_markers(),
+                        KeyCode::Char('i') => state.toggle_info(),
+                        KeyCode::Char('%') => state.toggle_percent(),
+                        KeyCode::Char('c') => state.toggle_calendar(),
+                        KeyCode::Char('r') => state.reset_view(args),
+                        KeyCode::Char('d') => state.cycle_shadow(),
+                        KeyCode::Char('p') => state.toggle_poem(),
+                        KeyCode::Char('P') if state.show_poem && state.poem_filter.is_none() => state.next_poem(),
+                        KeyCode::Char('P') if state.show_poem => state.step_poem(true),
+                        KeyCode::Char('O') if state.show_poem => state.step_poem(false),
+                        KeyCode::Char('n') => {
+                            state.follow_clock();
+                            last_tick = Instant::now();
+                        }
+                        KeyCode::Left if state.show_calendar => {
+                            state.step_month(false);
+                        }
+                        KeyCode::Right if state.show_calendar => {
+                            state.step_month(true);
+                        }
+                        KeyCode::Left => {
+                            state.step_day(-1);
+                        }
+                        KeyCode::Right => {
+                            state.step_day(1);
+                        }
+                        // Nothing changed: skip the redraw.
+                        _ => continue,
+                    }
+                    needs_redraw = true;
+                }
+                Event::Resize(_, _) => {
+                    needs_redraw = true;
+                }
+                Event::FocusLost => state.set_focused(false),
+                Event::FocusGained => {
+                    state.set_focused(true);
+                    needs_redraw = true;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Background counterpart of `color_to_ansi_fg`: the same palette, 10 codes up.
+fn color_to_ansi_bg(color: Color) -> String {
+    match color {
+        Color::Reset | Color::Indexed(_) => "\x1b[49m".to_string(),
+        Color::Rgb(r, g, b) => format!("\x1b[48;2;{};{};{}m", r, g, b),
+        _ => {
+            let fg = color_to_ansi_fg(color);
+            let code: u8 = fg[2..fg.len() - 1].parse().unwrap_or(39);
+            format!("\x1b[{}m", code + 10)
+        }
+    }
+}
+
+// Helper function to convert ratatui::style::Color to ANSI foreground code
+fn color_to_ansi_fg(color: Color) -> String {
+    match color {
+        Color::Reset => "\x1b[39m".to_string(),
+        Color::Black => "\x1b[30m".to_string(),
+        Color::Red => "\x1b[31m".to_string(),
+        Color::Green => "\x1b[32m".to_string(),
+        Color::Yellow => "\x1b[33m".to_string(),
+        Color::Blue => "\x1b[34m".to_string(),
+        Color::Magenta => "\x1b[35m".to_string(),
+        Color::Cyan => "\x1b[36m".to_string(),
+        Color::Gray => "\x1b[90m".to_string(), // Bright Black
+        Color::DarkGray => "\x1b[30m".to_string(), // Often same as Black
+        Color::LightRed => "\x1b[91m".to_string(),
+        Color::LightGreen => "\x1b[92m".to_string(),
+        Color::LightYellow => "\x1b[93m".to_string(),
+        Color::LightBlue => "\x1b[94m".to_string(),
+        Color::LightMagenta => "\x1b[95m".to_string(),
+        Color::LightCyan => "\x1b[96m".to_string(),
+        Color::White => "\x1b[97m".to_string(),
+        Color::Rgb(r, g, b) => format!("\x1b[38;2;{};{};{}m", r, g, b),
+        Color::Indexed(_) => "\x1b[39m".to_string(), // Default to reset
+    }
+}
+
+/// The moon widget for non-interactive output, configured from the command line.
+fn print_widget(args: &Args, date: DateTime<Utc>) -> MoonWidget {
+    MoonWidget {
+        status: calculate_moon_phase_with(date, args.precision),
+        options: RenderOptions {
+            show_labels: false,
+            language: Language::English,
+            shadow: args.shadow_mode(),
+            truecolor: supports_truecolor(),
+            libration: args.libration,
+            rotation: args.rotate,
+            cell_ratio: args.cell_ratio,
+            show_markers: args.markers,
+            selected_feature: None,
+            label_limit: None,
+            smooth: args.smooth,
+            flash: false,
+            warm: args.warm,
+            southern: Hemisphere::from_args(args) == Hemisphere::South,
+            max_width: args.max_moon_size,
+            dumb: args.dumb_terminal,
+            light_bg: args.light_bg,
+            lod: args.lod,
+            no_mask: args.no_mask,
+            density_by_illum: args.density_by_illum,
+            limb_darkening: args.limb_darkening,
+            terminator: args.terminator,
+            shrink: 0.0,
+            stars: args.stars,
+        },
+    }
+}
+
+// Illumination (percent) counted as "the" full moon moment, and how the flash behaves.
+const FULL_FLASH_THRESHOLD: f64 = 99.9;
+const FULL_FLASH_DURATION: std::time::Duration = std::time::Duration::from_secs(2);
+// Illumination can wobble around the threshold between ticks; one flash per lunation.
+const FULL_FLASH_MIN_GAP_DAYS: i64 = 20;
+
+/// Whether illumination rose through `FULL_FLASH_THRESHOLD` between two ticks.
+fn full_moon_reached(previous: f64, current: f64) -> bool {
+    previous < FULL_FLASH_THRESHOLD && current >= FULL_FLASH_THRESHOLD
+}
+
+/// The principal phase just entered between two ticks, if any.
+fn principal_phase_reached(previous: MoonPhase, current: MoonPhase) -> Option<MoonPhase> {
+    (previous != current && current.is_principal()).then_some(current)
+}
+
+/// Best-effort desktop notification via the platform's own tool; silently does
+/// nothing where there is none (or it fails).
+fn notify_desktop(message: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut c = std::process::Command::new("osascript");
+        c.arg("-e")
+            .arg(format!("display notification {} with title \"ascii_moon\"", json_string(message)));
+        c
+    } else if cfg!(all(unix, not(target_os = "macos"))) {
+        let mut c = std::process::Command::new("notify-send");
+        c.arg("ascii_moon").arg(message);
+        c
+    } else {
+        return;
+    };
+    let spawned = command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+    // Reap the helper off the UI thread so it doesn't linger as a zombie.
+    if let Ok(mut child) = spawned {
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+    }
+}
+
+fn print_buffer(lines: u16, widget: MoonWidget, max_width: u16) -> Buffer {
+    let aspect = widget.options.cell_ratio.aspect();
+    // A `--max-moon-size` cap trims the rows too, rather than padding them out blank.
+    let lines = match widget.options.max_width {
+        Some(max) => lines.min((max as f64 / aspect).ceil() as u16).max(1),
+        None => lines,
+    };
+    // Make the box as wide as a round disc needs for this cell shape,
+    // but at least one column so a 1-line render is still a sliver rather than nothing.
+    let width = (lines as f64 * aspect) as u16;
+    let width = width.min(max_width).max(1);
+
+    let area = Rect::new(0, 0, width, lines);
+    let mut buffer = Buffer::empty(area);
+    widget.render(area, &mut buffer);
+    buffer
+}
+
+/// Width printed moons must fit in: `--assume-cols`, else the terminal's.
+fn print_columns(args: &Args) -> u16 {
+    // In non-TTY scenarios, `size()` can fail; fall back to a reasonable default.
+    args.assume_cols.unwrap_or_else(|| crossterm::terminal::size().map_or(80, |(cols, _)| cols))
+}
+
+fn print_moon<W: Write>(out: &mut W, lines: u16, widget: MoonWidget, max_width: u16) -> io::Result<()> {
+    // Don't let the width exceed the terminal width, or lines wrap into garbage.
+    let plain = widget.options.dumb;
+    let buffer = print_buffer(lines, widget, max_width);
+    write_ansi(out, &buffer, plain)?;
+    out.flush()
+}
+
+// `--both-hemispheres`: captions over the northern and southern views, and the columns between them.
+const HEMISPHERE_CAPTIONS: [&str; 2] = ["Northern hemisphere", "Southern hemisphere"];
+const HEMISPHERE_GAP: u16 = 4;
+
+/// `--both-hemispheres` in print mode: the northern and southern views of `widget` side by side,
+/// captioned, together no wider than `max_width`.
+fn print_hemispheres<W: Write>(out: &mut W, lines: u16, widget: MoonWidget, max_width: u16) -> io::Result<()> {
+    let half = max_width.saturating_sub(HEMISPHERE_GAP) / 2;
+    let views = [false, true].map(|southern| {
+        print_buffer(lines, MoonWidget { options: RenderOptions { southern, ..widget.options }, ..widget }, half)
+    });
+    let width = views[0].area.width + HEMISPHERE_GAP + views[1].area.width;
+    let mut sheet = Buffer::empty(Rect::new(0, 0, width, views[0].area.height + 1));
+    let mut left = 0;
+    for (view, caption) in views.iter().zip(HEMISPHERE_CAPTIONS) {
+        let w = view.area.width;
+        if let Some(caption) = truncate_to_width(caption, w as usize) {
+            let x = left + (w - caption.width() as u16) / 2;
+            sheet.set_string(x, 0, caption, Style::default().fg(Color::DarkGray));
+        }
+        for y in 0..view.area.height {
+            for x in 0..w {
+                *sheet.get_mut(left + x, y + 1) = view.get(x, y).clone();
+            }
+        }
+        left += w + HEMISPHERE_GAP;
+    }
+    write_ansi(out, &sheet, widget.options.dumb)?;
+    out.flush()
+}
+
+/// Write `buffer` as text with ANSI colours (none when `plain`), one line per row.
+fn write_ansi<W: Write>(out: &mut W, buffer: &Buffer, plain: bool) -> io::Result<()> {
+    let area = buffer.area;
+    for y in 0..area.height {
+        // Colours are reset at each line end, so start every row from the defaults.
+        let (mut last_fg, mut last_bg) = (Color::Reset, Color::Reset);
+        for x in 0..area.width {
+            let cell = buffer.get(x, y);
+            if cell.fg != last_fg && !plain {
+                write!(out, "{}", color_to_ansi_fg(cell.fg))?;
+                last_fg = cell.fg;
+            }
+            if cell.bg != last_bg && !plain {
+                write!(out, "{}", color_to_ansi_bg(cell.bg))?;
+                last_bg = cell.bg;
+            }
+            write!(out, "{}", cell.symbol())?;
+        }
+        if plain {
+            writeln!(out)?;
+        } else {
+            writeln!(out, "\x1b[0m")?; // Reset color at end of line and print newline
+        }
+    }
+    Ok(())
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// `--pretty`: re-indent compact JSON (as built by the `*_json` functions) two spaces per level.
+/// Only whitespace changes; empty objects and arrays stay on one line.
+fn pretty_json(compact: &str) -> String {
+    let mut out = String::with_capacity(compact.len() * 2);
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut chars = compact.chars().peekable();
+    let newline = |out: &mut String, depth: usize| {
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth));
+    };
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '{' | '[' => {
+                out.push(c);
+                if matches!(chars.peek(), Some('}' | ']')) {
+                    out.extend(chars.next());
+                } else {
+                    depth += 1;
+                    newline(&mut out, depth);
+                }
+            }
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                newline(&mut out, depth);
+                out.push(c);
+            }
+            ',' => {
+                out.push(c);
+                newline(&mut out, depth);
+            }
+            ':' => out.push_str(": "),
+            c if c.is_whitespace() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// `--dump-buffer`: `buffer` as `{"width":W,"height":H,"rows":[[{"char":..,"fg":..},..],..]}`,
+/// one array per row. Colours use ratatui's names (`"Yellow"`), `"#RRGGBB"`, or a 256-colour index (`"214"`).
+fn buffer_json(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let rows: Vec<String> = (0..area.height)
+        .map(|y| {
+            let cells: Vec<String> = (0..area.width)
+                .map(|x| {
+                    let cell = buffer.get(x, y);
+                    format!(
+                        "{{\"char\":{},\"fg\":{}}}",
+                        json_string(cell.symbol()),
+                        json_string(&cell.fg.to_string())
+                    )
+                })
+                .collect();
+            format!("[{}]", cells.join(","))
+        })
+        .collect();
+    format!("{{\"width\":{},\"height\":{},\"rows\":[{}]}}", area.width, area.height, rows.join(","))
+}
+
+fn status_json(date: DateTime<Utc>, moon: &MoonStatus) -> String {
+    format!(
+        "{{\"date\":{},\"phase\":{},\"phase_fraction\":{:.4},\"age_days\":{:.1},\"illumination\":{:.1}}}",
+        json_string(&date.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+        json_string(moon.phase.name()),
+        moon.phase_fraction,
+        round_age(moon.age_days),
+        round_illumination(moon.illumination),
+    )
+}
+
+fn describe_moon(date: DateTime<Utc>, tz: Option<FixedOffset>, moon: &MoonStatus) -> String {
+    let local_date = in_display_tz(date, tz);
+    format!(
+        "On {} the moon phase is {} ({:.1}% illuminated), {:.1} days into the lunar cycle.",
+        local_date.format("%A, %B %-d, %Y"),
+        moon.phase.name(),
+        round_illumination(moon.illumination),
+        round_age(moon.age_days),
+    )
+}
+
+// Upper bound for `--forecast`: about a lunar month ahead.
+const MAX_FORECAST_DAYS: u16 = 30;
+
+/// `--forecast`: one short sentence per following day, e.g. "Tomorrow: Full Moon (99.8%).".
+fn describe_forecast(date: DateTime<Utc>, days: u16, precision: Precision) -> String {
+    (1..=days)
+        .map(|day| {
+            let moon = calculate_moon_phase_with(date + Duration::days(day as i64), precision);
+            let when = if day == 1 { "Tomorrow".to_string() } else { format!("In {day} days") };
+            format!("{when}: {} ({:.1}%).", moon.phase.name(), round_illumination(moon.illumination))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn print_status(format: OutputFormat, args: &Args, date: DateTime<Utc>) -> io::Result<()> {
+    let moon = calculate_moon_phase_with(date, args.precision);
+    let mut stdout = io::stdout();
+    match format {
+        OutputFormat::Ascii => {
+            let lines = args.lines.unwrap_or(DEFAULT_PRINT_LINES);
+            if args.both_hemispheres {
+                print_hemispheres(&mut stdout, lines, print_widget(args, date), print_columns(args))?;
+            } else {
+                print_moon(&mut stdout, lines, print_widget(args, date), print_columns(args))?;
+            }
+            if args.print_details {
+                writeln!(stdout)?;
+                for line in details_lines(date, args.tz, &moon) {
+                    writeln!(stdout, "{line}")?;
+                }
+            }
+        }
+        OutputFormat::Json => writeln!(stdout, "{}", args.json_text(status_json(date, &moon)))?,
+        OutputFormat::Emoji => writeln!(stdout, "{}", moon.phase.symbol())?,
+        OutputFormat::Name => writeln!(stdout, "{}", moon.phase.name())?,
+        OutputFormat::Table => {
+            for line in details_lines(date, args.tz, &moon) {
+                writeln!(stdout, "{line}")?;
+            }
+        }
+        OutputFormat::Describe => match args.forecast {
+            Some(days) => writeln!(stdout, "{} {}", describe_moon(date, args.tz, &moon), describe_forecast(date, days, args.precision))?,
+            None => writeln!(stdout, "{}", describe_moon(date, args.tz, &moon))?,
+        },
+    }
+    // JSON stays a single parseable object.
+    if args.verbose && format != OutputFormat::Json {
+        for line in verbose_lines(&moon) {
+            writeln!(stdout, "{}", line)?;
+        }
+    }
+    stdout.flush()
+}
+
+// Upper bound for `--days`: a year of moons is already a very long scroll.
+const MAX_PRINT_DAYS: u16 = 366;
+
+/// `--repeat` screen handling. On a terminal the cursor is hidden and each frame is drawn over
+/// the last; dropping it (on Ctrl-C, an early `?` return, or a panic) clears the screen and shows
+/// the cursor again. Anywhere else (a pipe, a log file) frames are simply appended, no escapes.
+struct RepeatScreen<W: Write> {
+    out: W,
+    tty: bool,
+}
+
+impl<W: Write> RepeatScreen<W> {
+    fn new(out: W, tty: bool) -> io::Result<Self> {
+        // Construct first so a failed write still restores the cursor via `Drop`.
+        let mut screen = RepeatScreen { out, tty };
+        if tty {
+            write!(screen.out, "\x1b[?25l\x1b[2J")?;
+        }
+        Ok(screen)
+    }
+
+    /// Home the cursor so the next frame draws over the previous one.
+    fn begin_frame(&mut self) -> io::Result<()> {
+        if self.tty {
+            write!(self.out, "\x1b[H")?;
+        }
+        Ok(())
+    }
+
+    /// Clear whatever the previous, taller frame left below this one.
+    fn end_frame(&mut self) -> io::Result<()> {
+        if self.tty {
+            write!(self.out, "\x1b[J")?;
+        }
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Drop for RepeatScreen<W> {
+    fn drop(&mut self) {
+        if self.tty {
+            // Best effort, as in `TerminalGuard`.
+            let _ = write!(self.out, "\x1b[2J\x1b[H\x1b[?25h");
+            let _ = self.out.flush();
+        }
+    }
+}
+
+/// `--repeat`: redraw the print-mode moon for the current time every `period`, in place on
+/// the normal screen, until SIGINT (Ctrl-C). See `RepeatScreen` for the cursor and non-terminals.
+fn repeat_print(args: &Args, period: std::time::Duration) -> io::Result<()> {
+    use std::io::IsTerminal;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    let interrupted = std::sync::Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGINT, std::sync::Arc::clone(&interrupted))?;
+
+    let mut screen = RepeatScreen::new(io::stdout(), io::stdout().is_terminal())?;
+    while !interrupted.load(Ordering::Relaxed) {
+        screen.begin_frame()?;
+        print_status(OutputFormat::Ascii, args, Utc::now())?;
+        screen.end_frame()?;
+
+        // Sleep in short slices so Ctrl-C is handled promptly.
+        let next = Instant::now() + period;
+        while !interrupted.load(Ordering::Relaxed) {
+            let left = next.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break;
+            }
+            std::thread::sleep(left.min(std::time::Duration::from_millis(100)));
+        }
+    }
+    Ok(())
+}
+
+/// `--days`: one print-mode moon per day from `date`, stacked, each under a date header.
+fn print_days(args: &Args, date: DateTime<Utc>, days: u16) -> io::Result<()> {
+    for offset in 0..days {
+        let day = date + Duration::days(offset as i64);
+        let moon = calculate_moon_phase_with(day, args.precision);
+        let mut stdout = io::stdout();
+        if offset > 0 {
+            writeln!(stdout)?;
+        }
+        writeln!(
+            stdout,
+            "{}  {} {}",
+            in_display_tz(day, args.tz).format("%a %Y-%m-%d"),
+            moon.phase.symbol(),
+            moon.phase.name()
+        )?;
+        print_status(OutputFormat::Ascii, args, day)?;
+    }
+    Ok(())
+}
+
+/// Two-column calendar mark for a day's phase: the emoji, or on a dumb terminal an ASCII
+/// abbreviation for the principal phases and `+`/`-` for waxing/waning in between.
+fn calendar_glyph(phase: MoonPhase, dumb: bool) -> &'static str {
+    if !dumb {
+        return phase.symbol();
+    }
+    match phase {
+        MoonPhase::New => "NM",
+        MoonPhase::FirstQuarter => "FQ",
+        MoonPhase::Full => "FM",
+        MoonPhase::LastQuarter => "LQ",
+        MoonPhase::WaxingCrescent | MoonPhase::WaxingGibbous => " +",
+        MoonPhase::WaningCrescent | MoonPhase::WaningGibbous => " -",
+    }
+}
+
+/// The `c` view: `shown`'s month as a Monday-first wall calendar, each day a phase glyph
+/// (at midday UTC, like `--csv`) and the day number. `shown` is drawn reversed and `today`
+/// bold yellow.
+fn calendar_lines(shown: NaiveDate, today: NaiveDate, dumb: bool, precision: Precision) -> Vec<Line<'static>> {
+    let first = shown.with_day(1).expect("day 1 exists");
+    let mut lines = vec![
+        Line::from(Span::styled(first.format("%B %Y").to_string(), Style::default().add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(Span::styled(
+            ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"].map(|d| format!("  {d} ")).concat(),
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let lead = first.weekday().num_days_from_monday() as usize;
+    let mut week = vec![Span::raw("     ".repeat(lead))];
+    for day in first.iter_days().take_while(|d| d.month() == first.month()) {
+        let phase = calculate_moon_phase_with(midday_utc(day), precision).phase;
+        let style = if day == shown {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else if day == today {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        week.push(Span::styled(format!("{}{:>2}", calendar_glyph(phase, dumb), day.day()), style));
+        week.push(Span::raw(" "));
+        if day.weekday() == chrono::Weekday::Sun {
+            lines.push(Line::from(std::mem::take(&mut week)));
+        }
+    }
+    if !week.is_empty() {
+        // Pad the last week so centering keeps the columns aligned.
+        let used: usize = week.iter().map(|s| s.content.width()).sum();
+        week.push(Span::raw(" ".repeat(35usize.saturating_sub(used))));
+        lines.push(Line::from(week));
+    }
+    lines
+}
+
+/// The Details panel's core fields as aligned `key: value` lines (`--format table`, `--print-details`).
+fn details_lines(date: DateTime<Utc>, tz: Option<FixedOffset>, moon: &MoonStatus) -> Vec<String> {
+    vec![
+        format!("Date:         {}", in_display_tz(date, tz).format("%Y-%m-%d")),
+        format!("Phase:        {} {}", moon.phase.symbol(), moon.phase.name()),
+        format!("Age:          {:.1} days", round_age(moon.age_days)),
+        format!("Illumination: {:.1}%", round_illumination(moon.illumination)),
+    ]
+}
+
+/// Technical readouts for `--verbose`, to reconcile the numbers with the drawing.
+fn verbose_lines(moon: &MoonStatus) -> Vec<String> {
+    vec![
+        format!("Phase fraction: {:.4}", moon.phase_fraction),
+        format!(
+            "Terminator:     {:.3} across the disc (0 = left limb, 1 = right limb)",
+            moon.terminator_fraction
+        ),
+        format!(
+            "Libration:      {:+.2}° lon, {:+.2}° lat",
+            moon.libration_lon, moon.libration_lat
         ),
+        format!("Ecliptic lat:   {:+.2}° ({})", moon.ecliptic_lat, node_distance_text(moon)),
+        format!("Eclipse:        {}", eclipse_reasoning(moon)),
+    ]
+}
+
+// Rough eclipse limits on the Moon's ecliptic latitude at syzygy (degrees). Beyond these the
+// Moon passes above or below the Sun (solar) or Earth's shadow (lunar).
+const SOLAR_ECLIPSE_LIMIT: f64 = 1.5;
+const LUNAR_ECLIPSE_LIMIT: f64 = 1.0;
+
+/// Distance along the orbit from the nearest node, e.g. "12.3° past the ascending node".
+fn node_distance_text(moon: &MoonStatus) -> String {
+    let f = normalize_degrees(moon.arg_latitude);
+    let (offset, node) = if !(90.0..270.0).contains(&f) {
+        (if f > 180.0 { f - 360.0 } else { f }, "ascending")
+    } else {
+        (f - 180.0, "descending")
+    };
+    let side = if offset >= 0.0 { "past" } else { "before" };
+    format!("{:.1}° {side} the {node} node", offset.abs())
+}
+
+/// Why an eclipse is or isn't possible right now: it takes both a new/full moon and a
+/// Moon close enough to the ecliptic (i.e. near a node).
+fn eclipse_reasoning(moon: &MoonStatus) -> String {
+    let (kind, limit) = match moon.phase {
+        MoonPhase::New => ("solar", SOLAR_ECLIPSE_LIMIT),
+        MoonPhase::Full => ("lunar", LUNAR_ECLIPSE_LIMIT),
+        _ => return "none (only possible at new or full moon)".to_string(),
+    };
+    let lat = moon.ecliptic_lat.abs();
+    if lat <= limit {
+        format!("{kind} eclipse possible near this syzygy (latitude {lat:.1}° is within ~{limit}° of the node)")
+    } else {
+        format!("no {kind} eclipse (latitude {lat:.1}° is too far from the node; needs under ~{limit}°)")
+    }
+}
+
+fn parse_date_arg(s: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| AppError::InvalidDate(format!("`{s}` is not a valid date. Use YYYY-MM-DD")))
+}
+
+/// `--date`: `YYYY-MM-DD`, or a day relative to `today`: `today`, `tomorrow`, `yesterday`, or a
+/// signed count of days or weeks (`+3d`, `-2w`).
+fn parse_date_input(s: &str, today: NaiveDate) -> Result<NaiveDate, AppError> {
+    let s = s.trim();
+    let offset = match s.to_ascii_lowercase().as_str() {
+        "today" => Some(0),
+        "tomorrow" => Some(1),
+        "yesterday" => Some(-1),
+        rel if rel.starts_with(['+', '-']) => {
+            let (count, unit) = rel.split_at(rel.len() - 1);
+            let days_per = match unit {
+                "d" => Some(1),
+                "w" => Some(7),
+                _ => None,
+            };
+            days_per.zip(count.parse::<i64>().ok()).map(|(per, n)| n.saturating_mul(per))
+        }
+        _ => None,
+    };
+    match offset {
+        Some(days) => Duration::try_days(days)
+            .and_then(|d| today.checked_add_signed(d))
+            .ok_or_else(|| AppError::InvalidDate(format!("`{s}` is out of range"))),
+        None => parse_date_arg(s).map_err(|_| {
+            AppError::InvalidDate(format!(
+                "`{s}` is not a valid date. Use YYYY-MM-DD, today, tomorrow, yesterday, or +Nd/-Nd/+Nw/-Nw"
+            ))
+        }),
     }
 }
 
-const LINE_FADE_STEPS: u8 = 6;
+/// `YYYY-MM-DDTHH:MM[:SS]` in UTC (a trailing `Z` is accepted), or a bare date meaning noon UTC.
+/// Instants outside `SUPPORTED_YEARS` are rejected.
+fn parse_datetime_arg(s: &str) -> Result<DateTime<Utc>, AppError> {
+    let t = s.trim().trim_end_matches('Z');
+    let at = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .into_iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(t, format).ok())
+        .map(|naive| Utc.from_utc_datetime(&naive));
+    let at = match at {
+        Some(at) => at,
+        None => parse_date_arg(t).map(midday_utc).map_err(|_| {
+            AppError::InvalidDate(format!("`{s}` is not a valid date/time. Use YYYY-MM-DDTHH:MM (UTC) or YYYY-MM-DD"))
+        })?,
+    };
+    check_supported_date(at)
+}
 
-fn reset_poem_fade(state: &mut PoemViewState) {
-    state.line_fade = vec![0; state.poem.lines.len()];
-    state.fade_idx = 0;
-    state.fade_step = 0;
-    state.last_fade = Instant::now();
-    state.fade_pause_until = None;
+/// Parse an inclusive `from..=to` day range, rejecting reversed ranges and days outside `SUPPORTED_YEARS`.
+fn parse_date_range(from: &str, to: &str) -> Result<(NaiveDate, NaiveDate), AppError> {
+    let (from, to) = (parse_date_arg(from)?, parse_date_arg(to)?);
+    check_supported_date(midday_utc(from))?;
+    check_supported_date(midday_utc(to))?;
+    if from > to {
+        return Err(AppError::InvalidDate(format!(
+            "range start {from} is after its end {to}"
+        )));
+    }
+    Ok((from, to))
 }
 
-fn soft_palette_for_theme(glow_phase: u64, theme: Theme, truecolor: bool) -> (Color, Color, Color) {
-    match theme {
-        Theme::Light => {
-            // Higher contrast on light terminals.
-            let step = (glow_phase / 16) % 3;
-            if truecolor {
-                match step {
-                    0 => (
-                        Color::Rgb(92, 40, 88),   // title: plum
-                        Color::Rgb(52, 62, 120),  // body: deep indigo
-                        Color::Rgb(95, 95, 105),  // dim: graphite
-                    ),
-                    1 => (
-                        Color::Rgb(70, 45, 115),
-                        Color::Rgb(40, 75, 130),
-                        Color::Rgb(90, 90, 100),
-                    ),
-                    _ => (
-                        Color::Rgb(110, 40, 70),
-                        Color::Rgb(65, 55, 120),
-                        Color::Rgb(95, 95, 105),
-                    ),
-                }
-            } else {
-                // 256-color approximations for light backgrounds.
-                match step {
-                    0 => (Color::Indexed(90), Color::Indexed(25), Color::Indexed(244)),
-                    1 => (Color::Indexed(91), Color::Indexed(24), Color::Indexed(244)),
-                    _ => (Color::Indexed(89), Color::Indexed(61), Color::Indexed(244)),
-                }
-            }
-        }
-        _ => {
-            if truecolor {
-                soft_palette(glow_phase)
-            } else {
-                // 256-color "romantic" palette for dark terminals.
-                let step = (glow_phase / 16) % 3;
-                match step {
-                    0 => (Color::Indexed(212), Color::Indexed(110), Color::Indexed(245)),
-                    1 => (Color::Indexed(213), Color::Indexed(111), Color::Indexed(245)),
-                    _ => (Color::Indexed(211), Color::Indexed(109), Color::Indexed(245)),
-                }
-            }
-        }
+/// Rejects a `--standstills`/`--offline-almanac` year the ephemeris can't vouch for.
+fn check_supported_year(year: i32) -> Result<i32, AppError> {
+    if !SUPPORTED_YEARS.contains(&year) {
+        return Err(AppError::InvalidDate(format!(
+            "year {year} is outside the supported range {}-{}",
+            SUPPORTED_YEARS.start(),
+            SUPPORTED_YEARS.end()
+        )));
     }
+    Ok(year)
 }
 
-fn render_poem_lines_soft(
-    poem: &Poem,
-    line_fade: &[u8],
-    glow_phase: u64,
-    theme: Theme,
-    truecolor: bool,
-) -> Vec<Line<'static>> {
-    let (title_c, body_c, dim_c) = soft_palette_for_theme(glow_phase, theme, truecolor);
-    let mut out: Vec<Line> = Vec::new();
+/// Rejects a user-supplied instant the ephemeris can't vouch for (see `try_calculate_moon_phase`).
+fn check_supported_date(at: DateTime<Utc>) -> Result<DateTime<Utc>, AppError> {
+    try_calculate_moon_phase(at).map(|_| at).map_err(|err| AppError::InvalidDate(err.to_string()))
+}
 
-    out.push(Line::from(Span::styled(
-        poem.title.clone(),
-        // Avoid BOLD/ITALIC: Terminal.app can remap "bright colors" unexpectedly.
-        Style::default().fg(title_c),
-    )));
+fn midday_utc(day: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&day.and_hms_opt(12, 0, 0).expect("noon is a valid time"))
+}
 
-    if poem.author.is_empty() {
-        out.push(Line::from(Span::styled("", Style::default().fg(dim_c))));
-    } else {
-        out.push(Line::from(Span::styled(
-            format!("— {}", poem.author),
-            Style::default().fg(dim_c),
-        )));
+fn parse_tz(s: &str) -> Result<FixedOffset, String> {
+    let err = || format!("`{s}` is not a UTC offset (e.g. +09:00, -0500, UTC)");
+    let t = s.trim();
+    if t.eq_ignore_ascii_case("utc") || t.eq_ignore_ascii_case("z") {
+        return Ok(FixedOffset::east_opt(0).expect("zero offset"));
+    }
+    let (sign, rest) = match t.as_bytes().first() {
+        Some(b'+') => (1, &t[1..]),
+        Some(b'-') => (-1, &t[1..]),
+        _ => return Err(err()),
+    };
+    let digits: String = rest.chars().filter(|c| *c != ':').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(err());
     }
+    let (hours, minutes) = match digits.len() {
+        1 | 2 => (digits.parse::<i32>().map_err(|_| err())?, 0),
+        4 => (
+            digits[..2].parse::<i32>().map_err(|_| err())?,
+            digits[2..].parse::<i32>().map_err(|_| err())?,
+        ),
+        _ => return Err(err()),
+    };
+    if hours > 14 || minutes > 59 {
+        return Err(err());
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(err)
+}
 
-    out.push(Line::from(""));
+/// `date` as wall-clock time in `--tz` (or the system zone), for showing calendar days.
+fn in_display_tz(date: DateTime<Utc>, tz: Option<FixedOffset>) -> DateTime<FixedOffset> {
+    match tz {
+        Some(offset) => date.with_timezone(&offset),
+        None => date.with_timezone(&Local).fixed_offset(),
+    }
+}
 
-    for (i, line) in poem.lines.iter().enumerate() {
-        let level = line_fade.get(i).copied().unwrap_or(0).min(LINE_FADE_STEPS);
-        if level == 0 {
-            out.push(Line::from(Span::styled("", Style::default().fg(dim_c))));
-            continue;
+/// The instant `anchor` picks on `day`, with "midnight" and "now" read in `tz`.
+fn anchored_instant<Tz: TimeZone>(day: NaiveDate, anchor: Anchor, tz: &Tz, now: DateTime<Utc>) -> DateTime<Utc> {
+    let local = match anchor {
+        Anchor::Noon => return midday_utc(day),
+        Anchor::Midnight => day.and_hms_opt(0, 0, 0).expect("midnight is a valid time"),
+        Anchor::Now => day.and_time(now.with_timezone(tz).time()),
+    };
+    // A DST jump can skip the wall-clock time; fall back to noon rather than fail.
+    match tz.from_local_datetime(&local).earliest() {
+        Some(t) => t.with_timezone(&Utc),
+        None => midday_utc(day),
+    }
+}
+
+fn parse_latitude(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(v) if (-90.0..=90.0).contains(&v) => Ok(v),
+        _ => Err("latitude must be a number from -90 to 90".to_string()),
+    }
+}
+
+fn parse_longitude(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(v) if (-180.0..=180.0).contains(&v) => Ok(v),
+        _ => Err("longitude must be a number from -180 to 180".to_string()),
+    }
+}
+
+/// Where on Earth the sky is seen from (degrees; north and east positive).
+#[derive(Debug, Clone, Copy)]
+struct Observer {
+    lat: f64,
+    lon: f64,
+}
+
+// Geocentric altitude of the Moon's center at rise/set: refraction and semidiameter
+// lower the horizon, parallax raises it; the net is slightly above zero (Meeus).
+const MOONRISE_ALTITUDE: f64 = 0.125;
+
+/// Geocentric (altitude, azimuth) in degrees; azimuth from north through east.
+///
+/// Parallax (up to ~1°) is left out, so altitude reads a little high near the horizon.
+fn horizontal(date: DateTime<Utc>, moon: &MoonStatus, observer: Observer) -> (f64, f64) {
+    let gmst = normalize_degrees(280.460_618_37 + 360.985_647_366_29 * (julian_day_utc(date) - 2451545.0));
+    let hour_angle = deg_to_rad(gmst + observer.lon - moon.right_ascension);
+    let (lat, dec) = (deg_to_rad(observer.lat), deg_to_rad(moon.declination));
+    let altitude = (lat.sin() * dec.sin() + lat.cos() * dec.cos() * hour_angle.cos()).asin();
+    let azimuth = hour_angle
+        .sin()
+        .atan2(hour_angle.cos() * lat.sin() - dec.tan() * lat.cos());
+    (altitude.to_degrees(), normalize_degrees(azimuth.to_degrees() + 180.0))
+}
+
+/// Next moonrise (`rising`) or moonset within 48 hours after `after`, to the minute.
+fn next_horizon_crossing(
+    after: DateTime<Utc>,
+    observer: Observer,
+    rising: bool,
+    precision: Precision,
+) -> Option<DateTime<Utc>> {
+    let above = |t: DateTime<Utc>| horizontal(t, &calculate_moon_phase_with(t, precision), observer).0 > MOONRISE_ALTITUDE;
+    let step = Duration::minutes(10);
+    let mut t = after;
+    let mut was_above = above(t);
+    while t < after + Duration::hours(48) {
+        let next = t + step;
+        let is_above = above(next);
+        if is_above != was_above && is_above == rising {
+            // Bisect down to a minute.
+            let (mut lo, mut hi) = (t, next);
+            while hi - lo > Duration::minutes(1) {
+                let mid = lo + (hi - lo) / 2;
+                if above(mid) == rising { hi = mid } else { lo = mid }
+            }
+            return Some(hi);
         }
+        t = next;
+        was_above = is_above;
+    }
+    None
+}
 
-        // Fade without DIM/BOLD/ITALIC: use dim color first, then body color.
-        let style = if level < (LINE_FADE_STEPS / 2).max(1) {
-            Style::default().fg(dim_c)
-        } else {
-            Style::default().fg(body_c)
+fn ephemeris_json(date: DateTime<Utc>, observer: Option<Observer>, precision: Precision) -> String {
+    let moon = calculate_moon_phase_with(date, precision);
+    let time = |t: Option<DateTime<Utc>>| match t {
+        Some(t) => json_string(&t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+        None => "null".to_string(),
+    };
+    let observer = match observer {
+        Some(obs) => {
+            let (altitude, azimuth) = horizontal(date, &moon, obs);
+            format!(
+                "{{\"lat\":{:.4},\"lon\":{:.4},\"altitude_deg\":{:.2},\"azimuth_deg\":{:.2},\"next_rise\":{},\"next_set\":{}}}",
+                obs.lat,
+                obs.lon,
+                altitude,
+                azimuth,
+                time(next_horizon_crossing(date, obs, true, precision)),
+                time(next_horizon_crossing(date, obs, false, precision)),
+            )
+        }
+        None => "null".to_string(),
+    };
+    format!(
+        concat!(
+            "{{\"date\":{},\"phase\":{},\"phase_fraction\":{:.4},\"age_days\":{:.1},\"illumination\":{:.1},",
+            "\"distance_km\":{:.0},\"angular_diameter_deg\":{:.4},\"ecliptic_lon_deg\":{:.3},\"ecliptic_lat_deg\":{:.3},",
+            "\"right_ascension_deg\":{:.3},\"declination_deg\":{:.3},\"observer\":{}}}"
+        ),
+        time(Some(date)),
+        json_string(moon.phase.name()),
+        moon.phase_fraction,
+        round_age(moon.age_days),
+        round_illumination(moon.illumination),
+        moon.distance_km,
+        moon.angular_diameter(),
+        moon.ecliptic_lon,
+        moon.ecliptic_lat,
+        moon.right_ascension,
+        moon.declination,
+        observer,
+    )
+}
+
+fn ephemeris_text(date: DateTime<Utc>, observer: Option<Observer>, precision: Precision) -> String {
+    let moon = calculate_moon_phase_with(date, precision);
+    let mut lines = vec![
+        format!("Date (UTC):        {}", date.format("%Y-%m-%d %H:%M:%S")),
+        format!("Phase:             {} ({:.4})", moon.phase.name(), moon.phase_fraction),
+        format!("Age:               {:.1} days", round_age(moon.age_days)),
+        format!("Illumination:      {:.1}%", round_illumination(moon.illumination)),
+        format!("Distance:          {:.0} km", moon.distance_km),
+        format!("Angular diameter:  {:.4}°", moon.angular_diameter()),
+        format!("Ecliptic lon/lat:  {:.3}° / {:+.3}°", moon.ecliptic_lon, moon.ecliptic_lat),
+        format!(
+            "RA / Dec:          {:.3}° ({:.3} h) / {:+.3}°",
+            moon.right_ascension,
+            moon.right_ascension / 15.0,
+            moon.declination
+        ),
+    ];
+    if let Some(obs) = observer {
+        let (altitude, azimuth) = horizontal(date, &moon, obs);
+        let time = |t: Option<DateTime<Utc>>| {
+            t.map_or("none in the next 48 h".to_string(), |t| t.format("%Y-%m-%d %H:%M UTC").to_string())
         };
+        lines.push(format!("Observer:          {:+.4}°, {:+.4}°", obs.lat, obs.lon));
+        lines.push(format!("Altitude/azimuth:  {:+.2}° / {:.2}°", altitude, azimuth));
+        lines.push(format!("Next rise:         {}", time(next_horizon_crossing(date, obs, true, precision))));
+        lines.push(format!("Next set:          {}", time(next_horizon_crossing(date, obs, false, precision))));
+    }
+    lines.join("\n")
+}
 
-        {
-            out.push(Line::from(Span::styled(
-                line.clone(),
-                style,
-            )));
+/// A local maximum or minimum of some quantity over time.
+#[derive(Debug, Clone, Copy)]
+struct Extremum {
+    at: DateTime<Utc>,
+    value: f64,
+    is_max: bool,
+}
+
+/// Local extrema of `f` sampled every `step` over `[start, end)`.
+///
+/// Hourly sampling is plenty for lunar quantities, which change slowly near their turning points.
+fn find_extrema(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step: Duration,
+    f: impl Fn(DateTime<Utc>) -> f64,
+) -> Vec<Extremum> {
+    let mut out = Vec::new();
+    let mut t = start;
+    let (mut prev, mut cur) = (f(start - step), f(start));
+    while t < end {
+        let next = f(t + step);
+        if cur > prev && cur >= next {
+            out.push(Extremum { at: t, value: cur, is_max: true });
+        } else if cur < prev && cur <= next {
+            out.push(Extremum { at: t, value: cur, is_max: false });
         }
+        t += step;
+        (prev, cur) = (cur, next);
     }
-
     out
 }
 
-fn twinkle_char(kind: u8) -> char {
-    match kind % 4 {
-        0 => '·',
-        1 => '⋅',
-        2 => '.',
-        _ => '˙',
+/// Monthly northern/southern declination extremes within `year`, plus the yearly peaks.
+fn print_standstills(year: i32, precision: Precision) -> io::Result<()> {
+    let start = midday_utc(NaiveDate::from_ymd_opt(year, 1, 1).expect("valid year")) - Duration::hours(12);
+    let end = midday_utc(NaiveDate::from_ymd_opt(year + 1, 1, 1).expect("valid year")) - Duration::hours(12);
+    let extrema = find_extrema(start, end, Duration::hours(1), |t| {
+        calculate_moon_phase_with(t, precision).declination
+    });
+
+    let mut stdout = io::stdout();
+    writeln!(stdout, "Lunar declination extremes in {year} (UTC)")?;
+    writeln!(stdout)?;
+    for e in &extrema {
+        writeln!(
+            stdout,
+            "  {}  {:+6.2}°  {}",
+            e.at.format("%Y-%m-%d %H:%M"),
+            e.value,
+            if e.is_max { "northernmost" } else { "southernmost" },
+        )?;
     }
+
+    let north = extrema.iter().filter(|e| e.is_max).max_by(|a, b| a.value.total_cmp(&b.value));
+    let south = extrema.iter().filter(|e| !e.is_max).min_by(|a, b| a.value.total_cmp(&b.value));
+    writeln!(stdout)?;
+    if let Some(e) = north {
+        writeln!(stdout, "Highest of the year: {}  {:+.2}°", e.at.format("%Y-%m-%d %H:%M"), e.value)?;
+    }
+    if let Some(e) = south {
+        writeln!(stdout, "Lowest of the year:  {}  {:+.2}°", e.at.format("%Y-%m-%d %H:%M"), e.value)?;
+    }
+    stdout.flush()
 }
 
-fn update_twinkles(twinkles: &mut Vec<Twinkle>, seed: &mut u64, area: Rect) {
-    // Maintain a small, persistent field of twinkles that fade slowly.
-    // This avoids the "pop in/pop out" effect.
-    if area.width < 6 || area.height < 6 {
-        twinkles.clear();
-        return;
+// Full moons nearer than this are flagged as supermoons (the usual popular threshold).
+const SUPERMOON_DISTANCE_KM: f64 = 360_000.0;
+
+/// One row of the `--offline-almanac` report.
+struct AlmanacEvent {
+    at: DateTime<Utc>,
+    /// A principal phase name, "Perigee" or "Apogee".
+    name: &'static str,
+    distance_km: f64,
+    supermoon: bool,
+    /// "solar" or "lunar" when a new/full moon is close enough to a node for an eclipse.
+    eclipse: Option<&'static str>,
+}
+
+/// Quarter of the cycle the Moon is in at `t`: 0 from new, 1 from first quarter, and so on.
+fn quarter_index(t: DateTime<Utc>, precision: Precision) -> usize {
+    (calculate_moon_phase_with(t, precision).phase_fraction * 4.0).floor() as usize % 4
+}
+
+/// `--remember-date` state file: `$XDG_STATE_HOME/ascii_moon/last_date`, falling back to
+/// `~/.local/state`.
+fn last_date_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_STATE_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(base.join("ascii_moon").join("last_date"))
+}
+
+/// Save where the TUI was: `now` while following the clock, else the RFC 3339 instant.
+fn save_last_date(path: &std::path::Path, date: DateTime<Utc>, follow_now: bool) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
     }
+    let text = if follow_now { "now".to_string() } else { date.to_rfc3339_opts(chrono::SecondsFormat::Secs, true) };
+    std::fs::write(path, text + "\n")
+}
 
-    // Decay
-    for t in twinkles.iter_mut() {
-        t.ttl = t.ttl.saturating_sub(1);
-        // Keep within bounds if the pane resizes
-        t.x = t.x.min(area.width.saturating_sub(1));
-        t.y = t.y.min(area.height.saturating_sub(1));
+/// The saved view as `(date, follow_now)`: following now resumes at `now`, not the stale time.
+/// `None` for a missing or unreadable file.
+fn load_last_date(path: &std::path::Path, now: DateTime<Utc>) -> Option<(DateTime<Utc>, bool)> {
+    match std::fs::read_to_string(path).ok()?.trim() {
+        "now" => Some((now, true)),
+        saved => DateTime::parse_from_rfc3339(saved).ok().map(|d| (d.with_timezone(&Utc), false)),
     }
-    twinkles.retain(|t| t.ttl > 0);
+}
 
-    // Target density: scale gently with pane size so larger terminals feel more "starry"
-    // without becoming noisy.
-    //
-    // Roughly: one twinkle per ~90 cells, clamped to a tasteful range.
-    let cells = area.width as usize * area.height as usize;
-    let base = (cells / 90).clamp(10, 22);
-    let jitter = lcg_next_u32(seed) as usize % 4; // +0..3
-    let target = base + jitter;
-    while twinkles.len() < target {
-        // Spawn away from borders so it feels like "in the sky", not hugging the frame.
-        let x = 1 + (lcg_next_u32(seed) as u16 % area.width.saturating_sub(2));
-        let y = 1 + (lcg_next_u32(seed) as u16 % area.height.saturating_sub(2));
-        let max_ttl = 28 + (lcg_next_u32(seed) as u16 % 26); // 28..53 ticks (~3.4s..6.4s at 120ms)
-        let kind = (lcg_next_u32(seed) % 4) as u8;
-        twinkles.push(Twinkle {
-            x,
-            y,
-            ttl: max_ttl,
-            kind,
-        });
+/// `--watch-phase`: the next instant after `from` for `phase`, to the minute. For new, quarter
+/// and full moons that's the exact moment (see `principal_phase_times`); for the phases in
+/// between, when the Moon enters them. Searched hourly over a little more than a lunar month.
+fn next_phase_start(from: DateTime<Utc>, phase: MoonPhase, precision: Precision) -> Option<DateTime<Utc>> {
+    if phase.is_principal() {
+        let end = from + Duration::days(MAX_FORECAST_DAYS as i64 + 5);
+        return principal_phase_times(from + Duration::minutes(1), end, precision)
+            .into_iter()
+            .find(|&(_, p)| p == phase)
+            .map(|(at, _)| at);
+    }
+    let phase_at = |t| calculate_moon_phase_with(t, precision).phase;
+    let mut t = from;
+    let mut inside = phase_at(from) == phase;
+    while t - from < Duration::days(MAX_FORECAST_DAYS as i64 + 5) {
+        let next = t + Duration::hours(1);
+        let next_inside = phase_at(next) == phase;
+        if next_inside && !inside {
+            let (mut lo, mut hi) = (t, next);
+            while hi - lo > Duration::minutes(1) {
+                let mid = lo + (hi - lo) / 2;
+                if phase_at(mid) == phase { hi = mid } else { lo = mid }
+            }
+            return Some(hi);
+        }
+        (t, inside) = (next, next_inside);
     }
+    None
 }
 
-fn render_twinkles(
-    buf: &mut Buffer,
-    area: Rect,
-    twinkles: &[Twinkle],
-    glow_phase: u64,
-    theme: Theme,
-    truecolor: bool,
-) {
-    // Draw twinkles *only* on blank cells so we don't overwrite poem text.
-    let (_, _, dim_c) = soft_palette_for_theme(glow_phase, theme, truecolor);
-    for t in twinkles {
-        let x = area.left() + t.x;
-        let y = area.top() + t.y;
-        if x <= area.left() || x + 1 >= area.right() || y <= area.top() || y + 1 >= area.bottom()
-        {
-            continue;
+/// Instants in `[start, end)` when the Moon reaches new, first quarter, full and last quarter:
+/// found hourly, then bisected to the minute.
+fn principal_phase_times(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    precision: Precision,
+) -> Vec<(DateTime<Utc>, MoonPhase)> {
+    const QUARTERS: [MoonPhase; 4] = [MoonPhase::New, MoonPhase::FirstQuarter, MoonPhase::Full, MoonPhase::LastQuarter];
+    let mut out = Vec::new();
+    let (mut t, mut quarter) = (start, quarter_index(start, precision));
+    while t < end {
+        let next = t + Duration::hours(1);
+        let next_quarter = quarter_index(next, precision);
+        if next_quarter != quarter {
+            let (mut lo, mut hi) = (t, next);
+            while hi - lo > Duration::minutes(1) {
+                let mid = lo + (hi - lo) / 2;
+                if quarter_index(mid, precision) == quarter { lo = mid } else { hi = mid }
+            }
+            // Report whole minutes; the bisection is no finer than that anyway.
+            let at = hi.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(hi);
+            if at < end {
+                out.push((at, QUARTERS[next_quarter]));
+            }
         }
+        (t, quarter) = (next, next_quarter);
+    }
+    out
+}
 
-        if buf.get(x, y).symbol() != " " {
-            continue;
+/// Principal phases and apsides in `[start, end)`, in time order.
+fn almanac_events(start: DateTime<Utc>, end: DateTime<Utc>, precision: Precision) -> Vec<AlmanacEvent> {
+    let mut events: Vec<AlmanacEvent> = principal_phase_times(start, end, precision)
+        .into_iter()
+        .map(|(at, phase)| {
+            let moon = calculate_moon_phase_with(at, precision);
+            let eclipse = match phase {
+                MoonPhase::New if moon.ecliptic_lat.abs() <= SOLAR_ECLIPSE_LIMIT => Some("solar"),
+                MoonPhase::Full if moon.ecliptic_lat.abs() <= LUNAR_ECLIPSE_LIMIT => Some("lunar"),
+                _ => None,
+            };
+            AlmanacEvent {
+                at,
+                name: phase.name(),
+                distance_km: moon.distance_km,
+                supermoon: phase == MoonPhase::Full && moon.distance_km < SUPERMOON_DISTANCE_KM,
+                eclipse,
+            }
+        })
+        .collect();
+    let apsides = find_extrema(start, end, Duration::hours(1), |t| calculate_moon_phase_with(t, precision).distance_km);
+    events.extend(apsides.into_iter().map(|e| AlmanacEvent {
+        at: e.at,
+        name: if e.is_max { "Apogee" } else { "Perigee" },
+        distance_km: e.value,
+        supermoon: false,
+        eclipse: None,
+    }));
+    events.sort_by_key(|e| e.at);
+    events
+}
+
+fn almanac_text(year: i32, events: &[AlmanacEvent], tz: Option<FixedOffset>) -> String {
+    let mut lines = vec![format!("Moon almanac for {year}"), String::new()];
+    for e in events {
+        let mut notes = Vec::new();
+        if e.supermoon {
+            notes.push("supermoon".to_string());
         }
+        if let Some(kind) = e.eclipse {
+            notes.push(format!("{kind} eclipse season"));
+        }
+        lines.push(
+            format!(
+                "  {}  {:<14} {:>9.0} km  {}",
+                in_display_tz(e.at, tz).format("%Y-%m-%d %H:%M %:z"),
+                e.name,
+                e.distance_km,
+                notes.join(", ")
+            )
+            .trim_end()
+            .to_string(),
+        );
+    }
+    let count = |name: &str| events.iter().filter(|e| e.name == name).count();
+    lines.push(String::new());
+    lines.push(format!(
+        "{} full moons ({} supermoons), {} new moons; {} possible eclipses",
+        count(MoonPhase::Full.name()),
+        events.iter().filter(|e| e.supermoon).count(),
+        count(MoonPhase::New.name()),
+        events.iter().filter(|e| e.eclipse.is_some()).count(),
+    ));
+    lines.join("\n")
+}
 
-        // Keep it subtle; avoid BOLD/DIM modifiers for Terminal.app.
-        let style = Style::default().fg(dim_c);
+fn almanac_json(year: i32, events: &[AlmanacEvent], tz: Option<FixedOffset>) -> String {
+    let rows: Vec<String> = events
+        .iter()
+        .map(|e| {
+            format!(
+                "{{\"time\":{},\"event\":{},\"distance_km\":{:.1},\"supermoon\":{},\"eclipse\":{}}}",
+                json_string(&in_display_tz(e.at, tz).to_rfc3339_opts(chrono::SecondsFormat::Secs, false)),
+                json_string(e.name),
+                e.distance_km,
+                e.supermoon,
+                e.eclipse.map_or("null".to_string(), json_string),
+            )
+        })
+        .collect();
+    format!("{{\"year\":{year},\"events\":[{}]}}", rows.join(","))
+}
 
-        buf.get_mut(x, y).set_char(twinkle_char(t.kind)).set_style(style);
+/// Write one CSV row per day in `from..=to` (phase computed at midday UTC, like `--date`).
+fn write_csv<W: Write>(out: &mut W, from: NaiveDate, to: NaiveDate, precision: Precision) -> io::Result<()> {
+    writeln!(out, "date,phase,phase_fraction,age_days,illumination,is_waxing")?;
+    for day in from.iter_days().take_while(|d| *d <= to) {
+        let moon = calculate_moon_phase_with(midday_utc(day), precision);
+        writeln!(
+            out,
+            "{},{},{:.4},{:.1},{:.1},{}",
+            day.format("%Y-%m-%d"),
+            moon.phase.name(),
+            moon.phase_fraction,
+            round_age(moon.age_days),
+            round_illumination(moon.illumination),
+            moon.is_waxing(),
+        )?;
     }
+    out.flush()
 }
 
-impl Widget for MoonWidget {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        // Pre-process source art into a grid for easy sampling
-        let source_lines: Vec<Vec<char>> = MOON_ART_RAW
-            .lines()
-            .filter(|l| !l.is_empty())
-            .map(|l| l.chars().collect())
-            .collect();
-        
-        if source_lines.is_empty() { return; }
-
-        // Calculate bounding box of non-whitespace characters
-        let mut min_x = usize::MAX;
-        let mut max_x = 0;
-        let mut min_y = usize::MAX;
-        let mut max_y = 0;
-
-        for (y, line) in source_lines.iter().enumerate() {
-            for (x, &ch) in line.iter().enumerate() {
-                if ch != ' ' {
-                    if x < min_x { min_x = x; }
-                    if x > max_x { max_x = x; }
-                    if y < min_y { min_y = y; }
-                    if y > max_y { max_y = y; }
-                }
-            }
-        }
+/// `--summary`: the Moon over a span of days, each sampled at noon UTC like `--csv`.
+#[derive(Debug, Clone, PartialEq)]
+struct RangeSummary {
+    from: NaiveDate,
+    to: NaiveDate,
+    min_illumination: f64,
+    max_illumination: f64,
+    mean_illumination: f64,
+    /// Days spent in each phase, in order of first appearance.
+    phases: Vec<(MoonPhase, usize)>,
+}
 
-        if min_x > max_x || min_y > max_y { return; }
+impl RangeSummary {
+    /// The phase covering the most days (the earlier one on a tie).
+    fn dominant_phase(&self) -> MoonPhase {
+        // `max_by_key` keeps the last of equals, so search from the end.
+        let (phase, _) = self.phases.iter().rev().max_by_key(|&&(_, days)| days).expect("a range has at least one day");
+        *phase
+    }
 
-        let crop_w = (max_x - min_x + 1) as f64;
-        let crop_h = (max_y - min_y + 1) as f64;
+    fn days(&self) -> usize {
+        self.phases.iter().map(|&(_, days)| days).sum()
+    }
+}
 
-        // Aspect ratio of the cropped source art
-        let art_aspect = crop_w / crop_h;
+/// `--date-range`: `START..END`, both `YYYY-MM-DD`, start no later than end.
+fn parse_range_arg(s: &str) -> Result<(NaiveDate, NaiveDate), AppError> {
+    let (from, to) = s
+        .split_once("..")
+        .ok_or_else(|| AppError::InvalidDate(format!("`{s}` is not a date range. Use START..END")))?;
+    parse_date_range(from.trim(), to.trim())
+}
 
-        let avail_w = area.width as f64;
-        let avail_h = area.height as f64;
+fn summarize_range(from: NaiveDate, to: NaiveDate, precision: Precision) -> RangeSummary {
+    let mut summary = RangeSummary {
+        from,
+        to,
+        min_illumination: f64::INFINITY,
+        max_illumination: f64::NEG_INFINITY,
+        mean_illumination: 0.0,
+        phases: Vec::new(),
+    };
+    let mut total = 0.0;
+    for day in from.iter_days().take_while(|d| *d <= to) {
+        let moon = calculate_moon_phase_with(midday_utc(day), precision);
+        summary.min_illumination = summary.min_illumination.min(moon.illumination);
+        summary.max_illumination = summary.max_illumination.max(moon.illumination);
+        total += moon.illumination;
+        match summary.phases.iter_mut().find(|(phase, _)| *phase == moon.phase) {
+            Some((_, days)) => *days += 1,
+            None => summary.phases.push((moon.phase, 1)),
+        }
+    }
+    summary.mean_illumination = total / summary.days().max(1) as f64;
+    summary
+}
 
-        // Calculate drawing dimensions to fit 'area' while maintaining aspect ratio
-        let (draw_w, draw_h) = if avail_w / avail_h < art_aspect {
-            // Limited by width
-            (avail_w, avail_w / art_aspect)
-        } else {
-            // Limited by height
-            (avail_h * art_aspect, avail_h)
-        };
+fn summary_text(summary: &RangeSummary) -> String {
+    let days = |n: usize| if n == 1 { "1 day".to_string() } else { format!("{n} days") };
+    let phases: Vec<String> = summary.phases.iter().map(|&(phase, n)| format!("{} ({})", phase.name(), days(n))).collect();
+    format!(
+        "{} to {} ({}): {:.1}% to {:.1}% illuminated, {:.1}% on average; mostly {}.\nPhases: {}.",
+        summary.from.format("%Y-%m-%d"),
+        summary.to.format("%Y-%m-%d"),
+        days(summary.days()),
+        round_illumination(summary.min_illumination),
+        round_illumination(summary.max_illumination),
+        round_illumination(summary.mean_illumination),
+        summary.dominant_phase().name(),
+        phases.join(", "),
+    )
+}
 
-        // Center the drawing in the area
-        let start_x = area.left() as f64 + (avail_w - draw_w) / 2.0;
-        let start_y = area.top() as f64 + (avail_h - draw_h) / 2.0;
+fn summary_json(summary: &RangeSummary) -> String {
+    let phases: Vec<String> = summary
+        .phases
+        .iter()
+        .map(|&(phase, days)| format!("{{\"phase\":{},\"days\":{days}}}", json_string(phase.name())))
+        .collect();
+    format!(
+        "{{\"from\":{},\"to\":{},\"days\":{},\"min\":{:.1},\"max\":{:.1},\"mean\":{:.1},\"dominant_phase\":{},\"phases\":[{}]}}",
+        json_string(&summary.from.format("%Y-%m-%d").to_string()),
+        json_string(&summary.to.format("%Y-%m-%d").to_string()),
+        summary.days(),
+        round_illumination(summary.min_illumination),
+        round_illumination(summary.max_illumination),
+        round_illumination(summary.mean_illumination),
+        json_string(summary.dominant_phase().name()),
+        phases.join(","),
+    )
+}
 
-        let phase = self.status.phase_fraction;
+/// `--interval-phases`: the first day of the range and each later day whose noon-UTC phase
+/// differs from the day before's, with that phase.
+fn phase_changes(from: NaiveDate, to: NaiveDate, precision: Precision) -> Vec<(NaiveDate, MoonPhase)> {
+    let mut changes: Vec<(NaiveDate, MoonPhase)> = Vec::new();
+    for day in from.iter_days().take_while(|d| *d <= to) {
+        let phase = MoonPhase::from_fraction(calculate_moon_phase_with(midday_utc(day), precision).phase_fraction);
+        if changes.last().is_none_or(|&(_, last)| last != phase) {
+            changes.push((day, phase));
+        }
+    }
+    changes
+}
 
-        // Iterate over the target terminal area
-        for y in area.top()..area.bottom() {
-            for x in area.left()..area.right() {
-                // Normalized coordinates relative to the drawn moon box (0.0 to 1.0)
-                let ny = (y as f64 - start_y) / draw_h;
-                let nx = (x as f64 - start_x) / draw_w;
+fn phase_changes_text(changes: &[(NaiveDate, MoonPhase)]) -> String {
+    changes
+        .iter()
+        .map(|(day, phase)| format!("{}  {}", day.format("%Y-%m-%d"), phase.name()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-                // Check if we are inside the moon drawing box
-                if !(0.0..1.0).contains(&ny) || !(0.0..1.0).contains(&nx) {
-                    continue;
-                }
+fn phase_changes_json(from: NaiveDate, to: NaiveDate, changes: &[(NaiveDate, MoonPhase)]) -> String {
+    let changes: Vec<String> = changes
+        .iter()
+        .map(|(day, phase)| {
+            format!(
+                "{{\"date\":{},\"phase\":{}}}",
+                json_string(&day.format("%Y-%m-%d").to_string()),
+                json_string(phase.name())
+            )
+        })
+        .collect();
+    format!(
+        "{{\"from\":{},\"to\":{},\"changes\":[{}]}}",
+        json_string(&from.format("%Y-%m-%d").to_string()),
+        json_string(&to.format("%Y-%m-%d").to_string()),
+        changes.join(",")
+    )
+}
 
-                // Sample from Source Art (Nearest Neighbor) mapped to CROP box
-                let src_y = (min_y as f64 + ny * crop_h).floor() as usize;
-                let src_x = (min_x as f64 + nx * crop_w).floor() as usize;
+/// A reference moment with published phase data, checked by `--self-test`.
+struct ReferenceCase {
+    label: &'static str,
+    /// UTC as (year, month, day, hour, minute).
+    at: (i32, u32, u32, u32, u32),
+    illumination: f64,
+    tolerance: f64,
+    /// Expected phase name, where the moment is a named phase.
+    phase: Option<MoonPhase>,
+}
 
-                if src_y >= source_lines.len() { continue; }
-                let row = &source_lines[src_y];
-                let ch = if src_x < row.len() { row[src_x] } else { ' ' };
+// Times from timeanddate.com's 2025 phase tables (converted to UTC), like the unit tests.
+const REFERENCE_CASES: &[ReferenceCase] = &[
+    ReferenceCase { label: "New Moon 2025-11-20", at: (2025, 11, 20, 6, 47), illumination: 0.0, tolerance: 3.0, phase: Some(MoonPhase::New) },
+    ReferenceCase { label: "First Quarter 2025-11-28", at: (2025, 11, 28, 6, 58), illumination: 50.0, tolerance: 6.0, phase: Some(MoonPhase::FirstQuarter) },
+    ReferenceCase { label: "Full Moon 2025-12-04", at: (2025, 12, 4, 23, 14), illumination: 100.0, tolerance: 5.0, phase: Some(MoonPhase::Full) },
+    ReferenceCase { label: "Last Quarter 2025-12-11", at: (2025, 12, 11, 20, 51), illumination: 50.0, tolerance: 6.0, phase: Some(MoonPhase::LastQuarter) },
+    ReferenceCase { label: "Waning 2025-12-13", at: (2025, 12, 13, 4, 46), illumination: 37.1, tolerance: 6.0, phase: None },
+];
 
-                // Circular Mask & Spherical Projection Logic
-                let dx = nx - 0.5;
-                let dy = ny - 0.5;
-                let dist_sq = dx * dx + dy * dy;
+/// Run `REFERENCE_CASES`, writing one PASS/FAIL line each plus a summary; returns the failure count.
+fn run_self_test<W: Write>(out: &mut W, precision: Precision) -> io::Result<usize> {
+    let mut failures = 0;
+    for case in REFERENCE_CASES {
+        let (y, mo, d, h, mi) = case.at;
+        let at = Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap();
+        let moon = calculate_moon_phase_with(at, precision);
+        let ok = (moon.illumination - case.illumination).abs() <= case.tolerance
+            && case.phase.is_none_or(|p| p == moon.phase);
+        if !ok {
+            failures += 1;
+        }
+        writeln!(
+            out,
+            "{} {:<26} illumination {:5.1}% (expected {:.1}% ±{:.0}), phase {}{}",
+            if ok { "PASS" } else { "FAIL" },
+            case.label,
+            moon.illumination,
+            case.illumination,
+            case.tolerance,
+            moon.phase.name(),
+            case.phase.map(|p| format!(" (expected {})", p.name())).unwrap_or_default(),
+        )?;
+    }
+    writeln!(
+        out,
+        "{}: {}/{} cases passed",
+        if failures == 0 { "PASS" } else { "FAIL" },
+        REFERENCE_CASES.len() - failures,
+        REFERENCE_CASES.len()
+    )?;
+    Ok(failures)
+}
 
-                // Radius is 0.5. Radius^2 is 0.25.
-                if dist_sq > 0.25 {
-                    continue;
-                }
+/// `--dump-art`: the art lines with the crop box marked.
+///
+/// A ruler of `v`/`^` marks the first and last cropped columns above and below the art, and
+/// rows inside the crop start with `|` (outside rows with a space), so the art itself is
+/// shifted right by two columns.
+fn dump_art<W: Write>(out: &mut W, source_lines: &[Vec<char>]) -> io::Result<()> {
+    let width = source_lines.iter().map(|l| l.len()).max().unwrap_or(0);
+    let Some(crop) = art_crop(source_lines) else {
+        return writeln!(out, "# art is blank ({} lines)", source_lines.len());
+    };
+    let max_x = crop.min_x + crop.crop_w as usize - 1;
+    let max_y = crop.min_y + crop.crop_h as usize - 1;
+    writeln!(out, "# art: {} x {} chars", width, source_lines.len())?;
+    writeln!(
+        out,
+        "# crop: columns {}..={}, rows {}..={} ({} x {} chars, aspect {:.3})",
+        crop.min_x,
+        max_x,
+        crop.min_y,
+        max_y,
+        crop.crop_w,
+        crop.crop_h,
+        crop.crop_w / crop.crop_h
+    )?;
+    let ruler = |mark: char| -> String {
+        (0..width)
+            .map(|x| if x == crop.min_x || x == max_x { mark } else { ' ' })
+            .collect()
+    };
+    writeln!(out, "  {}", ruler('v'))?;
+    for (y, line) in source_lines.iter().enumerate() {
+        let edge = if (crop.min_y..=max_y).contains(&y) { '|' } else { ' ' };
+        writeln!(out, "{edge} {}", line.iter().collect::<String>())?;
+    }
+    writeln!(out, "  {}", ruler('^'))
+}
 
-                // Map to -1..1 range for sphere math
-                let u = dx * 2.0;
-                let v = dy * 2.0;
-                
-                // z is the depth of the sphere at this pixel (towards viewer)
-                // x^2 + y^2 + z^2 = 1
-                let z = (1.0 - u * u - v * v).sqrt();
-
-                // Sun vector calculation
-                // Angle 0 = New Moon (Sun behind Moon, Vector 0,0,-1)
-                // Angle PI = Full Moon (Sun behind Earth, Vector 0,0,1)
-                let angle = phase * 2.0 * std::f64::consts::PI;
-                let sun_x = angle.sin();
-                let sun_z = -angle.cos();
-
-                // Dot product of Surface Normal (u, v, z) and Sun Vector (sun_x, 0, sun_z)
-                // If positive, the point is illuminated.
-                let intensity = u * sun_x + z * sun_z;
-
-                if intensity > 0.0 {
-                    // IMPORTANT: set full style to avoid attribute "leakage" (DIM/BOLD/ITALIC)
-                    // when the layout changes (e.g. poem panel toggled).
-                    buf.get_mut(x, y)
-                        .set_char(ch)
-                        .set_style(Style::default().fg(moon_lit_color(self.truecolor)));
-                } else if !self.hide_dark {
-                    // Shadow (Earthshine)
-                    buf.get_mut(x, y)
-                        .set_char(ch)
-                        .set_style(Style::default().fg(moon_shadow_color(self.truecolor)));
-                }
-            }
+/// `--info-size`: the geometry the renderer would use, for rendering bug reports.
+fn print_info_size(args: &Args) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    let (cols, rows) = match crossterm::terminal::size() {
+        Ok((cols, rows)) => {
+            writeln!(out, "terminal:        {cols}x{rows}")?;
+            (cols, rows)
+        }
+        Err(err) => {
+            writeln!(out, "terminal:        unknown ({err}); assuming 80x24")?;
+            (80, 24)
         }
+    };
+    let ratio = args.cell_ratio;
+    writeln!(out, "cell ratio:      {}:{} (disc aspect {:.3})", ratio.w, ratio.h, ratio.aspect())?;
 
-        // Render Labels
-        if self.show_labels {
-            for feature in LUNAR_FEATURES {
-                // Orthographic projection
-                let rad_lat = feature.lat.to_radians();
-                let rad_lon = feature.lon.to_radians();
-                
-                let u = rad_lat.cos() * rad_lon.sin();
-                let v = rad_lat.sin();
-                
-                // Project to screen UV (0..1)
-                // In math, v is Up. In screen, ny goes Down.
-                // Center is 0.5, 0.5
-                // Scale 0.95 to pull labels slightly inwards.
-                // Offset (-0.10, -0.10) to shift labels Down-Left (fixing Top-Right bias).
-                let scale = 0.95;
-                let u_adj = u * scale - 0.10;
-                let v_adj = v * scale - 0.10;
-                
-                let nx = 0.5 + u_adj / 2.0;
-                let ny = 0.5 - v_adj / 2.0; 
-                
-                let term_x = start_x + nx * draw_w;
-                let term_y = start_y + ny * draw_h;
-                
-                let x_idx = term_x as u16;
-                let y_idx = term_y as u16;
-
-                // Simple collision check with screen bounds
-                if x_idx >= area.left() && x_idx < area.right() && y_idx >= area.top() && y_idx < area.bottom() {
-                    buf.get_mut(x_idx, y_idx).set_char('x').set_fg(Color::Red);
-                    let label_x = x_idx + 1;
-                    let name = feature.names[self.language as usize];
-                    if label_x + (name.width() as u16) < area.right() {
-                        buf.set_string(label_x, y_idx, name, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
-                    }
-                }
-            }
+    // Same split as the TUI's default layout (Details panel open, no poem).
+    let (moon_pane, _, _) = tui_layout(Rect::new(0, 0, cols, rows), true, ratio);
+    let lines = args.lines.unwrap_or(DEFAULT_PRINT_LINES);
+    let print_area = print_buffer(lines, print_widget(args, Utc::now()), args.assume_cols.unwrap_or(cols)).area;
+    for (label, area) in [("tui moon pane:", moon_pane), ("print area:", print_area)] {
+        match disc_box(area, ratio, args.max_moon_size) {
+            Some(b) => writeln!(
+                out,
+                "{label:<16} {}x{}, draw box {:.1}x{:.1} at ({:.1}, {:.1})",
+                area.width, area.height, b.draw_w, b.draw_h, b.start_x, b.start_y
+            )?,
+            None => writeln!(out, "{label:<16} {}x{}, too small to draw", area.width, area.height)?,
         }
     }
+
+    if let Some(crop) = art_crop(&source_art_lines()) {
+        writeln!(
+            out,
+            "art crop:        {}x{} chars at ({}, {}), aspect {:.3}",
+            crop.crop_w, crop.crop_h, crop.min_x, crop.min_y, crop.crop_w / crop.crop_h
+        )?;
+    }
+    out.flush()
 }
 
-fn placeholder_poem() -> Poem {
-    Poem {
-        title: "Moon".to_string(),
-        author: "".to_string(),
-        lines: vec!["(no poems found)".to_string()],
+// Off-screen area used by `--benchmark`: roughly a full-screen moon pane.
+const BENCHMARK_AREA: Rect = Rect { x: 0, y: 0, width: 160, height: 48 };
+
+/// Render the moon `frames` times into an off-screen buffer and report throughput.
+fn run_benchmark(frames: u32, widget: MoonWidget) -> io::Result<()> {
+    let mut buffer = Buffer::empty(BENCHMARK_AREA);
+    let started = Instant::now();
+    for _ in 0..frames {
+        buffer.reset();
+        widget.render(BENCHMARK_AREA, &mut buffer);
     }
+    let elapsed = started.elapsed();
+
+    let fps = frames as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    println!(
+        "{} frames at {}x{} in {:.3} s ({:.1} frames/s, {:.3} ms/frame)",
+        frames,
+        BENCHMARK_AREA.width,
+        BENCHMARK_AREA.height,
+        elapsed.as_secs_f64(),
+        fps,
+        elapsed.as_secs_f64() * 1000.0 / frames.max(1) as f64,
+    );
+    Ok(())
 }
 
-fn pick_poem(lib: &PoemLibrary, lang: Language) -> Poem {
-    lib.random_poem(lang).unwrap_or_else(placeholder_poem)
+/// The poem library selected by the command line:
+/// `--poems-dir` > the default filesystem scan > embedded-only with `--no-load-poems`.
+fn load_poem_library(args: &Args) -> PoemLibrary {
+    if args.no_load_poems {
+        poems::default_poems()
+    } else {
+        poems::load_poems(args.poems_dir.as_deref(), args.poems_merge)
+    }
 }
 
-fn run_app<B: Backend>(
-    terminal: &mut Terminal<B>,
-    mut date: DateTime<Utc>,
-    mut follow_now: bool,
-    refresh_minutes: u64,
-    mut hide_dark: bool,
-    poems_dir: Option<PathBuf>,
-    theme: Theme,
-) -> io::Result<()> {
-    let mut show_labels = false;
-    let mut show_info = true;
-    let mut language = Language::English;
-    let mut show_poem = false;
+/// `--list-cities`: one `name  lat  lon  UTC offset` row per `--city` entry.
+fn list_cities<W: Write>(out: &mut W) -> io::Result<()> {
+    for city in cities::CITIES {
+        let dst = if city.observes_dst { " (standard time; DST not applied)" } else { "" };
+        writeln!(out, "{:<14} {:>7.2} {:>8.2}  UTC{}{dst}", city.name, city.lat, city.lon, city.offset())?;
+    }
+    out.flush()
+}
 
-    let theme = resolve_theme(theme);
-    let truecolor = supports_truecolor();
-    let poem_library = poems::load_poems(poems_dir.as_deref());
-    let mut poem_state = PoemViewState {
-        poem: pick_poem(&poem_library, language),
-        glow_phase: 0,
-        last_anim: Instant::now(),
-        twinkle_seed: rand::random::<u64>(),
-        twinkles: Vec::new(),
-        line_fade: Vec::new(),
-        fade_idx: 0,
-        fade_step: 0,
-        last_fade: Instant::now(),
-        fade_pause_until: None,
-    };
-    reset_poem_fade(&mut poem_state);
-    let tick_rate = if refresh_minutes == 0 {
-        None
-    } else {
-        Some(std::time::Duration::from_secs(refresh_minutes.saturating_mul(60)))
+fn list_poems(args: &Args, only: Option<Language>) -> io::Result<()> {
+    let lib = load_poem_library(args);
+    let langs: Vec<Language> = match only {
+        Some(lang) => vec![lang],
+        None => vec![
+            Language::English,
+            Language::Chinese,
+            Language::French,
+            Language::Japanese,
+            Language::Spanish,
+        ],
     };
-    let mut last_tick = Instant::now();
-    let mut needs_redraw = true;
-    loop {
-        // Poem animation: slow, romantic, peaceful.
-        // - Gentle breathing glow (slow phase increment)
-        // - Fade-in by line
-        const ANIM_RATE: std::time::Duration = std::time::Duration::from_millis(120);
-        const FADE_RATE: std::time::Duration = std::time::Duration::from_millis(140);
-        const LINE_GAP: std::time::Duration = std::time::Duration::from_millis(400);
-        if show_poem && poem_state.last_anim.elapsed() >= ANIM_RATE {
-            poem_state.last_anim = Instant::now();
-            poem_state.glow_phase = poem_state.glow_phase.wrapping_add(1);
-            needs_redraw = true;
-        }
-
-        // Advance fade for the current line on its own cadence, with a pause between lines.
-        if show_poem && poem_state.last_fade.elapsed() >= FADE_RATE {
-            // Respect inter-line pause if set.
-            if let Some(until) = poem_state.fade_pause_until {
-                if Instant::now() < until {
-                    // Still pausing.
-                } else {
-                    poem_state.fade_pause_until = None;
-                }
-            }
 
-            if poem_state.fade_pause_until.is_none() && poem_state.fade_idx < poem_state.poem.lines.len() {
-                poem_state.last_fade = Instant::now();
-                poem_state.fade_step = poem_state.fade_step.saturating_add(1);
-                let level = poem_state.fade_step.min(LINE_FADE_STEPS);
-                if let Some(slot) = poem_state.line_fade.get_mut(poem_state.fade_idx) {
-                    *slot = level;
-                }
-                if poem_state.fade_step >= LINE_FADE_STEPS {
-                    poem_state.fade_idx += 1;
-                    poem_state.fade_step = 0;
-                    poem_state.fade_pause_until = Some(Instant::now() + LINE_GAP);
-                }
-                needs_redraw = true;
+    let mut stdout = io::stdout();
+    for (i, lang) in langs.iter().enumerate() {
+        let poems = lib.for_language(*lang);
+        if i > 0 {
+            writeln!(stdout)?;
+        }
+        writeln!(stdout, "{} ({})", lang.name(), poems.len())?;
+        for poem in poems {
+            if poem.author.is_empty() {
+                writeln!(stdout, "  {}", poem.title)?;
+            } else {
+                writeln!(stdout, "  {} — {}", poem.title, poem.author)?;
             }
         }
+    }
+    stdout.flush()
+}
 
-        if needs_redraw {
-            terminal.draw(|f| {
-                // Clear the whole frame first so style modifiers from the previous layout
-                // can't affect the new layout (macOS Terminal is especially sensitive to this).
-                f.render_widget(Clear, f.size());
-
-                let constraints = if show_info {
-                    vec![Constraint::Percentage(80), Constraint::Percentage(20)]
-                } else {
-                    vec![Constraint::Percentage(100), Constraint::Min(0)]
-                };
-
-                let chunks = Layout::default()
-                    .direction(Direction::Vertical)
-                    .margin(1)
-                    .constraints(constraints)
-                    .split(f.size());
+/// Puts the terminal into raw mode on the alternate screen for the TUI, and undoes
+/// both (plus shows the cursor) when dropped: on normal exit, early `?` return, or panic.
+struct TerminalGuard;
 
-                let moon = calculate_moon_phase(date);
+impl TerminalGuard {
+    fn new() -> io::Result<Self> {
+        enable_raw_mode()?;
+        // Construct before entering the alternate screen so a failure there still
+        // leaves raw mode via `Drop`.
+        let guard = TerminalGuard;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        // Terminals that don't report focus simply never send the events.
+        execute!(io::stdout(), crossterm::event::EnableFocusChange)?;
+        Ok(guard)
+    }
+}
 
-                // Main content area: Moon on the left, optional poem panel on the right.
-                let main_cols = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints(if show_poem {
-                        // Ensure both panes have a minimum; moon will "shrink" naturally.
-                        vec![Constraint::Min(18), Constraint::Min(28)]
-                    } else {
-                        vec![Constraint::Percentage(100), Constraint::Min(0)]
-                    })
-                    .split(chunks[0]);
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        // Best effort: there's nowhere useful to report failures while unwinding.
+        let _ = disable_raw_mode();
+        let _ = execute!(
+            io::stdout(),
+            crossterm::event::DisableFocusChange,
+            LeaveAlternateScreen,
+            crossterm::cursor::Show
+        );
+    }
+}
 
-                // Render Custom Moon Widget
-                f.render_widget(
-                    MoonWidget {
-                        status: MoonStatus {
-                            phase: moon.phase,
-                            phase_fraction: moon.phase_fraction,
-                            age_days: moon.age_days,
-                            illumination: moon.illumination,
-                        },
-                        show_labels,
-                        language,
-                        hide_dark,
-                        truecolor,
-                    },
-                    main_cols[0],
-                );
-
-                if show_poem {
-                    let (title_c, _, dim_c) =
-                        soft_palette_for_theme(poem_state.glow_phase, theme, truecolor);
-                    let border_style = Style::default().fg(title_c);
-                    let block = Block::default()
-                        .title(" Moon Poem ")
-                        .borders(Borders::ALL)
-                        .border_style(border_style);
-                    let inner = block.inner(main_cols[1]);
-                    f.render_widget(block, main_cols[1]);
+fn run(mut args: Args) -> Result<(), AppError> {
+    // `--city` stands in for `--lat`/`--lon` (clap rejects both together) and a default `--tz`.
+    // A fixed offset is only right all year where clocks don't change; elsewhere keep the system zone.
+    if let Some(city) = args.city {
+        args.lat = Some(city.lat);
+        args.lon = Some(city.lon);
+        if args.tz.is_none() && !city.observes_dst {
+            args.tz = Some(city.offset());
+        }
+    }
 
-                    if inner.width >= 2 && inner.height >= 2 {
-                        let poem_lines = render_poem_lines_soft(
-                            &poem_state.poem,
-                            &poem_state.line_fade,
-                            poem_state.glow_phase,
-                            theme,
-                            truecolor,
-                        );
-                        let paragraph = Paragraph::new(poem_lines)
-                            .alignment(Alignment::Left)
-                            .style(Style::default().fg(dim_c))
-                            .wrap(ratatui::widgets::Wrap { trim: false });
-                        f.render_widget(paragraph, inner);
+    if args.list_cities {
+        return Ok(list_cities(&mut io::stdout().lock())?);
+    }
 
-                        // Persistent twinkles on blank space.
-                        // We update based on the current pane size, then render after poem text.
-                        update_twinkles(&mut poem_state.twinkles, &mut poem_state.twinkle_seed, inner);
-                        let buf = f.buffer_mut();
-                        render_twinkles(
-                            buf,
-                            inner,
-                            &poem_state.twinkles,
-                            poem_state.glow_phase,
-                            theme,
-                            truecolor,
-                        );
-                    }
-                }
+    if let Some(only) = args.list_poems {
+        return Ok(list_poems(&args, only)?);
+    }
 
-                // Info Area
-                if show_info {
-                    let local_date: DateTime<Local> = DateTime::from(date);
-                    let mode = if follow_now { "Now (auto)" } else { "Manual" };
-                    let info_text = vec![
-                        Line::from(vec![
-                            Span::raw("Date: "),
-                            Span::styled(
-                                local_date.format("%Y-%m-%d").to_string(),
-                                Style::default().add_modifier(Modifier::BOLD),
-                            ),
-                        ]),
-                        Line::from(vec![
-                            Span::raw("Mode: "),
-                            Span::styled(mode, Style::default().fg(Color::Green)),
-                        ]),
-                        Line::from(vec![
-                            Span::raw("Phase: "),
-                            Span::styled(moon.phase.name(), Style::default().fg(Color::Cyan)),
-                        ]),
-                        Line::from(format!("Age: {:.1} days", moon.age_days)),
-                        Line::from(format!("Illumination: {:.1}%", moon.illumination)),
-                        Line::from(vec![
-                            Span::raw("Language: "),
-                            Span::styled(language.name(), Style::default().fg(Color::Green)),
-                        ]),
-                        Line::from(""),
-                        Line::from(Span::styled(
-                            "Use <Left>/<Right> date (switches to Manual). <n> now (auto). <l> labels. <L> language. <d> hide dark. <p> poem. <P> next poem. <i> toggle info. <q> quit.",
-                            Style::default().fg(Color::DarkGray),
-                        )),
-                    ];
+    if args.self_test {
+        let failures = run_self_test(&mut io::stdout().lock(), args.precision)?;
+        return if failures == 0 { Ok(()) } else { Err(AppError::SelfTestFailed(failures)) };
+    }
 
-                    let info_block = Paragraph::new(info_text)
-                        .block(Block::default().title(" Details ").borders(Borders::ALL))
-                        .alignment(Alignment::Center);
-                    f.render_widget(info_block, chunks[1]);
-                }
-            })?;
-            needs_redraw = false;
+    // Parse date or use now
+    let now = Utc::now();
+    let anchor_on = |day: NaiveDate, anchor: Anchor| match args.tz {
+        Some(offset) => anchored_instant(day, anchor, &offset, now),
+        None => anchored_instant(day, anchor, &Local, now),
+    };
+    let (date, follow_now) = match (args.date.as_deref(), args.anchor) {
+        (Some(d), anchor) => {
+            let day = parse_date_input(d, in_display_tz(now, args.tz).date_naive())?;
+            (check_supported_date(anchor_on(day, anchor.unwrap_or(Anchor::Noon)))?, false)
         }
+        (None, Some(anchor)) => (anchor_on(in_display_tz(now, args.tz).date_naive(), anchor), false),
+        (None, None) => match (args.remember_date && args.watch_phase.is_none()).then(last_date_path).flatten() {
+            Some(path) => load_last_date(&path, now).unwrap_or((now, true)),
+            None => (now, true),
+        },
+    };
+    let (date, follow_now) = match args.watch_phase {
+        Some(phase) => (next_phase_start(date, phase, args.precision).expect("every phase recurs within a lunar month"), false),
+        None => (date, follow_now),
+    };
+    let follow_now = follow_now && !args.no_follow;
 
-        // Timer tick: refresh "now" periodically
-        if let Some(tick_rate) = tick_rate {
-            if last_tick.elapsed() >= tick_rate {
-                last_tick = Instant::now();
-                if follow_now {
-                    date = Utc::now();
-                }
-                needs_redraw = true;
-            }
-        }
+    if args.csv {
+        // clap guarantees both with `requires_all`.
+        let (from, to) = parse_date_range(
+            args.from.as_deref().unwrap_or_default(),
+            args.to.as_deref().unwrap_or_default(),
+        )?;
+        return Ok(match &args.out {
+            Some(path) => write_csv(&mut io::BufWriter::new(std::fs::File::create(path)?), from, to, args.precision),
+            None => write_csv(&mut io::stdout().lock(), from, to, args.precision),
+        }?);
+    }
 
-        // Wait for input/resize up to the next tick
-        let timeout = {
-            // Keep the UI responsive for animation even if refresh_minutes is large.
-            let base = if let Some(tick_rate) = tick_rate {
-                tick_rate
-                    .checked_sub(last_tick.elapsed())
-                    .unwrap_or_else(|| std::time::Duration::from_secs(0))
-            } else {
-                std::time::Duration::from_millis(250)
-            };
-            if show_poem {
-                base.min(ANIM_RATE)
-            } else {
-                base
-            }
+    if let Some(range) = args.interval_phases.as_deref() {
+        let (from, to) = parse_range_arg(range)?;
+        let changes = phase_changes(from, to, args.precision);
+        let text = if args.json {
+            args.json_text(phase_changes_json(from, to, &changes))
+        } else {
+            phase_changes_text(&changes)
         };
-
-        if event::poll(timeout)? {
-            match event::read()? {
-                Event::Key(key) if key.kind == KeyEventKind::Press => {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
-                        KeyCode::Char('l') => {
-                            show_labels = !show_labels;
-                            needs_redraw = true;
-                        }
-                        KeyCode::Char('L') => {
-                            language = language.next();
-                            if show_poem {
-                                poem_state.poem = pick_poem(&poem_library, language);
-                                poem_state.glow_phase = 0;
-                                poem_state.last_anim = Instant::now();
-                                poem_state.twinkle_seed = rand::random::<u64>();
-                                poem_state.twinkles.clear();
-                                reset_poem_fade(&mut poem_state);
-                            }
-                            needs_redraw = true;
-                        }
-                        KeyCode::Char('i') => {
-                            show_info = !show_info;
-                            needs_redraw = true;
-                        }
-                        KeyCode::Char('d') => {
-                            hide_dark = !hide_dark;
-                            needs_redraw = true;
-                        }
-                        KeyCode::Char('p') => {
-                            show_poem = !show_poem;
-                            if show_poem {
-                                poem_state.poem = pick_poem(&poem_library, language);
-                                poem_state.glow_phase = 0;
-                                poem_state.last_anim = Instant::now();
-                                poem_state.twinkle_seed = rand::random::<u64>();
-                                poem_state.twinkles.clear();
-                                reset_poem_fade(&mut poem_state);
-                            }
-                            needs_redraw = true;
-                        }
-                        KeyCode::Char('P') => {
-                            if show_poem {
-                                poem_state.poem = pick_poem(&poem_library, language);
-                                poem_state.glow_phase = 0;
-                                poem_state.last_anim = Instant::now();
-                                poem_state.twinkle_seed = rand::random::<u64>();
-                                poem_state.twinkles.clear();
-                                reset_poem_fade(&mut poem_state);
-                                needs_redraw = true;
-                            }
-                        }
-                        KeyCode::Char('n') => {
-                            follow_now = true;
-                            date = Utc::now();
-                            last_tick = Instant::now();
-                            needs_redraw = true;
-                        }
-                        KeyCode::Left => {
-                            follow_now = false;
-                            date -= Duration::days(1);
-                            needs_redraw = true;
-                        }
-                        KeyCode::Right => {
-                            follow_now = false;
-                            date += Duration::days(1);
-                            needs_redraw = true;
-                        }
-                        _ => {}
-                    }
-                }
-                Event::Resize(_, _) => {
-                    needs_redraw = true;
-                }
-                _ => {}
-            }
-        }
+        let mut out = io::stdout().lock();
+        writeln!(out, "{text}")?;
+        return Ok(out.flush()?);
     }
-}
 
+    if let Some(range) = args.date_range.as_deref() {
+        // clap pairs `--date-range` with `--summary`.
+        let (from, to) = parse_range_arg(range)?;
+        let summary = summarize_range(from, to, args.precision);
+        let text = if args.json { args.json_text(summary_json(&summary)) } else { summary_text(&summary) };
+        let mut out = io::stdout().lock();
+        writeln!(out, "{text}")?;
+        return Ok(out.flush()?);
+    }
 
-// Helper function to convert ratatui::style::Color to ANSI foreground code
-fn color_to_ansi_fg(color: Color) -> String {
-    match color {
-        Color::Reset => "\x1b[39m".to_string(),
-        Color::Black => "\x1b[30m".to_string(),
-        Color::Red => "\x1b[31m".to_string(),
-        Color::Green => "\x1b[32m".to_string(),
-        Color::Yellow => "\x1b[33m".to_string(),
-        Color::Blue => "\x1b[34m".to_string(),
-        Color::Magenta => "\x1b[35m".to_string(),
-        Color::Cyan => "\x1b[36m".to_string(),
-        Color::Gray => "\x1b[90m".to_string(), // Bright Black
-        Color::DarkGray => "\x1b[30m".to_string(), // Often same as Black
-        Color::LightRed => "\x1b[91m".to_string(),
-        Color::LightGreen => "\x1b[92m".to_string(),
-        Color::LightYellow => "\x1b[93m".to_string(),
-        Color::LightBlue => "\x1b[94m".to_string(),
-        Color::LightMagenta => "\x1b[95m".to_string(),
-        Color::LightCyan => "\x1b[96m".to_string(),
-        Color::White => "\x1b[97m".to_string(),
-        Color::Rgb(r, g, b) => format!("\x1b[38;2;{};{};{}m", r, g, b),
-        Color::Indexed(_) => "\x1b[39m".to_string(), // Default to reset
+    if let Some(year) = args.standstills {
+        return Ok(print_standstills(check_supported_year(year)?, args.precision)?);
     }
-}
 
-fn print_moon(lines: u16, date: DateTime<Utc>, hide_dark: bool) -> io::Result<()> {
-    let moon = calculate_moon_phase(date);
+    if let Some(year) = args.offline_almanac {
+        let year = check_supported_year(year)?;
+        // The year runs from midnight to midnight in the display time zone.
+        let new_year = |y: i32| anchor_on(NaiveDate::from_ymd_opt(y, 1, 1).expect("valid year"), Anchor::Midnight);
+        let events = almanac_events(new_year(year), new_year(year + 1), args.precision);
+        let mut out = io::stdout().lock();
+        let text = if args.json {
+            args.json_text(almanac_json(year, &events, args.tz))
+        } else {
+            almanac_text(year, &events, args.tz)
+        };
+        writeln!(out, "{text}")?;
+        return Ok(out.flush()?);
+    }
 
-    // The moon art is roughly 160 chars wide and 80 chars high in the source.
-    // This gives an aspect ratio of 2.0 (width/height).
-    let aspect_ratio = 2.0;
-    let width = (lines as f64 * aspect_ratio) as u16;
+    #[cfg(feature = "serve")]
+    if let Some(port) = args.serve {
+        let observer = args.lat.zip(args.lon).map(|(lat, lon)| Observer { lat, lon });
+        return Ok(serve::serve(args.bind, port, observer, args.precision)?);
+    }
 
-    // Don't let the width exceed the terminal width
-    // In non-TTY scenarios, `size()` can fail; fall back to a reasonable default.
-    let (terminal_width, _) = crossterm::terminal::size().unwrap_or((80, 0));
-    let width = width.min(terminal_width);
+    if let Some(at) = args.ephemeris.as_deref() {
+        let at = parse_datetime_arg(at)?;
+        let observer = args.lat.zip(args.lon).map(|(lat, lon)| Observer { lat, lon });
+        let mut out = io::stdout().lock();
+        let text = if args.json {
+            args.json_text(ephemeris_json(at, observer, args.precision))
+        } else {
+            ephemeris_text(at, observer, args.precision)
+        };
+        writeln!(out, "{text}")?;
+        return Ok(out.flush()?);
+    }
 
-    let area = Rect::new(0, 0, width, lines);
-    let mut buffer = Buffer::empty(area);
+    if args.dump_art {
+        let mut out = io::stdout().lock();
+        dump_art(&mut out, &source_art_lines())?;
+        return Ok(out.flush()?);
+    }
 
-    let widget = MoonWidget {
-        status: moon,
-        show_labels: false,
-        language: Language::English,
-        hide_dark,
-        truecolor: supports_truecolor(),
-    };
-    widget.render(area, &mut buffer);
+    if args.info_size {
+        return Ok(print_info_size(&args)?);
+    }
 
-    // Manually print the buffer to stdout with color
-    let mut stdout = io::stdout();
-    let mut last_fg = Color::Reset;
+    if args.dump_buffer {
+        // A fixed width cap rather than the terminal's, so dumps are the same everywhere.
+        let lines = args.lines.unwrap_or(DEFAULT_PRINT_LINES);
+        let buffer = print_buffer(lines, print_widget(&args, date), u16::MAX);
+        let mut out = io::stdout().lock();
+        writeln!(out, "{}", buffer_json(&buffer))?;
+        return Ok(out.flush()?);
+    }
 
-    for y in 0..area.height {
-        for x in 0..area.width {
-            let cell = buffer.get(x, y);
-            if cell.fg != last_fg {
-                write!(stdout, "{}", color_to_ansi_fg(cell.fg))?;
-                last_fg = cell.fg;
-            }
-            write!(stdout, "{}", cell.symbol())?;
-        }
-        writeln!(stdout, "\x1b[0m")?; // Reset color at end of line and print newline
+    if let Some(frames) = args.benchmark {
+        return Ok(run_benchmark(frames, print_widget(&args, date))?);
     }
 
-    stdout.flush()?;
-    Ok(())
-}
+    if let Some(format) = args.output_format() {
+        return Ok(print_status(format, &args, date)?);
+    }
 
+    if let Some(days) = args.days {
+        check_supported_date(date + Duration::days(i64::from(days) - 1))?;
+        return Ok(print_days(&args, date, days)?);
+    }
 
-fn main() -> io::Result<()> {
-    let args = Args::parse();
-    
-    // Parse date or use now
-    let (date, follow_now) = match args.date {
-        Some(d) => {
-            let naive_date = NaiveDate::parse_from_str(&d, "%Y-%m-%d").map_err(|_| {
-                io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "Invalid date format. Use YYYY-MM-DD",
-                )
-            })?;
-            let naive = naive_date
-                .and_hms_opt(12, 0, 0)
-                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid date"))?; // Midday
-            (Utc.from_utc_datetime(&naive), false)
-        },
-        None => (Utc::now(), true),
-    };
+    if let Some(secs) = args.repeat {
+        return Ok(repeat_print(&args, std::time::Duration::from_secs_f64(secs))?);
+    }
 
-    if let Some(lines) = args.lines {
+    if args.lines.is_some() || args.print_details {
         // Non-interactive print mode
-        return print_moon(lines, date, args.hide_dark);
+        return Ok(print_status(OutputFormat::Ascii, &args, date)?);
     }
 
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    // Run app
-    let res = run_app(
-        &mut terminal,
-        date,
-        follow_now,
-        args.refresh_minutes,
-        args.hide_dark,
-        args.poems_dir.clone(),
-        args.theme,
-    );
-
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-    )?;
-    terminal.show_cursor()?;
+    // The guard restores the terminal when this block ends, however `run_app` exits.
+    let _guard = TerminalGuard::new()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    Ok(run_app(&mut terminal, &args, date, follow_now)?)
+}
 
-    if let Err(err) = res {
-        println!("{:?}", err);
+fn main() -> ExitCode {
+    // Invalid usage (unknown flags, bad values) exits with clap's code 2.
+    let args = Args::parse();
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("ascii_moon: {err}");
+            err.exit_code()
+        }
     }
-
-    Ok(())
 }
@@ -0,0 +1,654 @@
+//! Where the Moon is and how much of it is lit: `calculate_moon_phase` and `MoonStatus`.
+
+use crate::Language;
+use chrono::{DateTime, Datelike, Utc};
+
+/// Which lunar series `calculate_moon_phase_with` uses (`--precision`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    /// The compact series: a few arcminutes, fast enough to call per frame.
+    Standard,
+    /// Meeus' full main-problem tables (chapter 47): sub-arcminute, several times slower.
+    High,
+}
+
+impl std::str::FromStr for Precision {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" => Ok(Precision::Standard),
+            "high" => Ok(Precision::High),
+            _ => Err("precision must be one of: standard, high".to_string()),
+        }
+    }
+}
+
+// Synodic month (new moon to new moon) in days (average; used only to express "age" in days)
+pub const SYNODIC_MONTH: f64 = 29.53058867;
+
+// Sidereal month (back to the same place among the stars) in days (average).
+pub const SIDEREAL_MONTH: f64 = 27.321661;
+
+// Tropical zodiac: 30° of ecliptic longitude each, starting at the March equinox point.
+pub const ZODIAC_SIGNS: [&str; 12] = [
+    "Aries", "Taurus", "Gemini", "Cancer", "Leo", "Virgo",
+    "Libra", "Scorpio", "Sagittarius", "Capricorn", "Aquarius", "Pisces",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoonPhase {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl std::str::FromStr for MoonPhase {
+    type Err = String;
+    /// A phase name in any case, with spaces, hyphens or underscores and an optional "moon":
+    /// `full`, `Full Moon`, `waxing-crescent`, `last_quarter`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = s.trim().to_ascii_lowercase().replace([' ', '_'], "-");
+        match key.strip_suffix("-moon").unwrap_or(&key) {
+            "new" => Ok(MoonPhase::New),
+            "waxing-crescent" => Ok(MoonPhase::WaxingCrescent),
+            "first-quarter" => Ok(MoonPhase::FirstQuarter),
+            "waxing-gibbous" => Ok(MoonPhase::WaxingGibbous),
+            "full" => Ok(MoonPhase::Full),
+            "waning-gibbous" => Ok(MoonPhase::WaningGibbous),
+            "last-quarter" => Ok(MoonPhase::LastQuarter),
+            "waning-crescent" => Ok(MoonPhase::WaningCrescent),
+            _ => Err("phase must be one of: new, waxing-crescent, first-quarter, waxing-gibbous, full, \
+                      waning-gibbous, last-quarter, waning-crescent"
+                .to_string()),
+        }
+    }
+}
+
+impl MoonPhase {
+    /// The phase for a point in the synodic cycle (0 = new, 0.5 = full; other values wrap).
+    ///
+    /// Each phase covers an eighth of the cycle centered on its nominal point: New is
+    /// `[15/16, 1/16)`, First Quarter `[3/16, 5/16)`, Full `[7/16, 9/16)` and so on, with the
+    /// crescents and gibbous phases in between. A boundary belongs to the phase that follows it.
+    pub fn from_fraction(f: f64) -> MoonPhase {
+        const CYCLE: [MoonPhase; 8] = [
+            MoonPhase::New,
+            MoonPhase::WaxingCrescent,
+            MoonPhase::FirstQuarter,
+            MoonPhase::WaxingGibbous,
+            MoonPhase::Full,
+            MoonPhase::WaningGibbous,
+            MoonPhase::LastQuarter,
+            MoonPhase::WaningCrescent,
+        ];
+        // NaN falls through the float-to-int cast as 0, i.e. New.
+        let segment = (f.rem_euclid(1.0) * 8.0 + 0.5).floor() as usize % 8;
+        CYCLE[segment]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MoonPhase::New => "New Moon",
+            MoonPhase::WaxingCrescent => "Waxing Crescent",
+            MoonPhase::FirstQuarter => "First Quarter",
+            MoonPhase::WaxingGibbous => "Waxing Gibbous",
+            MoonPhase::Full => "Full Moon",
+            MoonPhase::WaningGibbous => "Waning Gibbous",
+            MoonPhase::LastQuarter => "Last Quarter",
+            MoonPhase::WaningCrescent => "Waning Crescent",
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            MoonPhase::New => "🌑",
+            MoonPhase::WaxingCrescent => "🌒",
+            MoonPhase::FirstQuarter => "🌓",
+            MoonPhase::WaxingGibbous => "🌔",
+            MoonPhase::Full => "🌕",
+            MoonPhase::WaningGibbous => "🌖",
+            MoonPhase::LastQuarter => "🌗",
+            MoonPhase::WaningCrescent => "🌘",
+        }
+    }
+
+    /// New, first quarter, full and last quarter: the phases worth announcing.
+    pub fn is_principal(&self) -> bool {
+        matches!(
+            self,
+            MoonPhase::New | MoonPhase::FirstQuarter | MoonPhase::Full | MoonPhase::LastQuarter
+        )
+    }
+
+    /// Tag used in a poem's `phase:` header to mark it as suited to this phase.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            MoonPhase::New => "new",
+            MoonPhase::WaxingCrescent | MoonPhase::WaningCrescent => "crescent",
+            MoonPhase::FirstQuarter | MoonPhase::LastQuarter => "quarter",
+            MoonPhase::WaxingGibbous | MoonPhase::WaningGibbous => "gibbous",
+            MoonPhase::Full => "full",
+        }
+    }
+}
+
+// "Waxing" / "waning" for the Details illumination trend, indexed like `Feature::names`.
+const WAX_WANE: [[&str; 2]; 5] = [
+    ["waxing", "waning"],
+    ["盈", "亏"],
+    ["croissante", "décroissante"],
+    ["満ちていく", "欠けていく"],
+    ["creciente", "menguante"],
+];
+
+/// Everything computed about the Moon at one instant; see `calculate_moon_phase`.
+#[derive(Debug, Clone, Copy)]
+pub struct MoonStatus {
+    /// The named phase: the eighth of the cycle `phase_fraction` falls in.
+    pub phase: MoonPhase,
+    /// Progress through the cycle of phases, 0.0 to 1.0 (0 = new, 0.5 = full, 1.0 = new again).
+    pub phase_fraction: f64,
+    /// Days since new moon, at the mean synodic rate.
+    pub age_days: f64,
+    /// Percentage of the disc that is lit, 0 to 100.
+    pub illumination: f64,
+    /// Optical libration in degrees: positive longitude shows more of the east limb
+    /// (Mare Crisium side), positive latitude more of the north pole.
+    pub libration_lon: f64,
+    pub libration_lat: f64,
+    /// Where the drawn day/night line crosses the equator: 0.0 = left limb, 1.0 = right limb.
+    /// Equals `illumination` / 100 measured from the lit limb, since the lit width along the
+    /// equator is exactly the illuminated fraction.
+    pub terminator_fraction: f64,
+    /// Geocentric declination in degrees (north positive).
+    pub declination: f64,
+    /// Ecliptic latitude in degrees: how far above/below the Sun's path the Moon is.
+    pub ecliptic_lat: f64,
+    /// Argument of latitude in degrees (0 = ascending node, 180 = descending node).
+    pub arg_latitude: f64,
+    /// Geocentric ecliptic longitude in degrees (0..360).
+    pub ecliptic_lon: f64,
+    /// Moon's longitude minus the Sun's in degrees (0..360); `phase_fraction` is this / 360.
+    pub elongation_deg: f64,
+    /// Geocentric right ascension in degrees (0..360).
+    pub right_ascension: f64,
+    /// Earth-Moon center distance in km.
+    pub distance_km: f64,
+}
+
+// Mean lunar radius in km, for the apparent (angular) diameter.
+const MOON_RADIUS_KM: f64 = 1737.4;
+
+impl MoonStatus {
+    /// Apparent diameter of the disc in degrees (about 0.49°–0.56° over the month).
+    pub fn angular_diameter(&self) -> f64 {
+        2.0 * (MOON_RADIUS_KM / self.distance_km).asin().to_degrees()
+    }
+
+    /// Between new and full, when the lit part grows.
+    pub fn is_waxing(&self) -> bool {
+        self.phase_fraction < 0.5
+    }
+
+    /// "Waxing" or "waning" in `language`.
+    pub fn trend_word(&self, language: Language) -> &'static str {
+        WAX_WANE[language as usize][usize::from(!self.is_waxing())]
+    }
+
+    /// Days since new moon, from the phase fraction at the mean synodic rate: progress through
+    /// the 29.53-day cycle of phases, measured against the Sun. Same as `age_days`.
+    ///
+    /// ```
+    /// use ascii_moon::{calculate_moon_phase, SYNODIC_MONTH};
+    /// use chrono::{TimeZone, Utc};
+    ///
+    /// // Full moon, 2025-03-14 06:55 UTC: about half a lunation in.
+    /// let full = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 3, 14, 6, 55, 0).unwrap());
+    /// assert!((full.synodic_age_days() - SYNODIC_MONTH / 2.0).abs() < 1.0);
+    /// ```
+    pub fn synodic_age_days(&self) -> f64 {
+        self.phase_fraction * SYNODIC_MONTH
+    }
+
+    /// Days into the sidereal month, counted from ecliptic longitude 0° (the March equinox
+    /// point) at the mean rate: the Moon's progress against the stars rather than the Sun.
+    /// Runs ahead of the synodic age and wraps sooner (27.32 days), and doesn't track the phase.
+    ///
+    /// ```
+    /// use ascii_moon::{calculate_moon_phase, SIDEREAL_MONTH};
+    /// use chrono::{TimeZone, Utc};
+    ///
+    /// let full = calculate_moon_phase(Utc.with_ymd_and_hms(2025, 3, 14, 6, 55, 0).unwrap());
+    /// assert!((0.0..SIDEREAL_MONTH).contains(&full.sidereal_age_days()));
+    /// assert_ne!(full.sidereal_age_days(), full.synodic_age_days());
+    /// ```
+    pub fn sidereal_age_days(&self) -> f64 {
+        self.ecliptic_lon / 360.0 * SIDEREAL_MONTH
+    }
+
+    /// The Moon's angular distance from the Sun in degrees: 0° at new, 90° at the quarters,
+    /// 180° at full (the elongation, the same on the waxing and waning sides).
+    pub fn phase_angle(&self) -> f64 {
+        self.elongation_deg.min(360.0 - self.elongation_deg)
+    }
+
+    /// Index into `ZODIAC_SIGNS` of the sign the Moon is in.
+    pub fn zodiac_sign(&self) -> usize {
+        (self.ecliptic_lon / 30.0).floor() as usize % 12
+    }
+}
+
+pub fn normalize_degrees(mut deg: f64) -> f64 {
+    deg %= 360.0;
+    if deg < 0.0 {
+        deg += 360.0;
+    }
+    deg
+}
+
+pub fn deg_to_rad(deg: f64) -> f64 {
+    deg * std::f64::consts::PI / 180.0
+}
+
+pub fn julian_day_utc(dt: DateTime<Utc>) -> f64 {
+    // Unix epoch (1970-01-01T00:00:00Z) is JD 2440587.5
+    let unix = dt.timestamp() as f64 + (dt.timestamp_subsec_nanos() as f64) * 1e-9;
+    unix / 86400.0 + 2440587.5
+}
+
+// Dates for which the approximate series below has been checked against ephemerides.
+// Outside this window the answers drift (slowly) and `try_calculate_moon_phase` refuses them.
+pub const SUPPORTED_YEARS: std::ops::RangeInclusive<i32> = 1900..=2100;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoonError {
+    /// The date is outside `SUPPORTED_YEARS`.
+    OutOfRange(DateTime<Utc>),
+    /// A computed quantity came out NaN or infinite.
+    NonFinite(&'static str),
+}
+
+impl std::fmt::Display for MoonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoonError::OutOfRange(date) => write!(
+                f,
+                "{} is outside the supported range {}-{}",
+                date.format("%Y-%m-%d"),
+                SUPPORTED_YEARS.start(),
+                SUPPORTED_YEARS.end()
+            ),
+            MoonError::NonFinite(what) => write!(f, "computed {what} is not a finite number"),
+        }
+    }
+}
+
+/// Validated moon status: rejects dates outside `SUPPORTED_YEARS` and any non-finite result.
+pub fn try_calculate_moon_phase_with(date: DateTime<Utc>, precision: Precision) -> Result<MoonStatus, MoonError> {
+    if !SUPPORTED_YEARS.contains(&date.year()) {
+        return Err(MoonError::OutOfRange(date));
+    }
+    let status = moon_status_unchecked(date, precision);
+    for (what, value) in [
+        ("phase fraction", status.phase_fraction),
+        ("age", status.age_days),
+        ("illumination", status.illumination),
+        ("libration", status.libration_lon),
+        ("libration", status.libration_lat),
+        ("terminator", status.terminator_fraction),
+        ("declination", status.declination),
+        ("ecliptic latitude", status.ecliptic_lat),
+        ("ecliptic longitude", status.ecliptic_lon),
+        ("right ascension", status.right_ascension),
+        ("distance", status.distance_km),
+    ] {
+        if !value.is_finite() {
+            return Err(MoonError::NonFinite(what));
+        }
+    }
+    Ok(status)
+}
+
+/// `try_calculate_moon_phase_with` at `Precision::Standard`.
+pub fn try_calculate_moon_phase(date: DateTime<Utc>) -> Result<MoonStatus, MoonError> {
+    try_calculate_moon_phase_with(date, Precision::Standard)
+}
+
+/// Infallible moon status for the app's own use.
+///
+/// Out-of-range dates still get the (less accurate) series result rather than an error;
+/// a non-finite result is a bug in the math and panics.
+pub fn calculate_moon_phase_with(date: DateTime<Utc>, precision: Precision) -> MoonStatus {
+    match try_calculate_moon_phase_with(date, precision) {
+        Ok(status) => status,
+        Err(MoonError::OutOfRange(_)) => moon_status_unchecked(date, precision),
+        Err(err) => panic!("{err}"),
+    }
+}
+
+/// `calculate_moon_phase_with` at `Precision::Standard`.
+pub fn calculate_moon_phase(date: DateTime<Utc>) -> MoonStatus {
+    calculate_moon_phase_with(date, Precision::Standard)
+}
+
+// Meeus table 47.A: multiples of (D, M, M', F), then the longitude term (sin, 1e-6 degrees)
+// and the distance term (cos, meters).
+const MEEUS_LON_DIST: &[(i8, i8, i8, i8, f64, f64)] = &[
+    (0, 0, 1, 0, 6288774.0, -20905355.0),
+    (2, 0, -1, 0, 1274027.0, -3699111.0),
+    (2, 0, 0, 0, 658314.0, -2955968.0),
+    (0, 0, 2, 0, 213618.0, -569925.0),
+    (0, 1, 0, 0, -185116.0, 48888.0),
+    (0, 0, 0, 2, -114332.0, -3149.0),
+    (2, 0, -2, 0, 58793.0, 246158.0),
+    (2, -1, -1, 0, 57066.0, -152138.0),
+    (2, 0, 1, 0, 53322.0, -170733.0),
+    (2, -1, 0, 0, 45758.0, -204586.0),
+    (0, 1, -1, 0, -40923.0, -129620.0),
+    (1, 0, 0, 0, -34720.0, 108743.0),
+    (0, 1, 1, 0, -30383.0, 104755.0),
+    (2, 0, 0, -2, 15327.0, 10321.0),
+    (0, 0, 1, 2, -12528.0, 0.0),
+    (0, 0, 1, -2, 10980.0, 79661.0),
+    (4, 0, -1, 0, 10675.0, -34782.0),
+    (0, 0, 3, 0, 10034.0, -23210.0),
+    (4, 0, -2, 0, 8548.0, -21636.0),
+    (2, 1, -1, 0, -7888.0, 24208.0),
+    (2, 1, 0, 0, -6766.0, 30824.0),
+    (1, 0, -1, 0, -5163.0, -8379.0),
+    (1, 1, 0, 0, 4987.0, -16675.0),
+    (2, -1, 1, 0, 4036.0, -12831.0),
+    (2, 0, 2, 0, 3994.0, -10445.0),
+    (4, 0, 0, 0, 3861.0, -11650.0),
+    (2, 0, -3, 0, 3665.0, 14403.0),
+    (0, 1, -2, 0, -2689.0, -7003.0),
+    (2, 0, -1, 2, -2602.0, 0.0),
+    (2, -1, -2, 0, 2390.0, 10056.0),
+    (1, 0, 1, 0, -2348.0, 6322.0),
+    (2, -2, 0, 0, 2236.0, -9884.0),
+    (0, 1, 2, 0, -2120.0, 5751.0),
+    (0, 2, 0, 0, -2069.0, 0.0),
+    (2, -2, -1, 0, 2048.0, -4950.0),
+    (2, 0, 1, -2, -1773.0, 4130.0),
+    (2, 0, 0, 2, -1595.0, 0.0),
+    (4, -1, -1, 0, 1215.0, -3958.0),
+    (0, 0, 2, 2, -1110.0, 0.0),
+    (3, 0, -1, 0, -892.0, 3258.0),
+    (2, 1, 1, 0, -810.0, 2616.0),
+    (4, -1, -2, 0, 759.0, -1897.0),
+    (0, 2, -1, 0, -713.0, -2117.0),
+    (2, 2, -1, 0, -700.0, 2354.0),
+    (2, 1, -2, 0, 691.0, 0.0),
+    (2, -1, 0, -2, 596.0, 0.0),
+    (4, 0, 1, 0, 549.0, -1423.0),
+    (0, 0, 4, 0, 537.0, -1117.0),
+    (4, -1, 0, 0, 520.0, -1571.0),
+    (1, 0, -2, 0, -487.0, -1739.0),
+    (2, 1, 0, -2, -399.0, 0.0),
+    (0, 0, 2, -2, -381.0, -4421.0),
+    (1, 1, 1, 0, 351.0, 0.0),
+    (3, 0, -2, 0, -340.0, 0.0),
+    (4, 0, -3, 0, 330.0, 0.0),
+    (2, -1, 2, 0, 327.0, 0.0),
+    (0, 2, 1, 0, -323.0, 1165.0),
+    (1, 1, -1, 0, 299.0, 0.0),
+    (2, 0, 3, 0, 294.0, 0.0),
+    (2, 0, -1, -2, 0.0, 8752.0),
+];
+
+// Meeus table 47.B: multiples of (D, M, M', F) and the latitude term (sin, 1e-6 degrees).
+const MEEUS_LAT: &[(i8, i8, i8, i8, f64)] = &[
+    (0, 0, 0, 1, 5128122.0),
+    (0, 0, 1, 1, 280602.0),
+    (0, 0, 1, -1, 277693.0),
+    (2, 0, 0, -1, 173237.0),
+    (2, 0, -1, 1, 55413.0),
+    (2, 0, -1, -1, 46271.0),
+    (2, 0, 0, 1, 32573.0),
+    (0, 0, 2, 1, 17198.0),
+    (2, 0, 1, -1, 9266.0),
+    (0, 0, 2, -1, 8822.0),
+    (2, -1, 0, -1, 8216.0),
+    (2, 0, -2, -1, 4324.0),
+    (2, 0, 1, 1, 4200.0),
+    (2, 1, 0, -1, -3359.0),
+    (2, -1, -1, 1, 2463.0),
+    (2, -1, 0, 1, 2211.0),
+    (2, -1, -1, -1, 2065.0),
+    (0, 1, -1, -1, -1870.0),
+    (4, 0, -1, -1, 1828.0),
+    (0, 1, 0, 1, -1794.0),
+    (0, 0, 0, 3, -1749.0),
+    (0, 1, -1, 1, -1565.0),
+    (1, 0, 0, 1, -1491.0),
+    (0, 1, 1, 1, -1475.0),
+    (0, 1, 1, -1, -1410.0),
+    (0, 1, 0, -1, -1344.0),
+    (1, 0, 0, -1, -1335.0),
+    (0, 0, 3, 1, 1107.0),
+    (4, 0, 0, -1, 1021.0),
+    (4, 0, -1, 1, 833.0),
+];
+
+/// High-precision geocentric Moon: (ecliptic longitude°, ecliptic latitude°, distance km).
+///
+/// Meeus, *Astronomical Algorithms*, chapter 47. `d` is days since J2000.0 (UT is used
+/// for TT; the ~1 minute difference moves the Moon about half an arcminute).
+fn meeus_moon_position(d: f64) -> (f64, f64, f64) {
+    let t = d / 36525.0;
+    let l = normalize_degrees(218.316_447_7 + 481_267.881_234_21 * t - 0.001_578_6 * t * t);
+    let dm = normalize_degrees(297.850_192_1 + 445_267.111_403_4 * t - 0.001_881_9 * t * t);
+    let m = normalize_degrees(357.529_109_2 + 35_999.050_290_9 * t - 0.000_153_6 * t * t);
+    let mm = normalize_degrees(134.963_396_4 + 477_198.867_505_5 * t + 0.008_741_4 * t * t);
+    let f = normalize_degrees(93.272_095_0 + 483_202.017_523_3 * t - 0.003_653_9 * t * t);
+    let a1 = 119.75 + 131.849 * t;
+    let a2 = 53.09 + 479_264.290 * t;
+    let a3 = 313.45 + 481_266.484 * t;
+    // Terms in the Sun's anomaly shrink with Earth's slowly decreasing eccentricity.
+    let e = 1.0 - 0.002_516 * t - 0.000_007_4 * t * t;
+    let arg = |cd: i8, cm: i8, cmm: i8, cf: i8| {
+        let factor = e.powi(cm.unsigned_abs() as i32);
+        let angle = deg_to_rad(cd as f64 * dm + cm as f64 * m + cmm as f64 * mm + cf as f64 * f);
+        (factor, angle)
+    };
+
+    let (mut sum_l, mut sum_r) = (0.0, 0.0);
+    for &(cd, cm, cmm, cf, coeff_l, coeff_r) in MEEUS_LON_DIST {
+        let (factor, angle) = arg(cd, cm, cmm, cf);
+        sum_l += factor * coeff_l * angle.sin();
+        sum_r += factor * coeff_r * angle.cos();
+    }
+    let mut sum_b = 0.0;
+    for &(cd, cm, cmm, cf, coeff_b) in MEEUS_LAT {
+        let (factor, angle) = arg(cd, cm, cmm, cf);
+        sum_b += factor * coeff_b * angle.sin();
+    }
+    // Venus, Jupiter and Earth-flattening corrections.
+    sum_l += 3958.0 * deg_to_rad(a1).sin() + 1962.0 * deg_to_rad(l - f).sin() + 318.0 * deg_to_rad(a2).sin();
+    sum_b += -2235.0 * deg_to_rad(l).sin()
+        + 382.0 * deg_to_rad(a3).sin()
+        + 175.0 * deg_to_rad(a1 - f).sin()
+        + 175.0 * deg_to_rad(a1 + f).sin()
+        + 127.0 * deg_to_rad(l - mm).sin()
+        - 115.0 * deg_to_rad(l + mm).sin();
+
+    (
+        normalize_degrees(l + sum_l / 1e6),
+        sum_b / 1e6,
+        385_000.56 + sum_r / 1000.0,
+    )
+}
+
+fn moon_status_unchecked(date: DateTime<Utc>, precision: Precision) -> MoonStatus {
+    // This uses a common Meeus-style approximation:
+    // compute Sun and Moon ecliptic longitudes and take their elongation.
+    // This is far more accurate than assuming a constant-length synodic month.
+    let jd = julian_day_utc(date);
+    let d = jd - 2451545.0; // days since J2000.0
+
+    // Sun (approx): mean longitude L and mean anomaly g
+    let l0 = normalize_degrees(280.460 + 0.9856474 * d);
+    let g = normalize_degrees(357.528 + 0.9856003 * d);
+    let lambda_sun = normalize_degrees(
+        l0 + 1.915 * deg_to_rad(g).sin() + 0.020 * deg_to_rad(2.0 * g).sin(),
+    );
+
+    // Moon (approx): mean longitude l, mean anomaly Mm, mean elongation D, argument of latitude F
+    let l = normalize_degrees(218.316 + 13.176396 * d);
+    let mm = normalize_degrees(134.963 + 13.064993 * d);
+    let d_moon = normalize_degrees(297.850 + 12.190749 * d);
+    let f = normalize_degrees(93.272 + 13.229350 * d);
+
+    // Moon longitude with a set of major periodic terms (degrees)
+    let lambda_moon = normalize_degrees(
+        l + 6.289 * deg_to_rad(mm).sin()
+            + 1.274 * deg_to_rad(2.0 * d_moon - mm).sin()
+            + 0.658 * deg_to_rad(2.0 * d_moon).sin()
+            + 0.214 * deg_to_rad(2.0 * mm).sin()
+            - 0.186 * deg_to_rad(g).sin()
+            - 0.059 * deg_to_rad(2.0 * d_moon - 2.0 * mm).sin()
+            - 0.057 * deg_to_rad(2.0 * d_moon - mm - g).sin()
+            + 0.053 * deg_to_rad(2.0 * d_moon + mm).sin()
+            + 0.046 * deg_to_rad(2.0 * d_moon - g).sin()
+            + 0.041 * deg_to_rad(mm - g).sin()
+            - 0.035 * deg_to_rad(d_moon).sin()
+            - 0.031 * deg_to_rad(mm + g).sin()
+            - 0.015 * deg_to_rad(2.0 * f - 2.0 * d_moon).sin()
+            + 0.011 * deg_to_rad(2.0 * d_moon - 4.0 * mm).sin(),
+    );
+    // `--precision high` swaps in the full tables for longitude, latitude and distance.
+    let precise = (precision == Precision::High).then(|| meeus_moon_position(d));
+    let lambda_moon = precise.map_or(lambda_moon, |(lon, _, _)| lon);
+
+    // Elongation (0..360): 0=new, 180=full
+    let elongation_deg = normalize_degrees(lambda_moon - lambda_sun);
+    let phase_fraction = elongation_deg / 360.0;
+
+    // Express "age" in days using the mean synodic month (good enough for display).
+    let age = phase_fraction * SYNODIC_MONTH;
+
+    let phase = MoonPhase::from_fraction(phase_fraction);
+
+    let illumination = 0.5 * (1.0 - deg_to_rad(elongation_deg).cos());
+
+    // Moon ecliptic latitude with the main periodic terms (degrees)
+    let beta_moon = 5.128 * deg_to_rad(f).sin()
+        + 0.281 * deg_to_rad(mm + f).sin()
+        + 0.278 * deg_to_rad(mm - f).sin()
+        + 0.173 * deg_to_rad(2.0 * d_moon - f).sin()
+        + 0.055 * deg_to_rad(2.0 * d_moon - mm + f).sin()
+        + 0.046 * deg_to_rad(2.0 * d_moon - mm - f).sin()
+        + 0.033 * deg_to_rad(2.0 * d_moon + f).sin()
+        + 0.017 * deg_to_rad(2.0 * mm + f).sin();
+    let beta_moon = precise.map_or(beta_moon, |(_, lat, _)| lat);
+
+    // Ecliptic -> equatorial: declination via the obliquity of the ecliptic
+    let obliquity = 23.439 - 0.000_000_4 * d;
+    let declination = (deg_to_rad(beta_moon).sin() * deg_to_rad(obliquity).cos()
+        + deg_to_rad(beta_moon).cos() * deg_to_rad(obliquity).sin() * deg_to_rad(lambda_moon).sin())
+    .asin()
+    .to_degrees();
+    let right_ascension = normalize_degrees(
+        (deg_to_rad(lambda_moon).sin() * deg_to_rad(obliquity).cos()
+            - deg_to_rad(beta_moon).tan() * deg_to_rad(obliquity).sin())
+        .atan2(deg_to_rad(lambda_moon).cos())
+        .to_degrees(),
+    );
+
+    // Distance (km) with the largest periodic terms
+    let distance_km = 385_000.56 - 20_905.355 * deg_to_rad(mm).cos()
+        - 3_699.111 * deg_to_rad(2.0 * d_moon - mm).cos()
+        - 2_955.968 * deg_to_rad(2.0 * d_moon).cos()
+        - 569.925 * deg_to_rad(2.0 * mm).cos()
+        + 48.888 * deg_to_rad(g).cos()
+        + 246.158 * deg_to_rad(2.0 * d_moon - 2.0 * mm).cos()
+        - 152.138 * deg_to_rad(2.0 * d_moon - g - mm).cos()
+        - 170.733 * deg_to_rad(2.0 * d_moon + mm).cos()
+        - 204.586 * deg_to_rad(2.0 * d_moon - g).cos()
+        - 129.620 * deg_to_rad(g - mm).cos()
+        + 108.743 * deg_to_rad(d_moon).cos()
+        + 104.755 * deg_to_rad(g + mm).cos();
+    let distance_km = precise.map_or(distance_km, |(_, _, dist)| dist);
+
+    // Optical libration (approx): the Moon spins uniformly at its mean motion, so in
+    // longitude we see it turned by (mean - true) longitude; in latitude the lunar equator
+    // is tilted ~6.68° to our line of sight, peaking when the argument of latitude F is ±90°.
+    let mut libration_lon = normalize_degrees(l - lambda_moon);
+    if libration_lon > 180.0 {
+        libration_lon -= 360.0;
+    }
+    let libration_lat = -6.68 * deg_to_rad(f).sin();
+
+    MoonStatus {
+        phase,
+        phase_fraction,
+        age_days: age,
+        illumination: illumination * 100.0,
+        libration_lon,
+        libration_lat,
+        terminator_fraction: terminator_fraction(elongation_deg),
+        elongation_deg,
+        declination,
+        ecliptic_lat: beta_moon,
+        arg_latitude: f,
+        ecliptic_lon: lambda_moon,
+        right_ascension,
+        distance_km,
+    }
+}
+
+/// Equator crossing of the terminator as drawn by `MoonWidget` (0 = left limb, 1 = right limb).
+///
+/// The renderer lights points where `u*sin(a) - z*cos(a) > 0`; on the equator `z = sqrt(1 - u^2)`,
+/// so the boundary sits at `u = cos(a)` while waxing and `u = -cos(a)` while waning.
+pub fn terminator_fraction(elongation_deg: f64) -> f64 {
+    let a = deg_to_rad(elongation_deg);
+    let u = if a.sin() >= 0.0 { a.cos() } else { -a.cos() };
+    (1.0 + u) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn high_precision_tracks_reference_ephemeris_more_tightly() {
+        // Meeus, Astronomical Algorithms, example 47.a: 1992-04-12 0h TD gives
+        // λ = 133.162655°, Δ = 368409.7 km. ΔT was ~59 s, so that's 23:59:01 UTC.
+        let dt = Utc.with_ymd_and_hms(1992, 4, 11, 23, 59, 1).unwrap();
+        let d = julian_day_utc(dt) - 2451545.0;
+        let (lon, _, dist) = meeus_moon_position(d);
+        let standard = calculate_moon_phase(dt);
+        assert_eq!(calculate_moon_phase_with(dt, Precision::High).ecliptic_lon, lon);
+
+        let (ref_lon, ref_dist) = (133.162_655, 368_409.7);
+        let high_err = (lon - ref_lon).abs();
+        let standard_err = (standard.ecliptic_lon - ref_lon).abs();
+        assert!(high_err < 1.0 / 60.0, "high precision off by {:.4}°", high_err);
+        assert!(high_err < standard_err, "high {:.4}° vs standard {:.4}°", high_err, standard_err);
+        assert!((dist - ref_dist).abs() < 5.0, "distance {:.1} km", dist);
+        assert!((dist - ref_dist).abs() < (standard.distance_km - ref_dist).abs());
+    }
+
+    #[test]
+    fn sidereal_age_runs_about_a_day_per_day() {
+        let start = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        let mut previous = calculate_moon_phase(start).sidereal_age_days();
+        for day in 1..60 {
+            let age = calculate_moon_phase(start + Duration::days(day)).sidereal_age_days();
+            assert!((0.0..SIDEREAL_MONTH).contains(&age));
+            // The Moon's speed varies ±15% around the mean; wrapping resets to ~0.
+            let step = (age - previous).rem_euclid(SIDEREAL_MONTH);
+            assert!((0.75..1.3).contains(&step), "day {day}: step {step:.2}");
+            previous = age;
+        }
+        let moon = calculate_moon_phase(start);
+        assert!((moon.synodic_age_days() - moon.age_days).abs() < 1e-9);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Language;
+use crate::{Language, MoonPhase};
 use rand::seq::SliceRandom;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -8,6 +8,23 @@ pub struct Poem {
     pub title: String,
     pub author: String,
     pub lines: Vec<String>,
+    /// Optional moon-phase tags from the `phase:` header line (lowercase, e.g. `full`).
+    pub phases: Vec<String>,
+}
+
+impl Poem {
+    pub fn matches_phase(&self, phase: MoonPhase) -> bool {
+        self.phases.iter().any(|t| t == phase.tag())
+    }
+
+    /// Case-insensitive substring match on the title, author, or any line.
+    pub fn matches_query(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [&self.title, &self.author]
+            .into_iter()
+            .chain(&self.lines)
+            .any(|text| text.to_lowercase().contains(&query))
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -35,6 +52,22 @@ impl PoemLibrary {
         self.for_language(lang).choose(&mut rng).cloned()
     }
 
+    /// Like `random_poem`, but prefers poems tagged for `phase`.
+    ///
+    /// Falls back to any poem in the language when none carry a matching tag.
+    pub fn random_poem_for_phase(&self, lang: Language, phase: MoonPhase) -> Option<Poem> {
+        let mut rng = rand::thread_rng();
+        let matching: Vec<&Poem> = self
+            .for_language(lang)
+            .iter()
+            .filter(|p| p.matches_phase(phase))
+            .collect();
+        match matching.choose(&mut rng) {
+            Some(p) => Some((*p).clone()),
+            None => self.random_poem(lang),
+        }
+    }
+
     fn push(&mut self, lang: Language, poem: Poem) {
         match lang {
             Language::English => self.en.push(poem),
@@ -60,12 +93,26 @@ fn parse_poem_text(text: &str) -> Option<Poem> {
     // File format:
     // Line 1: title
     // Line 2: author
-    // Optional line 3: --- (separator)
+    // Optional line 3: phase: <tag>[, <tag>...] (e.g. `phase: full` or `phase: crescent, new`)
+    // Optional next line: --- (separator)
     // Remaining lines: poem body (blank lines preserved)
-    let mut lines_iter = text.lines();
+    let mut lines_iter = text.lines().peekable();
     let title = lines_iter.next()?.trim().to_string();
     let author = lines_iter.next().unwrap_or("").trim().to_string();
 
+    let mut phases: Vec<String> = Vec::new();
+    if let Some(tags) = lines_iter
+        .peek()
+        .and_then(|l| l.trim().strip_prefix("phase:"))
+    {
+        phases = tags
+            .split(',')
+            .map(|t| t.trim().to_ascii_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+        lines_iter.next();
+    }
+
     let mut body: Vec<String> = Vec::new();
     let mut started = false;
     for (i, l) in lines_iter.enumerate() {
@@ -94,6 +141,7 @@ fn parse_poem_text(text: &str) -> Option<Poem> {
         title,
         author,
         lines: body,
+        phases,
     })
 }
 
@@ -111,8 +159,10 @@ fn load_poems_from_dir(base_dir: &Path) -> PoemLibrary {
         dir.push(lang_dir(lang));
 
         let Ok(read_dir) = fs::read_dir(&dir) else { continue };
-        for entry in read_dir.flatten() {
-            let path = entry.path();
+        // Sorted so `--poem-order sequential` follows file-name order on every platform.
+        let mut paths: Vec<PathBuf> = read_dir.flatten().map(|e| e.path()).collect();
+        paths.sort();
+        for path in paths {
             if path.extension().and_then(|e| e.to_str()) != Some("txt") {
                 continue;
             }
@@ -158,75 +208,34 @@ fn installed_poems_dir_from_exe() -> Option<PathBuf> {
     Some(prefix.join("share").join("ascii_moon").join("poems"))
 }
 
-fn default_poems() -> PoemLibrary {
+// (language code, file text) for every `poems/<lang>/*.txt`, generated by `build.rs`.
+const EMBEDDED_POEMS: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/embedded_poems.rs"));
+
+/// Only the poems embedded in the binary. Never touches the filesystem.
+pub fn default_poems() -> PoemLibrary {
     let mut lib = PoemLibrary::default();
 
     // Keep defaults in-repo but embedded in the binary, so the app still works
     // even when run from a directory without `./poems`.
-    let defaults: &[(Language, &str)] = &[
-        (Language::English, include_str!("../poems/en/the_moon_stevenson.txt")),
-        (
-            Language::English,
-            include_str!("../poems/en/to_the_moon_shelley_excerpt.txt"),
-        ),
-        (
-            Language::English,
-            include_str!("../poems/en/the_moon_dickinson_1896.txt"),
-        ),
-        (Language::Chinese, include_str!("../poems/zh/jing_ye_si_li_bai.txt")),
-        (
-            Language::Chinese,
-            include_str!("../poems/zh/wang_yue_huai_yuan_zhang_jiu_ling.txt"),
-        ),
-        (
-            Language::Chinese,
-            include_str!("../poems/zh/shi_wu_ye_wang_yue_wang_jian.txt"),
-        ),
-        (
-            Language::French,
-            include_str!("../poems/fr/clair_de_lune_verlaine_excerpt.txt"),
-        ),
-        (
-            Language::French,
-            include_str!("../poems/fr/au_clair_de_la_lune_traditionnel.txt"),
-        ),
-        (
-            Language::French,
-            include_str!("../poems/fr/la_lune_blanche_verlaine.txt"),
-        ),
-        (Language::Japanese, include_str!("../poems/ja/meigetsu_ya_basho.txt")),
-        (Language::Japanese, include_str!("../poems/ja/meigetsu_wo_issa.txt")),
-        (
-            Language::Japanese,
-            include_str!("../poems/ja/tsuki_tenshin_buson.txt"),
-        ),
-        (
-            Language::Spanish,
-            include_str!("../poems/es/romance_de_la_luna_lorca_excerpt.txt"),
-        ),
-        (
-            Language::Spanish,
-            include_str!("../poems/es/luna_lunera_tradicional.txt"),
-        ),
-    ];
-
-    for (lang, text) in defaults {
+    for (code, text) in EMBEDDED_POEMS {
+        let Ok(lang) = code.parse::<Language>() else { continue };
         if let Some(poem) = parse_poem_text(text) {
-            lib.push(*lang, poem);
+            lib.push(lang, poem);
         }
     }
 
     lib
 }
 
-/// Load poems from the filesystem (for customization) and merge with built-in defaults.
+/// Load poems from the filesystem (for customization) and combine them with built-in defaults.
 ///
 /// - If `poems_dir` is `None`, we try (in order):
 ///   - `./poems` (current working directory)
 ///   - `<prefix>/share/ascii_moon/poems` (derived from the installed binary location)
-/// - If a language has at least one poem in the filesystem dir, we use those poems for that language.
-///   Otherwise, we fall back to built-in poems for that language.
-pub fn load_poems(poems_dir: Option<&Path>) -> PoemLibrary {
+/// - Replace (default): if a language has at least one poem in the filesystem dir, we use those
+///   poems for that language. Otherwise, we fall back to built-in poems for that language.
+/// - Merge (`merge == true`): see `combine_libraries`.
+pub fn load_poems(poems_dir: Option<&Path>, merge: bool) -> PoemLibrary {
     let defaults = default_poems();
 
     let dir = if let Some(p) = poems_dir {
@@ -247,8 +256,14 @@ pub fn load_poems(poems_dir: Option<&Path>) -> PoemLibrary {
     };
 
     let fs_lib = load_poems_from_dir(&dir);
+    combine_libraries(&defaults, &fs_lib, merge)
+}
 
-    let mut merged = PoemLibrary::default();
+/// Per language: either the custom poems replace the defaults (when there are any), or with
+/// `merge` the defaults come first and custom poems are appended in file order. When merging, a
+/// custom poem with the same title and author as one already present takes its place instead.
+fn combine_libraries(defaults: &PoemLibrary, custom: &PoemLibrary, merge: bool) -> PoemLibrary {
+    let mut combined = PoemLibrary::default();
     for lang in [
         Language::English,
         Language::Chinese,
@@ -256,22 +271,116 @@ pub fn load_poems(poems_dir: Option<&Path>) -> PoemLibrary {
         Language::Japanese,
         Language::Spanish,
     ] {
-        let fs_poems = fs_lib.for_language(lang);
-        if !fs_poems.is_empty() {
+        let fs_poems = custom.for_language(lang);
+        if merge {
+            let mut poems: Vec<Poem> = defaults.for_language(lang).to_vec();
+            for p in fs_poems {
+                match poems.iter_mut().find(|q| q.title == p.title && q.author == p.author) {
+                    Some(existing) => *existing = p.clone(),
+                    None => poems.push(p.clone()),
+                }
+            }
+            for p in poems {
+                combined.push(lang, p);
+            }
+        } else if !fs_poems.is_empty() {
             for p in fs_poems {
-                merged.push(lang, p.clone());
+                combined.push(lang, p.clone());
             }
         } else {
             for p in defaults.for_language(lang) {
-                merged.push(lang, p.clone());
+                combined.push(lang, p.clone());
             }
         }
     }
 
-    merged
+    combined
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    #[test]
+    fn parses_title_author_and_body_without_separator() {
+        let poem = parse_poem_text("The Moon\nR. L. Stevenson\nThe moon has a face\nlike the clock").unwrap();
+        assert_eq!(poem.title, "The Moon");
+        assert_eq!(poem.author, "R. L. Stevenson");
+        assert_eq!(poem.lines, vec!["The moon has a face", "like the clock"]);
+    }
 
+    #[test]
+    fn skips_separator_line() {
+        let poem = parse_poem_text("Title\nAuthor\n---\nfirst\nsecond\n").unwrap();
+        assert_eq!(poem.lines, vec!["first", "second"]);
+    }
 
+    #[test]
+    fn only_the_first_separator_is_skipped() {
+        let poem = parse_poem_text("Title\nAuthor\n---\nfirst\n---\nsecond").unwrap();
+        assert_eq!(poem.lines, vec!["first", "---", "second"]);
+    }
+
+    #[test]
+    fn all_blank_body_is_rejected() {
+        assert!(parse_poem_text("Title\nAuthor\n---\n\n   \n\n").is_none());
+        assert!(parse_poem_text("Title\nAuthor\n").is_none());
+        assert!(parse_poem_text("").is_none());
+    }
+
+    #[test]
+    fn empty_author_line_is_allowed() {
+        let poem = parse_poem_text("Title\n\n---\nbody").unwrap();
+        assert_eq!(poem.author, "");
+        assert_eq!(poem.lines, vec!["body"]);
+    }
+
+    #[test]
+    fn crlf_line_endings_are_stripped() {
+        let poem = parse_poem_text("Title\r\nAuthor\r\n---\r\nfirst\r\n\r\nsecond\r\n\r\n").unwrap();
+        assert_eq!(poem.title, "Title");
+        assert_eq!(poem.author, "Author");
+        assert_eq!(poem.lines, vec!["first", "", "second"]);
+    }
 
+    #[test]
+    fn leading_blank_body_line_is_kept_but_trailing_ones_are_trimmed() {
+        // Only trailing blank lines are trimmed; a leading blank line is part of the body.
+        let poem = parse_poem_text("Title\nAuthor\n---\n\nfirst\n\n").unwrap();
+        assert_eq!(poem.lines, vec!["", "first"]);
+    }
+
+    #[test]
+    fn phase_header_is_parsed_before_separator() {
+        let poem = parse_poem_text("Title\nAuthor\nphase: Full, crescent\n---\nbody").unwrap();
+        assert_eq!(poem.phases, vec!["full", "crescent"]);
+        assert_eq!(poem.lines, vec!["body"]);
+    }
+
+    #[test]
+    fn merge_keeps_defaults_and_dedups_by_title_and_author() {
+        let poem = |title: &str, author: &str, line: &str| Poem {
+            title: title.to_string(),
+            author: author.to_string(),
+            lines: vec![line.to_string()],
+            phases: Vec::new(),
+        };
+        let mut defaults = PoemLibrary::default();
+        defaults.push(Language::English, poem("A", "x", "built-in"));
+        defaults.push(Language::English, poem("B", "y", "built-in"));
+        defaults.push(Language::French, poem("F", "z", "built-in"));
+        let mut custom = PoemLibrary::default();
+        custom.push(Language::English, poem("B", "y", "edited"));
+        custom.push(Language::English, poem("C", "w", "new"));
+
+        let replaced = combine_libraries(&defaults, &custom, false);
+        let titles = |lib: &PoemLibrary, lang| lib.for_language(lang).iter().map(|p| p.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(&replaced, Language::English), ["B", "C"]);
+        assert_eq!(titles(&replaced, Language::French), ["F"]);
+
+        let merged = combine_libraries(&defaults, &custom, true);
+        assert_eq!(titles(&merged, Language::English), ["A", "B", "C"]);
+        assert_eq!(merged.for_language(Language::English)[1].lines, ["edited"]);
+        assert_eq!(titles(&merged, Language::French), ["F"]);
+    }
+}
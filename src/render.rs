@@ -0,0 +1,1202 @@
+//! Drawing the moon into a ratatui `Buffer`: `render_moon`, `RenderOptions` and `MoonWidget`.
+
+use crate::MoonStatus;
+use ratatui::prelude::*;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// How much detail the moon is drawn with (`--lod`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lod {
+    /// No labels, and binary lighting (earthshine drawn as a flat shadow).
+    Low,
+    /// Follow the disc size: `Low` below `LOD_LOW_WIDTH` columns, `High` from `LOD_HIGH_WIDTH`,
+    /// and the standard detail in between.
+    Auto,
+    /// Earthshine gradient in place of the flat shadow, and labels for `DETAIL_FEATURES` too.
+    High,
+}
+
+impl std::str::FromStr for Lod {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(Lod::Low),
+            "auto" => Ok(Lod::Auto),
+            "high" => Ok(Lod::High),
+            _ => Err("level of detail must be one of: low, auto, high".to_string()),
+        }
+    }
+}
+
+impl Lod {
+    /// The level for a disc `disc_width` columns wide; `Auto` here means the standard detail.
+    fn at_width(self, disc_width: f64) -> Lod {
+        match self {
+            Lod::Auto if disc_width < LOD_LOW_WIDTH => Lod::Low,
+            Lod::Auto if disc_width >= LOD_HIGH_WIDTH => Lod::High,
+            lod => lod,
+        }
+    }
+}
+
+/// How the unlit part of the disc is drawn (cycled with `d` in the TUI).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowMode {
+    /// Flat graphite shadow over the whole night side.
+    Full,
+    /// Faint earthshine that fades away from the terminator.
+    Earthshine,
+    /// Only a dim ring along the dark limb; the terminal background shows through (`--bg-shadow`).
+    Outline,
+    /// Nothing drawn (`--hide-dark`).
+    Hidden,
+}
+
+impl ShadowMode {
+    pub fn next(&self) -> Self {
+        match self {
+            ShadowMode::Full => ShadowMode::Earthshine,
+            ShadowMode::Earthshine => ShadowMode::Outline,
+            ShadowMode::Outline => ShadowMode::Hidden,
+            ShadowMode::Hidden => ShadowMode::Full,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShadowMode::Full => "Full",
+            ShadowMode::Earthshine => "Earthshine",
+            ShadowMode::Outline => "Outline",
+            ShadowMode::Hidden => "Hidden",
+        }
+    }
+}
+
+const MOON_ART_RAW: &str = r#"                                                                                    #@&&%#%&(#&###&%###&&&&#/(@&(###.  %/#,                                                                             
+                                                                            #&%%#&@%(&%##(*%&%##(###&&%&%#(#%&%%%&%###%(%#(#((@&&&(/.                                                                   
+                                                                   .%&&##%###/%%#%%#&,%%&%%%%#%%%%%%&&&&%%%%##%&(#(%&(###%/##&##%(*(&%@#%*%/                                                            
+                                                             /#/%&%#%(@%##%(((#&&&%%%%&%%%%&%&&&&&&&%%%%%%%%%%%#####%#%&#%#%%%%%%%%&&&&%%.%%%%%*(                                                       
+                                                       ,(.@&%((#(@%#&%###(####((%&%%%%%%%&&&&&&&#&&&&&%%%##%###%####(%#%##%#%%%%%%&&%&&(%&&&&%&&%&&&#,                                                  
+                                                   /(*/**,.%#((((*###%###((###%##%(%%%#%%%%%%%%%%%%#%%%%%%##%########%(####%%%%%%%%%&&&&%#%%%&%%&%%%%%%&#&                                              
+                                               /*/((%%(#####((%((((((((#((#(##(###########%#%%#&%###%##(#%%%%#####(#%#(((##&#%##%%&&&%&%%%%%%%%#%/#%(#(/%%%###                                          
+                                           ,*/,(/%/#/((#((((/(((((*//(////((#((#//(/((((#########(#(##(#(##(#(#%%((((#(#####&%###%%%%%&%&&&%%%%%#%%###(((##(*,,,/((##/####                                   
+                                        .,.,///((/(((/(/*((/&*////**/*//********////((((((((#(##(##((#(#(#%%((((#(#####&%###%%%%%&%&&&%%%%%#%%###(((##(*,,,/((##/####                                   
+                                     .,,,**////*********,,,*,**//(//***********//*****/*,**////((/((///((((((((((##(####%#((###%%%%%&&&&%%%&####%&(((((##((%####%((%(#&*                                
+                                  ..,,,*,*,*,.,******//******,,*///////*****/******/********/////*/(/((///////(/(((//(/((((((((((#%%%%%&%&%%&%((#%#%(#(###(((#((#(##((#%%*@                             
+                               ,..,.,,,*,*....,,,*//(*/////((/(((((//(/**/*/***/((((((///**///////////((///////**(////*********(#/###%#%%%%#%&%///(%####(##(//(((((#((#(/(#(*                           
+                             ......,,,,,*,,.,,,****#&(((((#((/////(#//*/((####((//((//(((((((///////////((///////*//*/*/*/*******//((##%#%%#%%(#%%#%%%#(((#%##(##(%(#((((##(%*#*#                        
+                           ........,,,,**,*/*///(((((((%#/////(/(%/////**//##(#*,,,*#/(/(%%%#*//((/////////*/////*////***/******(((((#%##%########%(((##((###%%(#((%(((###%((#((%#                      
+                        ..........,,....,*///((//(((%##((((//(/(/*****,,,,***//(*/((*/(((#(####((#////(////###(#(((///(*///#((///###%####%#(##%####(///((####%###(##/(((####(##%#,%%                    
+                      .........,........,/(//((//#(,,,,,**,**//**,,,,,,,,,**/******//(#%((((((##((/(/*/////(#(/(((//(((((((/////(###(%%%####%%%#%&##((/(/*//((#(*((##(######(((((##(#@                  
+                    ...............,..,***/*////(/*,,,,*,,,,,.....,,,,,,,,************//(#%(#(##(((///((((/(((((((#(((((////////#(((###(#####%###%##((((((((((/#((/((##%&%%##(((%(%/(#(%                
+                   ..................**/////*/(//,,,,.,,,,,.........,,,,*,,,,**,*,********//#/((###(##((((##((###(%#####(///(##(/#((//(((((((((########(#(##(%#((#%((##(#((%####(##%###((%%               
+                 ...............,,,,*//**//*//*,**,.............,,.,,,,,,,***,*#,****/****##(((((###(((((###%##%(((#(((///////#(#((%(#(((((((((#######(///%##(#((###(#(((#((((((#(%#(#(//(#             
+               ..,,............,,,,*******(/,,...,......,..,..,,,,,.,,,,**,,,,,*,***/*****/%(/(#/####((#(##(####((#(/((/(/#//###(((((//(//(#%#####%#%##/##((###%#%#((((#(//((((#/((##(((#((,            
+              ................,.,,,,**,***,,,.,,,,,,................,,,,/,,,,,,********#//////###/(((###%%(((###((((/(/****/(///((/(///*(//(%#((#####((((########%%##(%(((((##((((#####%##((((          
+            /*..............,,..,,.,.,*,,...,...,......,........,.,.,,,,*,,,,,*******//////////////#%%%%####(##((#(((((/**/*////((((((****/(#%###((((#####(%#%##%%&%#%(#((##%/#%(##((##(%%##(##         
+           (.,.........,.,..,,,,...,,*,,....,,,,,,,,.,,,......,,,,,,,,,,**,,,,,,****///*////*(/((/(###(#(/****//(((((((/***(//***(//**/***//(((#%##%%%###(((##((##%%%##((((((###%(#########(#///        
+           ..........,...,,,,,,,,,,,,,,..,,,,,.......,..,...,,.,,,,,,,,,,,,,,,******/**//**/**//(##((**,*,,,,,****/*/(((**,,**///(/****//((///(%%%#%#%#%(#(###%#%%%((((/%(((##&#%#&###%#%%#(*//       
+         ,/.........,.,.,./,**,,,,,,,**,,,,,,,,...........,....,,,,...,,,,,,.,,*(*,***//#///***/**((#((*******,********,,***,****//(((////(####%%%#####&%%#%#(#%####%#(((##(#(#(((#(####%(#%%(##(/      
+        ..............,,*/,,,,*,,,,.,.,,,,,,,,,,,,,,....,,,,,,,,,,,,,,,,,,,,,,,,*****////*****//////,,,,,,,,,,*,*,,,,,**,,,,,,/(##%%((///#%#%%##%%#%##%%%##%%###%&###(######%####/%(((##%###/#((#     
+       ..............,,*//*,,****,,,,,*(,,*,,.,,,,.,,..,,,...,*,,,,,,,,,,,,,,**///**///((///****(***,,,,...,,,,,,,,,,,***,,,,,,*/((%((((####(%%%%%%%%&###%%%%###(###((##%%#(/((/((###%###%&###/(///    
+      ...,..........,/*&&***,***,,,,,,,,,,,.,..,,,(,,,,,,,.,,,,,,,,,,,,,,,,,,/****//////////*******,,,,,,,,,,,,,,,,,,***,,,,,,,,,**//((((#%##%##%##%%####%&%#(###%(//((/#(#((/#&*%/##(((####((##((//   
+     %.............,*,*****,,,*,,.,,..*...,,,..,,*,,,,,,,,..,,,,,,*****,,***///*/*****//(((//*/(/*/,,,*,,,,,,,,,,,,,,,*,*,,,,,....,,///(##%%%%%%%####%(##%((##(###/(((#(##*//**/(//,%((((((##%(((/##/   
+     ...............,,,,,***,,,....,,..,,..,,,,,,,,,,,,,*,..,*,,,,*/***,**/////(///****,,**//((///*,,,,,,.,,,,,,,*,,,,,,,,,,....,,*/(###%&&%%%#%%%%#(*/((((((//((#%/*,*,,*,,*,(//((/(##((((/,,(///  
+    ................,....*,*,,.,,,,,.,,,,,*/*,,.,*,*,,,,*,,,*,,,,,,,,,,,,**//////(//**,***,*/////*,,,,,,.,,,,,,,,,,,**,,,,.,,,,,,,.,**////%(#%%###%####(#/(#((/(((//(*******,,,,.,,.,*//(/%(//((*,,,,*. 
+    .......................,***,,.,,.,,,*,*****,*,***,,,,**,**,,,,*,*,,,,,,,,*/(/////(//*/(/((%(/**.,,.......,,,,,,,*,,,,,,,,,,,,,,.,,*////(((#(//#(%#%#(#/(#((((((((****,*,,,,,.....*.,(/(##//(//,.,,,/ 
+    ,,...............*....,,,*,,,*...,..,,,*/*,*,**,,,/(,*,,,**,,,,,*,,,,,,,,,,,***/(#((%##(((///*,,..........,.,,,**,,,,,,,.,,,,,,,,.(,*/(#((((/**//%&#(/#(##(####(/***,,,,,,,,,....,..*#(##(/,/**...,* 
+    /,*...................,,,,****..,,,.***,,*,,**,***,,,**,*,*,**,,**,,,,*,,,,,,,**(/((#(##(//*/**,,......,,,,,,,,,*,,,,,,,,,,,,,,.,.,**/(((/////**/((#((####%#&#%#((/*,,,,,,,,,.....,,,(((##(#/,//,*,,/*
+    */,...................,,,,****.*,,..,,*******/**/****,**,********,,,*/*,,.,,/*,/((/((///(((////*,*,,,..,/....,,,,,,,,,,,*,,,,,,....,/*//////,,******,*/(((%&&&%&&%%#/*,,,,,..,....,*((/((###/(#(//(**/*
+    (*,...............,,.,,,*****,,..*/***(##///////**/******/*/*//*********,,/(/((////(//(/**//*,**,/*,****,/**,,,,,,,*,,,,,,,...,,,.,...,,/*******,,,,,//(((((((#%%%#/(/*,,*.,,..,....,/##(*((((((((/*,*/*
+    (*,..................,,,******,*,*,,***/(*(/*/(((///(*//*//*/((/**/////////////////****,********,,,,**,,,*,/(/(*.,,(,.,,......,,,,.....,,***,,,,,,,,*//(%((/(#&%#%#/**,,,,,,,,.,.,*(#/((#,(((((/(//***
+    (*#*...................*,*//**/**.,,,*///((((//*(((#((((///*///(///*,*,,,,***/////**,,,,,,,,,,,,,*/((/****,,,/(((#/***/*..,/*..........,....,,..,,...*,/(/((/(##%%#%#(///,,.,,,,,///#((/,*(,/(#/#(/(*,*
+    /(/*,..........,....,.,**///(//**,,,,**((/(#(((///###((((///////*//*****,,,,,,,****,,,,*,,,,,,,,,,/#((/*,,**/**,#**/*,...............,.,,................,,*/((//#####(###//*(/#/(////#,.(,,//(((#//..,
+    *((*................,***//#(////**,,****//(//(((#%%%&%(///(/////*/******,,,,,...,,*,,,,,,,,..,,.....,**,,//*///#///,,............,,*,,................*...,*,**/(######(##(((((((//*,/,.**/,#//(/#/,...
+    ,(#,.................,,***/(((/**,,,,*,,**/(((((#%%%%%%(///////////***,,,,,,.,..,,***,,,,..,,.....,**,,//*///#///,,............,,*,,................*...,,//*/*****/(((((((/(((((((/(//*//,#/*/(/%(,,.,
+    #*,.................,*,,/*////,,**//**#***//(//*/////*//////**//****,,,,**,,***///////***,,,,,.,.,****,((/(/**//*,,.....................,.............*/*,*****,*,***/((%####(((*(/((,(////,**//**....
+    /**..................,**,,,/,,,******/*//*///*////**/*********/*//,,**,,,****//////((#///**//******,**((((((/(/**,.........,,,,......................,,..,**//*//,,*,..*/(/((//(**/#*,/*((////*//*,.,*
+    *,(*,.................***,#/,,,*/**,*,****//////*/****//,,.,,,***,.....,,***((#((///(////((/**//,/((//((#((#(//***,...,*,.,,,.........,,......,.*,,,,,.,,,******,*,........,*.,,,***,,*/*/(/(**//,,,*,
+    ../*/.................*..,,,,,,,,,**,,***///*//(//*****,**,,,,,**,,..,,,,****/////*/(*//(####((#(##((###((#((////,*,.,,,,,..........,..*,,,.,,*,,,,.,,,,*****,,,,..,..........,,/***/,//(/////((*,,,* 
+    ,/%**/.....,......,........,,,,.*,,**/**,****/((///*//**,**,**,,,,,,,,,*,.***/,**//(((/(#((((((##%%%%%#%((/(/*/***,,,...........,....,,*,,*,,,..,,.,,,,*,**..,,,............,,,,,**/*/(///(/////*..* 
+    */(**,......,........,......,,*,.,,,,*/******///*/((///***,,****,*****,**,,*/,******//(((((####(#%%&%%%%#(((///(/*//,,.,.,,,,,......,,,,,(#((%,,/,*,,,*/,,.,,.,,.........,,,,,,,,*(//(//*////////**  
+    //#//,.....*.,(............,,,,,*,,**,***********/((///*****,***,*/*////**(*,,,**/**//((###%%%###%%%%%####(#(////*,**,,,,,,,,,,,,,,*.,*/&%####(/((/*((*,,,,,.,,....,..,,,,**,,**//*//**////////*  
+    ,/%/(*,.,............,,....,,,,,,,,,***,*,,,,,****///(/*//*******///(((((///*****//(((###(%(#%%###%%#######&#((((((##(/*/*,*,,*,,,,,*//(#((((%(#%(//***,,,,,,.,...,...,,*,/(###((///(///(/##/(,   
+     (*/#(,...,...,...,...,,...,,*,.,,*(,,*,,*,,,/(*,////////***(//(((((//(##((((((/(//(((((#####&###################%#%###(#(#(*/*,,,,,*((#((((((((/(#/(((*,,,,.,,.....,...,,*,/(###((///(///(/##/(,   
+      (/(%(*,,**..,..,...,....,.,*,,/////*,,*,,,,/***/(/*//**/,**(##%#(#((###((#((((((((((###%######(####%##%###%##%%%%%%%%%%(((****/*(/((((#((#(((((///**//,/.,,,,,..,..*****/#(%%#/(##/(/////////    
+       ///(//***#,,,.,.,,*,,.....,.,****//,...,,***,*///////(***,**/((###((((###%###((#(((((#####%############%%%##%&&%%&%#((//***/*****/(((((((###%#((((//*,/*,..,.....,,****/((##(/(////(///(///*     
+        (((((//**,,,***/**/*,.,..,,,,,**/,,,,.,,,,,,*,**/((/*,*,.,**///((((#########(((#########%%%%%%%#%%#%%####%%%&&%%%%#((((//////*//((((((((##%&##%%/*(//((/,,,*,,,,,**,****/////(//////(//(/(      
+        .##/(/(/*,*//*///////,,,......**,,....,,,,,,,*,*,*/#*/,*,,,**(//((((####(((((#(((#%(%#(##%%%&%%%###%%######%%%%%%%%####((/(((/////(//(#((#%%##(%#%#%%/*((**,,*,,,******/*/(////(////*(/////       
+          ##((//*///*//*///(/*/,,.,...,,*,*.,...,,,,,,*,*,*/#*/,*,,,**(//((((####(((((#(((#%(%#(##%%%&%%%###%%######%%%%%%%%####((/(((/////(//(#((#%%##(%#%#%%/*((**,,*,,,******/*/(////(////*(/////       
+          ##((/(/////(///(*/*,.*(,.,....,,,#/**(*,*,,******/((,,,,,**(//((((((##(((((((#(##(((%%###########%%%&&%%%&%%%%%#%%%%%%&##(##((((/*****/**/(##((/(/#((((**(#(******//****/****////(**         
+           ##((/(/////(///(*/*,.*(,.,....,,,#/**(*,*,,******/((,,,,,**(//((((((##(((((((#(##(((%%###########%%%&&%%%&%%%%%#%%%%%%&##(##((((/*****/**/(##((/(/#((((**(#(******//****/****////(**         
+            %%(#/////(##(/*//(/,./,,,/*,.,,//*(((*,,,****,,,/**,,,,,,*******(#((#((#((((##((####(((((##%#%%%##%%##%%##%%%%&&&&%%%%####/((/********//(&#%#%&#(/(#(/*///*****,**/*/**//***////**          
+             %###%(/((((/*,*.....,,,,.,***,,/((//*,,*,,,,,,,,*,*,,,***/*/#**((#%((###((((((##((%######%#####%##%%%###%%%%&&&&&%&&%%#(//************(#%%#//*#((//********##((((/**///////*//            
+              .##%(#((((/,,.........**(///***//**,,*,,****,,,***,**,,,,,,**(((#########((((##(######%%%%#%%%###%#####%#%%%%&%%&&&&&%#(/(//***/******(##(#(*((/((//***,*/(#(((//////*////*(/             
+                #(((##(*%/*,,.,.......*(///*///***#(/*,***,*,,,...,,*,,,/(##(((###%###(###&%&%%##(###(#########((###%%#%#%#%%%%%&%%%##((/(/******,**/(((((#(/**/(/**////(#((*(((//(/**///,              
+                  #((#%(#(/,,,,,......,*/**,*,,*/***/******,,,,,,,,,,,**(#(((%#####((((##%###%%(((((#######(#########%##%%%%%%%#%%%%%%/////#/(#%##(//(##(/#(((((/((((((/(%(#((((/****/                
+                   /((####///,*,,,..,.,,,,**,*,,*//*****,,,,*/,,,,,,,,**///#(&%####((((####((##(((((######%##%%#%##%#((##%%%%#%%%%%%%%%#((((###%%##(/((##(#(#%#(#%#((((#%#(##((((/****                  
+                     /(((#&%#(/*/,,,,,,,/****(/,,,*******,,,,,,,,,,,,,,***/*/(###%##(#####%####((#####%#%%%%%%%%#(##########%%%%&&%%#######%####((((#%###%%#%##%#%%%###(%%#((%((////                    
+                       (((((%###(/***(**//(//*(,**,,/***,,,,,,,,,,,,,,*,***/###%##((##%%%%%%######%%%%%%%%%%%%######(###%##%&&&%%%#%%#%%%&%#%#(####(##%%%%%%%%%%%%%%#%%%#(#&((///*.                     
+                         #/(((/((*(/////(//(/,,*/(///*((/*/*****//**//*(/**(###%%#%%%%%#&%%%%###%%%%%&%%%#%(#((#####%###%%%%%%%##%#%%%%%########(###%#%#(##%%%&&%%%%%%###%(////*                        
+                           ///(((/**//((/((#*/,,***//*/#%%#(#/(((****######%%%%%%%&%%%&%%&&%%%##%%%%%%%%###(#(#(###%&&%###(#%%#######%%##(####%###########%%%&%%%%&&#(((///***                          
+                              //(/((***(((//((***((***/(#%%&&#%%#%#(%#%%%&%&&&&&%%%%&&%%%&%&%%%%%##%##(########(#%&%%%%######%%%###(##(##(######%#%####%%%%%%%%%##///////***                            
+                                /,/%(((/(/##((((%#(*(###((%##%%%%%#%%#%&%%&&&&&@@&&&&%%%%%%%%%%%%%#%##%##%%##%%%&%%####%#%%#%%##(#(####((###############%#%%####(///****                               
+                                   ////(((#(#((#(((/##%(%###%##%%##%%%%&&&&&&%%&&&&&%%%&%%%%%&&&#%##%%%%###%%%%##%####(###%###(####(#(##########(##(#(#%#%((/(////***,                                  
+                                      /*////((###(##(##((((##%%%%%&%&%&&&&&&&&&&&&&&&%&%&%%%%%%%###%&&%#%%######%%########(###(#%%%##(####(#####%%####%((((/////***.                                    
+                                         //*//(//((#((#((#%#%%#%%%%%&&&%%%%%%&&&&&&&&&&&&&&&&&&%%%%%##(####((##%######%#%%%%%##(((####%%###%%%#%####(#(((/(///*,                                        
+                                            ./////(((##(#((####%%%%%%%%#&&&&&%%%%&&&&&&%%%&%%%%%%%&%%##%%%%%%%%#((####%##(#(###((##%#######%%%####((////((/**                                           
+                                                ***#/(((#((##((%#%%%%%&%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%####((##((((((####((#####%%%%%##%%%%%##(#(///#((/*,,                                               
+                                                    ,**(((/((((###%#%%%%%&&&&%%&&&%&&&&&&%%&%%%%#%%%%#######((#(((((#(#(((####(######(##(((////////*,                                                   
+                                                         *(((#(((###%%#%%%%%&&%%%%%%%%%%%%#&&%%%%%###%%%#((###(((####((#(###(#######(((((((/////                                                        
+                                                               ((/(####(#%%%%%%#%%%%%%%%%%##%%%%%#%######(########%##((((((((###//(((/////                                                              
+                                                                     .(((##(##%%%#%%%%%%%%%%%#%%##%%%%#((####(((((((((((/((((((////,                                                                    
+                                                                              */(%%%%%%%%%##%##########(/(((/(((((////////.                                                                             
+"#;
+
+pub fn moon_lit_color(truecolor: bool) -> Color {
+    if truecolor {
+        Color::Rgb(232, 208, 88) // warm moonlight
+    } else {
+        // 256-color gold. Important for terminals that don't parse 24-bit SGR (38;2;...),
+        // which can otherwise degrade to green (color index 2).
+        Color::Indexed(214)
+    }
+}
+
+/// `--warm` lit color: cool white for a thin crescent through to warm cream at full.
+fn warm_lit_color(illumination: f64, truecolor: bool) -> Color {
+    let t = (illumination / 100.0).clamp(0.0, 1.0);
+    if truecolor {
+        let (cool, warm) = ((214.0, 226.0, 255.0), (255.0, 234.0, 186.0));
+        let lerp = |a: f64, b: f64| (a + (b - a) * t).round() as u8;
+        Color::Rgb(lerp(cool.0, warm.0), lerp(cool.1, warm.1), lerp(cool.2, warm.2))
+    } else {
+        // Pale blue-white, neutral white, cream.
+        match t {
+            t if t < 1.0 / 3.0 => Color::Indexed(189),
+            t if t < 2.0 / 3.0 => Color::Indexed(230),
+            _ => Color::Indexed(223),
+        }
+    }
+}
+
+// `--limb-darkening`: brightness left at the very edge of the disc (1.0 at the center).
+const LIMB_BRIGHTNESS: f64 = 0.55;
+
+/// `--limb-darkening`: `color` dimmed toward the limb, where `z` (depth toward the viewer) falls
+/// from 1 at the center to 0 at the edge. Palette colours can't be dimmed smoothly and are
+/// returned unchanged.
+fn limb_darkened(color: Color, z: f64) -> Color {
+    match color {
+        Color::Rgb(r, g, b) => {
+            let k = LIMB_BRIGHTNESS + (1.0 - LIMB_BRIGHTNESS) * z.clamp(0.0, 1.0);
+            let dim = |c: u8| (c as f64 * k).round() as u8;
+            Color::Rgb(dim(r), dim(g), dim(b))
+        }
+        other => other,
+    }
+}
+
+/// Earthshine shade for a night-side point; `depth` is 0 at the terminator and 1 opposite the Sun.
+/// `--terminator`: colour of the day/night boundary line.
+fn terminator_color(truecolor: bool) -> Color {
+    if truecolor { Color::Rgb(110, 140, 210) } else { Color::Blue }
+}
+
+/// Earthshine shade for a night-side point; `depth` is 0 at the terminator and 1 opposite the Sun.
+fn earthshine_color(depth: f64, truecolor: bool) -> Color {
+    let t = depth.clamp(0.0, 1.0);
+    if truecolor {
+        let v = |near: f64, far: f64| (near + (far - near) * t).round() as u8;
+        Color::Rgb(v(72.0, 30.0), v(72.0, 30.0), v(80.0, 36.0))
+    } else if t < 0.5 {
+        Color::Indexed(238)
+    } else {
+        Color::Indexed(235)
+    }
+}
+
+fn moon_shadow_color(truecolor: bool) -> Color {
+    if truecolor {
+        Color::Rgb(92, 92, 98) // soft graphite
+    } else {
+        Color::Indexed(242)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English = 0,
+    Chinese = 1,
+    French = 2,
+    Japanese = 3,
+    Spanish = 4,
+}
+
+impl Language {
+    pub fn next(&self) -> Self {
+        match self {
+            Language::English => Language::Chinese,
+            Language::Chinese => Language::French,
+            Language::French => Language::Japanese,
+            Language::Japanese => Language::Spanish,
+            Language::Spanish => Language::English,
+        }
+    }
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Chinese => "中文",
+            Language::French => "Français",
+            Language::Japanese => "日本語",
+            Language::Spanish => "Español",
+        }
+    }
+}
+
+impl std::str::FromStr for Language {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "en" | "english" => Ok(Language::English),
+            "zh" | "chinese" => Ok(Language::Chinese),
+            "fr" | "french" => Ok(Language::French),
+            "ja" | "japanese" => Ok(Language::Japanese),
+            "es" | "spanish" => Ok(Language::Spanish),
+            _ => Err("language must be one of: en, zh, fr, ja, es".to_string()),
+        }
+    }
+}
+
+pub struct Feature {
+    pub names: [&'static str; 5],
+    pub lat: f64,
+    pub lon: f64,
+}
+
+// Explains the red `x` feature markers, indexed like `Feature::names`.
+pub const FEATURE_LEGEND: [&str; 5] = [
+    "lunar feature",
+    "月面地貌",
+    "formation lunaire",
+    "月面の地形",
+    "accidente lunar",
+];
+
+// `--lod auto`: discs narrower than this many columns drop labels and gradients...
+const LOD_LOW_WIDTH: f64 = 24.0;
+
+// ...and discs at least this wide get the earthshine gradient and the extra labels.
+const LOD_HIGH_WIDTH: f64 = 100.0;
+
+// Ranked by prominence, the big maria first: `[`/`]` label a leading slice of this list.
+pub const LUNAR_FEATURES: &[Feature] = &[
+    Feature { names: ["Oceanus Procellarum", "风暴洋", "Océan des Tempêtes", "嵐の大洋", "Océano de las Tormentas"], lat: 18.4, lon: -57.4 },
+    Feature { names: ["Mare Imbrium", "雨海", "Mer des Pluies", "雨の海", "Mar de las Lluvias"], lat: 32.8, lon: -25.6 },
+    Feature { names: ["Mare Serenitatis", "澄海", "Mer de la Sérénité", "晴れの海", "Mar de la Serenidad"], lat: 20.0, lon: 13.5 },
+    Feature { names: ["Mare Tranquillitatis", "静海", "Mer de la Tranquillité", "静かの海", "Mar de la Tranquilidad"], lat: 3.5, lon: 22.4 },
+    Feature { names: ["Mare Crisium", "危海", "Mer des Crises", "危難の海", "Mar de las Crisis"], lat: 17.0, lon: 58.5 },
+    Feature { names: ["Tycho", "第谷", "Tycho", "ティコ", "Tycho"], lat: -43.3, lon: -11.2 },
+    Feature { names: ["Copernicus", "哥白尼", "Copernic", "コペルニクス", "Copérnico"], lat: 9.6, lon: -20.1 },
+    Feature { names: ["Aristarchus", "阿里斯塔克斯", "Aristarque", "アリスタルコス", "Aristarco"], lat: 23.7, lon: -47.4 },
+    Feature { names: ["Plato", "柏拉图", "Platon", "プラトン", "Platón"], lat: 51.6, lon: -9.3 },
+    Feature { names: ["Kepler", "开普勒", "Kepler", "ケプラー", "Kepler"], lat: 8.1, lon: -38.0 },
+];
+
+// Smaller features labelled only at `Lod::High`, where there's room. Not selectable with Tab.
+const DETAIL_FEATURES: &[Feature] = &[
+    Feature { names: ["Mare Nectaris", "酒海", "Mer du Nectar", "神酒の海", "Mar del Néctar"], lat: -15.2, lon: 35.5 },
+    Feature { names: ["Mare Fecunditatis", "丰富海", "Mer de la Fécondité", "豊かの海", "Mar de la Fecundidad"], lat: -7.8, lon: 53.7 },
+    Feature { names: ["Mare Humorum", "湿海", "Mer des Humeurs", "湿りの海", "Mar de los Humores"], lat: -24.4, lon: -38.6 },
+    Feature { names: ["Clavius", "克拉维乌斯", "Clavius", "クラビウス", "Clavius"], lat: -58.4, lon: -14.4 },
+    Feature { names: ["Grimaldi", "格里马尔迪", "Grimaldi", "グリマルディ", "Grimaldi"], lat: -5.2, lon: -68.6 },
+];
+
+/// Where a feature's marker goes in the disc box (0..1 each way, north up), or `None`
+/// when `rotation` has turned it onto the far side.
+fn feature_projection(feature: &Feature, rotation: f64) -> Option<(f64, f64)> {
+    // Orthographic projection
+    let rad_lat = feature.lat.to_radians();
+    let rad_lon = (feature.lon + rotation).to_radians();
+    if rad_lat.cos() * rad_lon.cos() < 0.0 {
+        return None;
+    }
+
+    let u = rad_lat.cos() * rad_lon.sin();
+    let v = rad_lat.sin();
+
+    // Project to screen UV (0..1)
+    // In math, v is Up. In screen, ny goes Down.
+    // Center is 0.5, 0.5
+    // Scale 0.95 to pull labels slightly inwards.
+    // Offset (-0.10, -0.10) to shift labels Down-Left (fixing Top-Right bias).
+    let scale = 0.95;
+    let u_adj = u * scale - 0.10;
+    let v_adj = v * scale - 0.10;
+    Some((0.5 + u_adj / 2.0, 0.5 - v_adj / 2.0))
+}
+
+/// Everything about how the moon is drawn, apart from the moon itself (`MoonStatus`).
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    /// Mark and name the `LUNAR_FEATURES` on the disc.
+    pub show_labels: bool,
+    /// Language of the feature names.
+    pub language: Language,
+    pub shadow: ShadowMode,
+    /// 24-bit colour; otherwise the 256-colour palette.
+    pub truecolor: bool,
+    /// Shift the art sampling with libration so the face rocks over the month.
+    pub libration: bool,
+    /// Spin about the vertical axis, in degrees (positive turns the east limb away).
+    pub rotation: f64,
+    /// The terminal's cell shape, so the disc comes out round.
+    pub cell_ratio: CellRatio,
+    /// Mark the disc center (+) and the subsolar point (*).
+    pub show_markers: bool,
+    /// Index into `LUNAR_FEATURES` of the feature to highlight among the labels.
+    pub selected_feature: Option<usize>,
+    /// Label only this many of `LUNAR_FEATURES` (the most prominent); `None` labels them all.
+    pub label_limit: Option<usize>,
+    /// Bilinear art sampling instead of nearest neighbour (softer when scaled down).
+    pub smooth: bool,
+    /// Full-moon celebration: draw the lit side in bold white.
+    pub flash: bool,
+    /// Tint the lit side by illumination (cool crescent, warm full) instead of the fixed gold.
+    pub warm: bool,
+    /// Southern-hemisphere view: the whole picture turned 180°.
+    pub southern: bool,
+    /// `--max-moon-size`: the widest the disc may be drawn, in columns.
+    pub max_width: Option<u16>,
+    /// `--dumb-terminal`: shade with `BRIGHTNESS_RAMP` by lighting alone, no art and no colour.
+    pub dumb: bool,
+    /// `--light-bg`: inverted video for white terminals and paper: dark lit glyphs, shadow on a light background.
+    pub light_bg: bool,
+    /// `--lod`: detail level, resolved against the disc width when drawing.
+    pub lod: Lod,
+    /// `--no-mask`: draw the whole art rectangle, not just the inscribed disc.
+    pub no_mask: bool,
+    /// `--density-by-illum`: dense glyphs for the lit side, sparse ones for the shadow.
+    pub density_by_illum: bool,
+    /// `--limb-darkening`: lit side dimmer toward the edge (truecolor only).
+    pub limb_darkening: bool,
+    /// `--terminator`: highlight the cells the day/night boundary runs through.
+    pub terminator: bool,
+    /// `--breathe`: fraction the disc is shrunk by this frame (0.0 draws it full size).
+    pub shrink: f64,
+    /// `--stars`: a fixed starfield behind the disc, dimmed by the moon's illumination.
+    pub stars: bool,
+}
+
+impl Default for RenderOptions {
+    /// The plain moon: gold on graphite in the 256-colour palette, no labels, nothing extra.
+    fn default() -> Self {
+        RenderOptions {
+            show_labels: false,
+            language: Language::English,
+            shadow: ShadowMode::Full,
+            truecolor: false,
+            libration: false,
+            rotation: 0.0,
+            cell_ratio: CellRatio::default(),
+            show_markers: false,
+            selected_feature: None,
+            label_limit: None,
+            smooth: false,
+            flash: false,
+            warm: false,
+            southern: false,
+            max_width: None,
+            dumb: false,
+            light_bg: false,
+            lod: Lod::Auto,
+            no_mask: false,
+            density_by_illum: false,
+            limb_darkening: false,
+            terminator: false,
+            shrink: 0.0,
+            stars: false,
+        }
+    }
+}
+
+/// Ratatui widget wrapper around `render_moon`.
+#[derive(Debug, Clone, Copy)]
+pub struct MoonWidget {
+    pub status: MoonStatus,
+    pub options: RenderOptions,
+}
+
+// The art's glyphs from sparse to dense, used by `--smooth` to blend between neighbours.
+const GLYPH_RAMP: [char; 10] = [' ', '.', ',', '*', '/', '(', '%', '#', '&', '@'];
+
+/// Brightness of an art glyph in 0..=1 (position on `GLYPH_RAMP`; unknown glyphs sit mid-ramp).
+fn glyph_density(ch: char) -> f64 {
+    match GLYPH_RAMP.iter().position(|&g| g == ch) {
+        Some(i) => i as f64 / (GLYPH_RAMP.len() - 1) as f64,
+        None => 0.5,
+    }
+}
+
+/// The `GLYPH_RAMP` glyph closest to `density`.
+fn density_glyph(density: f64) -> char {
+    let i = (density.clamp(0.0, 1.0) * (GLYPH_RAMP.len() - 1) as f64).round() as usize;
+    GLYPH_RAMP[i]
+}
+
+// `--density-by-illum`: lit glyphs are squeezed into the top of the ramp from here up...
+const LIT_DENSITY_FLOOR: f64 = 0.45;
+
+// ...and shadow glyphs into the bottom, up to here.
+const SHADOW_DENSITY_CEILING: f64 = 0.25;
+
+/// `--density-by-illum`: `ch` moved to the dense end of `GLYPH_RAMP` when lit and to the sparse
+/// end when in shadow, keeping its relative texture, so the phase reads without colour.
+fn density_by_light(ch: char, lit: bool) -> char {
+    let density = glyph_density(ch);
+    if lit {
+        density_glyph(LIT_DENSITY_FLOOR + density * (1.0 - LIT_DENSITY_FLOOR))
+    } else {
+        density_glyph(density * SHADOW_DENSITY_CEILING)
+    }
+}
+
+/// Terminal cell geometry as `width:height` (typically about 1:2).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellRatio {
+    pub w: f64,
+    pub h: f64,
+}
+
+impl CellRatio {
+    /// Columns per row that make a visually round disc.
+    pub fn aspect(&self) -> f64 {
+        self.h / self.w
+    }
+}
+
+impl Default for CellRatio {
+    fn default() -> Self {
+        CellRatio { w: 1.0, h: 2.0 }
+    }
+}
+
+impl std::str::FromStr for CellRatio {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || "cell ratio must look like W:H with positive numbers, e.g. 1:2".to_string();
+        let (w, h) = s.split_once(':').ok_or_else(err)?;
+        let w: f64 = w.trim().parse().map_err(|_| err())?;
+        let h: f64 = h.trim().parse().map_err(|_| err())?;
+        if !(w.is_finite() && h.is_finite() && w > 0.0 && h > 0.0) {
+            return Err(err());
+        }
+        Ok(CellRatio { w, h })
+    }
+}
+
+// Drawn where rotation exposes the far side, which the art doesn't cover.
+const FAR_SIDE_CHAR: char = '.';
+
+// `--dumb-terminal` shading, darkest to brightest; pure ASCII for any console.
+const BRIGHTNESS_RAMP: &[u8] = b" .:-=+*#%@";
+
+/// Ramp character for a lit point; `intensity` is the cosine of the sun angle (0..=1).
+/// Anything lit gets at least `.`, so the terminator stays visible.
+fn ramp_glyph(intensity: f64) -> char {
+    let top = BRIGHTNESS_RAMP.len() - 1;
+    let index = (intensity.clamp(0.0, 1.0) * top as f64).round() as usize;
+    BRIGHTNESS_RAMP[index.max(1)] as char
+}
+
+// Traces the dark limb in `ShadowMode::Outline`.
+const OUTLINE_CHAR: char = '.';
+
+/// Bounding box of the non-blank characters in the source art.
+#[derive(Debug, Clone, Copy)]
+pub struct ArtCrop {
+    pub min_x: usize,
+    pub min_y: usize,
+    pub crop_w: f64,
+    pub crop_h: f64,
+}
+
+pub fn art_crop(source_lines: &[Vec<char>]) -> Option<ArtCrop> {
+    // Calculate bounding box of non-whitespace characters
+    let mut min_x = usize::MAX;
+    let mut max_x = 0;
+    let mut min_y = usize::MAX;
+    let mut max_y = 0;
+
+    for (y, line) in source_lines.iter().enumerate() {
+        for (x, &ch) in line.iter().enumerate() {
+            if ch != ' ' {
+                min_x = min_x.min(x);
+                max_x = max_x.max(x);
+                min_y = min_y.min(y);
+                max_y = max_y.max(y);
+            }
+        }
+    }
+
+    if min_x > max_x || min_y > max_y {
+        return None;
+    }
+
+    Some(ArtCrop {
+        min_x,
+        min_y,
+        crop_w: (max_x - min_x + 1) as f64,
+        crop_h: (max_y - min_y + 1) as f64,
+    })
+}
+
+/// Where the disc is drawn inside a render area (cell coordinates, fractional).
+#[derive(Debug, Clone, Copy)]
+pub struct DiscBox {
+    pub start_x: f64,
+    pub start_y: f64,
+    pub draw_w: f64,
+    pub draw_h: f64,
+}
+
+pub fn disc_box(area: Rect, cell_ratio: CellRatio, max_width: Option<u16>) -> Option<DiscBox> {
+    // Cells per row needed for the disc to look round: a 1:2 cell needs twice as
+    // many columns as rows. The art is stretched to fill this box.
+    let disc_aspect = cell_ratio.aspect();
+
+    let avail_w = area.width as f64;
+    let avail_h = area.height as f64;
+
+    // Calculate drawing dimensions to fit 'area' while keeping the disc round
+    let (draw_w, draw_h) = if avail_w / avail_h < disc_aspect {
+        // Limited by width
+        (avail_w, avail_w / disc_aspect)
+    } else {
+        // Limited by height
+        (avail_h * disc_aspect, avail_h)
+    };
+    // `--max-moon-size`: keep the disc from filling a huge pane.
+    let (draw_w, draw_h) = match max_width {
+        Some(max) if draw_w > max as f64 => (max as f64, max as f64 / disc_aspect),
+        _ => (draw_w, draw_h),
+    };
+
+    // Too small to hold even one cell: the normalized coordinates would blow up.
+    if draw_w < 1.0 || draw_h < 1.0 {
+        return None;
+    }
+
+    // Center the drawing in the area
+    Some(DiscBox {
+        start_x: area.left() as f64 + (avail_w - draw_w) / 2.0,
+        start_y: area.top() as f64 + (avail_h - draw_h) / 2.0,
+        draw_w,
+        draw_h,
+    })
+}
+
+// Tab stops used when expanding tabs in art.
+const ART_TAB_WIDTH: usize = 8;
+
+pub fn source_art_lines() -> Vec<Vec<char>> {
+    parse_art(MOON_ART_RAW)
+}
+
+/// Art text as a rectangular grid: tabs expanded to `ART_TAB_WIDTH` stops, other control
+/// characters blanked, empty lines dropped, and every row padded with spaces to the widest.
+fn parse_art(raw: &str) -> Vec<Vec<char>> {
+    let mut rows: Vec<Vec<char>> = raw
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            let mut row = Vec::with_capacity(line.len());
+            for ch in line.chars() {
+                match ch {
+                    '\t' => row.resize((row.len() / ART_TAB_WIDTH + 1) * ART_TAB_WIDTH, ' '),
+                    c if c.is_control() => row.push(' '),
+                    c => row.push(c),
+                }
+            }
+            row
+        })
+        .collect();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    for row in &mut rows {
+        row.resize(width, ' ');
+    }
+    rows
+}
+
+/// Draw the moon for `status` into `area` of `buf`.
+///
+/// This is the whole renderer: embedders with their own ratatui layout can call it directly
+/// instead of going through `MoonWidget`.
+pub fn render_moon(status: &MoonStatus, area: Rect, buf: &mut Buffer, opts: RenderOptions) {
+    // Pre-process source art into a grid for easy sampling
+    render_art(status, area, buf, opts, &source_art_lines());
+}
+
+/// `render_moon` with the art supplied as a `parse_art` grid.
+fn render_art(status: &MoonStatus, area: Rect, buf: &mut Buffer, opts: RenderOptions, source_lines: &[Vec<char>]) {
+    // Narrow layouts (e.g. poem panel open) can hand us an empty pane.
+    if area.width == 0 || area.height == 0 {
+        return;
+    }
+
+    if source_lines.is_empty() { return; }
+
+    let Some(ArtCrop { min_x, min_y, crop_w, crop_h }) = art_crop(source_lines) else { return };
+    let Some(DiscBox { start_x, start_y, draw_w, draw_h }) = disc_box(area, opts.cell_ratio, opts.max_width) else { return };
+    // `--breathe`: shrink the box about its center; both sides scale alike, so the disc stays round.
+    let (start_x, start_y) = (start_x + draw_w * opts.shrink / 2.0, start_y + draw_h * opts.shrink / 2.0);
+    let (draw_w, draw_h) = (draw_w * (1.0 - opts.shrink), draw_h * (1.0 - opts.shrink));
+
+    if opts.stars {
+        render_stars(area, buf, opts, status.illumination, |x, y| {
+            let (dx, dy) = ((x as f64 - start_x) / draw_w - 0.5, (y as f64 - start_y) / draw_h - 0.5);
+            dx * dx + dy * dy <= 0.25
+        });
+    }
+
+    let phase = status.phase_fraction;
+
+    // Level of detail: small discs get binary lighting, big ones the earthshine gradient.
+    let lod = opts.lod.at_width(draw_w);
+    let shadow = match (lod, opts.shadow) {
+        (Lod::Low, ShadowMode::Earthshine) => ShadowMode::Full,
+        (Lod::High, ShadowMode::Full) => ShadowMode::Earthshine,
+        (_, shadow) => shadow,
+    };
+
+    // A libration of l degrees moves the disc center by sin(l) radii; in normalized
+    // box units that's 0.5 * sin(l), i.e. at most ~6% of the diameter.
+    let (lib_dx, lib_dy) = if opts.libration {
+        (
+            0.5 * status.libration_lon.to_radians().sin(),
+            -0.5 * status.libration_lat.to_radians().sin(),
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    let (rot_sin, rot_cos) = opts.rotation.to_radians().sin_cos();
+
+    // The southern view turns the picture 180° about the disc center. The map is its own
+    // inverse, so it takes screen positions to moon positions and back alike.
+    let view = |nx: f64, ny: f64| if opts.southern { (1.0 - nx, 1.0 - ny) } else { (nx, ny) };
+
+    // Iterate over the target terminal area
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            // Normalized coordinates relative to the drawn moon box (0.0 to 1.0)
+            let ny = (y as f64 - start_y) / draw_h;
+            let nx = (x as f64 - start_x) / draw_w;
+
+            // Check if we are inside the moon drawing box
+            if !(0.0..1.0).contains(&ny) || !(0.0..1.0).contains(&nx) {
+                continue;
+            }
+            let (nx, ny) = view(nx, ny);
+
+            // Circular Mask & Spherical Projection Logic
+            let dx = nx - 0.5;
+            let dy = ny - 0.5;
+            let dist_sq = dx * dx + dy * dy;
+
+            // Radius is 0.5. Radius^2 is 0.25. Unmasked, the corners outside it are drawn too,
+            // lit as if on the limb (z = 0 below).
+            let in_disc = dist_sq <= 0.25;
+            if !in_disc && !opts.no_mask {
+                continue;
+            }
+
+            // Map to -1..1 range for sphere math
+            let u = dx * 2.0;
+            let v = dy * 2.0;
+            
+            // z is the depth of the sphere at this pixel (towards viewer)
+            // x^2 + y^2 + z^2 = 1
+            // (clamped: rounding at the limb can push the radicand a hair below zero)
+            let z = (1.0 - u * u - v * v).max(0.0).sqrt();
+
+            // Spin the globe about its vertical axis to find which surface point faces
+            // us here. The art only covers the near side (art_z >= 0).
+            let art_u = u * rot_cos - z * rot_sin;
+            let art_z = u * rot_sin + z * rot_cos;
+
+            // Sample from Source Art mapped to CROP box
+            let ch = if art_z < 0.0 {
+                FAR_SIDE_CHAR
+            } else {
+                let sy = (ny + lib_dy).clamp(0.0, 0.999_999);
+                let sx = (0.5 + art_u / 2.0 + lib_dx).clamp(0.0, 0.999_999);
+                let fy = min_y as f64 + sy * crop_h;
+                let fx = min_x as f64 + sx * crop_w;
+                let art_at = |ax: usize, ay: usize| {
+                    source_lines
+                        .get(ay)
+                        .and_then(|row| row.get(ax))
+                        .copied()
+                        .unwrap_or(' ')
+                };
+
+                if opts.smooth {
+                    // Bilinear: blend the four neighbours' densities, then pick the closest glyph.
+                    // Offsetting by half a cell puts the sample between character centers.
+                    let (gx, gy) = ((fx - 0.5).max(0.0), (fy - 0.5).max(0.0));
+                    let (x0, y0) = (gx.floor() as usize, gy.floor() as usize);
+                    let (tx, ty) = (gx.fract(), gy.fract());
+                    let d = |ax, ay| glyph_density(art_at(ax, ay));
+                    let top = d(x0, y0) * (1.0 - tx) + d(x0 + 1, y0) * tx;
+                    let bottom = d(x0, y0 + 1) * (1.0 - tx) + d(x0 + 1, y0 + 1) * tx;
+                    density_glyph(top * (1.0 - ty) + bottom * ty)
+                } else {
+                    // Nearest neighbour: the crisp classic look.
+                    let src_y = fy.floor() as usize;
+                    if src_y >= source_lines.len() { continue; }
+                    art_at(fx.floor() as usize, src_y)
+                }
+            };
+
+            // Sun vector calculation
+            // Angle 0 = New Moon (Sun behind Moon, Vector 0,0,-1)
+            // Angle PI = Full Moon (Sun behind Earth, Vector 0,0,1)
+            let angle = phase * 2.0 * std::f64::consts::PI;
+            let sun_x = angle.sin();
+            let sun_z = -angle.cos();
+
+            // Dot product of Surface Normal (u, v, z) and Sun Vector (sun_x, 0, sun_z)
+            // If positive, the point is illuminated.
+            let intensity = u * sun_x + z * sun_z;
+
+            let ch = if opts.density_by_illum { density_by_light(ch, intensity > 0.0) } else { ch };
+
+            if opts.dumb {
+                if intensity > 0.0 {
+                    buf.get_mut(x, y).set_char(ramp_glyph(intensity)).set_style(Style::default());
+                }
+                continue;
+            }
+
+            // The terminator runs through this cell when the light changes sign between its
+            // left and right edges, i.e. |intensity| is below the change across one cell.
+            if opts.terminator && in_disc {
+                let half_cell = 1.0 / draw_w;
+                let light_at = |u: f64| u * sun_x + (1.0 - u * u - v * v).max(0.0).sqrt() * sun_z;
+                if (light_at(u - half_cell) > 0.0) != (light_at(u + half_cell) > 0.0) {
+                    let line = Style::default().fg(terminator_color(opts.truecolor));
+                    let line = if opts.light_bg { line.bg(Color::White) } else { line };
+                    buf.get_mut(x, y).set_char(ch).set_style(line);
+                    continue;
+                }
+            }
+
+            if intensity > 0.0 {
+                // IMPORTANT: set full style to avoid attribute "leakage" (DIM/BOLD/ITALIC)
+                // when the layout changes (e.g. poem panel toggled).
+                let lit = if opts.light_bg {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else if opts.flash {
+                    Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
+                } else {
+                    let color = if opts.warm {
+                        warm_lit_color(status.illumination, opts.truecolor)
+                    } else {
+                        moon_lit_color(opts.truecolor)
+                    };
+                    Style::default().fg(if opts.limb_darkening { limb_darkened(color, z) } else { color })
+                };
+                buf.get_mut(x, y).set_char(ch).set_style(lit);
+            } else {
+                let shade = match shadow {
+                    ShadowMode::Full => moon_shadow_color(opts.truecolor),
+                    ShadowMode::Earthshine => earthshine_color(-intensity, opts.truecolor),
+                    ShadowMode::Outline => {
+                        // Only cells with a neighbour outside the disc form the ring.
+                        let (step_x, step_y) = (1.0 / draw_w, 1.0 / draw_h);
+                        let on_limb = [(step_x, 0.0), (-step_x, 0.0), (0.0, step_y), (0.0, -step_y)]
+                            .iter()
+                            .any(|(ox, oy)| (dx + ox).powi(2) + (dy + oy).powi(2) > 0.25);
+                        if on_limb && in_disc {
+                            let ring = Style::default().fg(Color::DarkGray).add_modifier(Modifier::DIM);
+                            buf.get_mut(x, y).set_char(OUTLINE_CHAR).set_style(ring);
+                        }
+                        continue;
+                    }
+                    ShadowMode::Hidden => continue,
+                };
+                let shade = if opts.light_bg {
+                    Style::default().fg(Color::Gray).bg(Color::White)
+                } else {
+                    Style::default().fg(shade)
+                };
+                buf.get_mut(x, y).set_char(ch).set_style(shade);
+            }
+        }
+    }
+
+    // Educational markers: disc center, and the subsolar point (where the Sun is
+    // overhead, i.e. the middle of the lit region). Plotted in the same view frame
+    // as the lighting; the subsolar point is hidden while it's on the far side.
+    if opts.show_markers {
+        let angle = phase * 2.0 * std::f64::consts::PI;
+        let (sun_x, sun_z) = (angle.sin(), -angle.cos());
+        let mut plot = |nx: f64, ny: f64, ch: char, color: Color| {
+            let (nx, ny) = view(nx, ny);
+            let x = (start_x + nx * draw_w) as u16;
+            let y = (start_y + ny * draw_h) as u16;
+            if x >= area.left() && x < area.right() && y >= area.top() && y < area.bottom() {
+                buf.get_mut(x, y).set_char(ch).set_style(Style::default().fg(color));
+            }
+        };
+        plot(0.5, 0.5, '+', Color::White);
+        if sun_z > 0.0 {
+            plot(0.5 + sun_x / 2.0, 0.5, '*', Color::LightYellow);
+        }
+    }
+
+    // Render Labels
+    if opts.show_labels && lod != Lod::Low {
+        let detail: &[Feature] = if lod == Lod::High { DETAIL_FEATURES } else { &[] };
+        let limit = opts.label_limit.unwrap_or(LUNAR_FEATURES.len());
+        // The smaller detail features only join in while every main one is shown.
+        let detail = if limit < LUNAR_FEATURES.len() { &[] } else { detail };
+        let features = LUNAR_FEATURES.iter().take(limit).enumerate().map(|(i, f)| (Some(i), f));
+        for (index, feature) in features.chain(detail.iter().map(|f| (None, f))) {
+            let selected = index.is_some() && opts.selected_feature == index;
+            // Rotated onto the far side: nothing to label.
+            let Some((nx, ny)) = feature_projection(feature, opts.rotation) else { continue };
+            let (nx, ny) = view(nx, ny);
+            
+            let term_x = start_x + nx * draw_w;
+            let term_y = start_y + ny * draw_h;
+            
+            let x_idx = term_x as u16;
+            let y_idx = term_y as u16;
+
+            // Simple collision check with screen bounds
+            if x_idx >= area.left() && x_idx < area.right() && y_idx >= area.top() && y_idx < area.bottom() {
+                let (marker_color, label_style) = if selected {
+                    (Color::LightYellow, Style::default().fg(Color::Black).bg(Color::LightYellow))
+                } else {
+                    (Color::Red, Style::default().fg(Color::Cyan))
+                };
+                buf.get_mut(x_idx, y_idx).set_char('x').set_fg(marker_color);
+                let name = feature.names[opts.language as usize];
+                if let Some((label_x, label)) = fit_label(x_idx, name, area) {
+                    buf.set_string(label_x, y_idx, label, label_style.add_modifier(Modifier::BOLD));
+                }
+            }
+        }
+    }
+}
+
+// `--stars`: about one cell in this many holds a star.
+const STAR_SPACING: u64 = 40;
+
+/// `--stars`: how bright the star at cell (`x`, `y`) is, 0..1, or `None` for an empty cell.
+/// Positions come from a hash of the cell, so the field stays put from frame to frame.
+fn star_at(x: u16, y: u16) -> Option<f64> {
+    let mut h = (u64::from(x) << 32 | u64::from(y)).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    h ^= h >> 29;
+    h = h.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h ^= h >> 32;
+    (h % STAR_SPACING == 0).then(|| ((h >> 8) % 1000) as f64 / 1000.0)
+}
+
+/// `--stars`: the starfield over `area`, except where `covered` (the disc). Each star's brightness
+/// is scaled by `1 - illumination / 100`: the full moon washes them out, the new moon leaves a
+/// dark sky full of them.
+fn render_stars(area: Rect, buf: &mut Buffer, opts: RenderOptions, illumination: f64, covered: impl Fn(u16, u16) -> bool) {
+    let sky = 1.0 - (illumination / 100.0).clamp(0.0, 1.0);
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let Some(base) = star_at(x, y) else { continue };
+            let brightness = base * sky;
+            if brightness < 0.1 || covered(x, y) {
+                continue;
+            }
+            let glyph = if brightness > 0.7 { '*' } else if brightness > 0.35 { '+' } else { '.' };
+            let style = if opts.dumb {
+                Style::default()
+            } else if opts.truecolor {
+                let level = (90.0 + 165.0 * brightness).round() as u8;
+                Style::default().fg(Color::Rgb(level, level, level))
+            } else {
+                Style::default().fg(if brightness > 0.5 { Color::White } else { Color::DarkGray })
+            };
+            buf.get_mut(x, y).set_char(glyph).set_style(style);
+        }
+    }
+}
+
+/// Column where a feature name of `width` cells starts next to its marker at `marker_x`.
+///
+/// Names go right of the marker when they fit there; otherwise, if the left has more room
+/// (features near the east limb, like Mare Crisium), they end just left of the marker.
+/// `None` when the name fits on neither side.
+fn label_start(marker_x: u16, width: u16, area: Rect) -> Option<u16> {
+    let room_right = area.right().saturating_sub(marker_x + 1);
+    let room_left = marker_x.saturating_sub(area.left());
+    if width < room_right {
+        Some(marker_x + 1)
+    } else if room_left > room_right && width <= room_left {
+        Some(marker_x - width)
+    } else {
+        None
+    }
+}
+
+/// Where to draw `name` beside its marker, and the text to draw: the whole name when it fits
+/// (see `label_start`), else cut with an ellipsis to the roomier side. Cuts fall between
+/// characters, so double-width (CJK) names never spill a half glyph past the pane.
+fn fit_label(marker_x: u16, name: &str, area: Rect) -> Option<(u16, String)> {
+    if let Some(x) = label_start(marker_x, name.width() as u16, area) {
+        return Some((x, name.to_string()));
+    }
+    // Same spare column on the right as `label_start` keeps.
+    let room_right = area.right().saturating_sub(marker_x + 2);
+    let room_left = marker_x.saturating_sub(area.left());
+    let label = truncate_to_width(name, room_right.max(room_left) as usize)?;
+    if room_right >= room_left {
+        Some((marker_x + 1, label))
+    } else {
+        Some((marker_x - label.width() as u16, label))
+    }
+}
+
+/// `text` cut to at most `max` cells, ending in `…` when shortened. `None` if not even one
+/// character and the ellipsis fit.
+pub fn truncate_to_width(text: &str, max: usize) -> Option<String> {
+    if text.width() <= max {
+        return Some(text.to_string());
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > max {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    (!out.is_empty()).then(|| out + "…")
+}
+
+impl Widget for MoonWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        render_moon(&self.status, area, buf, self.options);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate_moon_phase;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn near_side_feature_markers_land_inside_the_disc() {
+        for feature in LUNAR_FEATURES.iter().chain(DETAIL_FEATURES) {
+            let (nx, ny) = feature_projection(feature, 0.0).expect("near-side feature");
+            let r = ((nx - 0.5).powi(2) + (ny - 0.5).powi(2)).sqrt();
+            assert!(r <= 0.5, "{} projects to radius {:.3}", feature.names[0], r);
+        }
+        // Spun half a turn, the whole near side is out of view.
+        assert!(LUNAR_FEATURES.iter().all(|f| feature_projection(f, 180.0).is_none()));
+    }
+
+    #[test]
+    fn glyph_ramp_round_trips_through_density() {
+        for g in GLYPH_RAMP {
+            assert_eq!(density_glyph(glyph_density(g)), g);
+        }
+        assert_eq!(density_glyph(-1.0), ' ');
+        assert_eq!(density_glyph(2.0), '@');
+        // Halfway between ' ' and '@' lands mid-ramp.
+        assert_eq!(density_glyph((glyph_density(' ') + glyph_density('@')) / 2.0), '(');
+    }
+
+    #[test]
+    fn warm_tint_runs_from_cool_to_cream() {
+        assert_eq!(warm_lit_color(0.0, true), Color::Rgb(214, 226, 255));
+        assert_eq!(warm_lit_color(100.0, true), Color::Rgb(255, 234, 186));
+        let Color::Rgb(r, _, b) = warm_lit_color(50.0, true) else { panic!("expected RGB") };
+        assert!(r > 214 && b < 255);
+        assert_eq!(warm_lit_color(10.0, false), Color::Indexed(189));
+        assert_eq!(warm_lit_color(95.0, false), Color::Indexed(223));
+    }
+
+    #[test]
+    fn shadow_modes_cycle_and_earthshine_fades() {
+        assert_eq!(ShadowMode::Full.next(), ShadowMode::Earthshine);
+        assert_eq!(ShadowMode::Earthshine.next(), ShadowMode::Outline);
+        assert_eq!(ShadowMode::Outline.next(), ShadowMode::Hidden);
+        assert_eq!(ShadowMode::Hidden.next(), ShadowMode::Full);
+        let (Color::Rgb(near, _, _), Color::Rgb(far, _, _)) = (earthshine_color(0.0, true), earthshine_color(1.0, true)) else {
+            panic!("expected RGB");
+        };
+        assert!(near > far);
+    }
+
+    #[test]
+    fn long_labels_are_cut_to_the_pane_by_display_width() {
+        assert_eq!(truncate_to_width("嵐の大洋", 8).as_deref(), Some("嵐の大洋"));
+        // 5 cells: two double-width characters plus the ellipsis; never half a glyph.
+        assert_eq!(truncate_to_width("嵐の大洋", 6).as_deref(), Some("嵐の…"));
+        assert_eq!(truncate_to_width("嵐の大洋", 5).as_deref(), Some("嵐の…"));
+        assert_eq!(truncate_to_width("嵐の大洋", 2), None);
+
+        let area = Rect::new(0, 0, 20, 10);
+        let (x, label) = fit_label(8, "嵐の大洋と雨の海", area).unwrap();
+        assert_eq!((x, label.as_str()), (9, "嵐の大洋…"));
+        assert!(x + (label.width() as u16) < area.right());
+        // With more room on the left, the cut name ends just left of the marker instead.
+        let (x, label) = fit_label(12, "嵐の大洋と雨の海", area).unwrap();
+        assert_eq!(x + label.width() as u16, 12);
+        // Short names are untouched.
+        assert_eq!(fit_label(2, "Tycho", area), Some((3, "Tycho".to_string())));
+    }
+
+    #[test]
+    fn labels_flip_left_near_the_right_edge() {
+        let area = Rect::new(0, 0, 40, 10);
+        assert_eq!(label_start(5, 12, area), Some(6));
+        // "Mare Crisium" at column 35 can't fit on the right: it ends just left of the marker.
+        assert_eq!(label_start(35, 12, area), Some(23));
+        assert_eq!(label_start(20, 30, area), None);
+    }
+
+    #[test]
+    fn dumb_terminal_shades_with_the_ascii_ramp_only() {
+        assert_eq!(ramp_glyph(1.0), '@');
+        assert_eq!(ramp_glyph(0.01), '.');
+        let dt = Utc.with_ymd_and_hms(2025, 12, 1, 12, 0, 0).unwrap();
+        let widget = MoonWidget {
+            status: calculate_moon_phase(dt),
+            options: RenderOptions { dumb: true, ..RenderOptions::default() },
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 40, 20));
+        widget.render(buf.area, &mut buf);
+        let mut lit = 0;
+        for y in 0..buf.area.height {
+            for x in 0..buf.area.width {
+                let cell = buf.get(x, y);
+                assert!(cell.symbol() == " " || BRIGHTNESS_RAMP.contains(&cell.symbol().as_bytes()[0]));
+                assert_eq!(cell.fg, Color::Reset);
+                lit += (cell.symbol() != " ") as usize;
+            }
+        }
+        assert!(lit > 100);
+    }
+
+    #[test]
+    fn level_of_detail_follows_the_disc_width() {
+        assert_eq!(Lod::Auto.at_width(20.0), Lod::Low);
+        assert_eq!(Lod::Auto.at_width(60.0), Lod::Auto);
+        assert_eq!(Lod::Auto.at_width(120.0), Lod::High);
+        assert_eq!(Lod::High.at_width(20.0), Lod::High);
+
+        let quarter = Utc.with_ymd_and_hms(2025, 3, 6, 16, 32, 0).unwrap();
+        let render = |area: Rect, lod| {
+            let mut buf = Buffer::empty(area);
+            let options = RenderOptions { show_labels: true, lod, ..RenderOptions::default() };
+            MoonWidget { status: calculate_moon_phase(quarter), options }.render(area, &mut buf);
+            (0..area.height)
+                .map(|y| (0..area.width).map(|x| buf.get(x, y).symbol()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        // A small disc drops the labels; a big one adds the detail features.
+        assert!(!render(Rect::new(0, 0, 40, 10), Lod::Auto).contains('x'));
+        assert!(render(Rect::new(0, 0, 40, 10), Lod::High).contains("Tycho"));
+        let big = render(Rect::new(0, 0, 240, 60), Lod::Auto);
+        assert!(big.contains("Tycho") && big.contains("Clavius"));
+        assert!(!render(Rect::new(0, 0, 160, 40), Lod::Auto).contains("Clavius"));
+    }
+
+    #[test]
+    fn limb_darkening_dims_truecolor_toward_the_edge() {
+        let gold = Color::Rgb(232, 208, 88);
+        assert_eq!(limb_darkened(gold, 1.0), gold);
+        assert_eq!(limb_darkened(gold, 0.0), Color::Rgb(128, 114, 48));
+        assert_eq!(limb_darkened(Color::Yellow, 0.0), Color::Yellow);
+
+        let full = Utc.with_ymd_and_hms(2025, 3, 14, 6, 55, 0).unwrap();
+        let options = RenderOptions { truecolor: true, limb_darkening: true, ..RenderOptions::default() };
+        let area = Rect::new(0, 0, 40, 20);
+        let mut buf = Buffer::empty(area);
+        MoonWidget { status: calculate_moon_phase(full), options }.render(area, &mut buf);
+        let red = |x, y| match buf.get(x, y).fg {
+            Color::Rgb(r, _, _) => r,
+            other => panic!("{other:?}"),
+        };
+        assert!(red(20, 10) > red(2, 10));
+    }
+
+    #[test]
+    fn terminator_traces_one_cell_per_row() {
+        // First quarter: the Sun is side-on, so the boundary is the disc's vertical midline.
+        let quarter = Utc.with_ymd_and_hms(2025, 3, 6, 16, 32, 0).unwrap();
+        let area = Rect::new(0, 0, 40, 20);
+        let render = |terminator| {
+            let options = RenderOptions { terminator, ..RenderOptions::default() };
+            let mut buf = Buffer::empty(area);
+            MoonWidget { status: calculate_moon_phase(quarter), options }.render(area, &mut buf);
+            buf
+        };
+        let buf = render(true);
+        let blue = terminator_color(false);
+        for y in [2, 10, 17] {
+            let hits: Vec<u16> = (0..40).filter(|&x| buf.get(x, y).fg == blue).collect();
+            assert_eq!(hits.len(), 1, "row {y}: {hits:?}");
+            assert!((18..=21).contains(&hits[0]), "row {y}: {hits:?}");
+        }
+        let plain = render(false);
+        assert!((0..20).all(|y| (0..40).all(|x| plain.get(x, y).fg != blue)));
+    }
+
+    #[test]
+    fn density_by_illum_splits_the_glyph_ramp() {
+        assert_eq!(density_by_light(' ', true), '/');
+        assert_eq!(density_by_light('@', true), '@');
+        assert_eq!(density_by_light('@', false), ',');
+        assert_eq!(density_by_light(' ', false), ' ');
+
+        let quarter = Utc.with_ymd_and_hms(2025, 3, 6, 16, 32, 0).unwrap();
+        let options = RenderOptions { density_by_illum: true, ..RenderOptions::default() };
+        let area = Rect::new(0, 0, 40, 20);
+        let mut buf = Buffer::empty(area);
+        MoonWidget { status: calculate_moon_phase(quarter), options }.render(area, &mut buf);
+        let cells: Vec<_> = (0..area.height).flat_map(|y| (0..area.width).map(move |x| (x, y))).collect();
+        let density = |x, y| glyph_density(buf.get(x, y).symbol().chars().next().unwrap());
+        let lit = moon_lit_color(options.truecolor);
+        assert!(cells.iter().filter(|&&(x, y)| buf.get(x, y).fg == lit).all(|&(x, y)| density(x, y) >= LIT_DENSITY_FLOOR - 0.01));
+        let shadow = moon_shadow_color(options.truecolor);
+        assert!(cells.iter().filter(|&&(x, y)| buf.get(x, y).fg == shadow).all(|&(x, y)| density(x, y) <= SHADOW_DENSITY_CEILING));
+    }
+
+    #[test]
+    fn ragged_tabbed_art_is_squared_up_and_renders() {
+        let art = parse_art("  @@\n\t#\n@@@@@@@@@@\n\n a\x07b\t|");
+        assert_eq!(art.len(), 4);
+        assert!(art.iter().all(|row| row.len() == 10));
+        assert_eq!(art[1].iter().collect::<String>(), "        # ");
+        assert_eq!(art[3].iter().collect::<String>(), " a b    | ");
+
+        let full = Utc.with_ymd_and_hms(2025, 3, 14, 6, 55, 0).unwrap();
+        let options = RenderOptions::default();
+        let area = Rect::new(0, 0, 40, 20);
+        let mut buf = Buffer::empty(area);
+        render_art(&calculate_moon_phase(full), area, &mut buf, options, &art);
+        let drawn = (0..area.height)
+            .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| buf.get(x, y).fg != Color::Reset)
+            .count();
+        // Roughly a disc's worth of cells: pi/4 of the 40x20 box.
+        assert!((500..700).contains(&drawn), "{drawn} cells drawn");
+    }
+}
@@ -0,0 +1,217 @@
+//! `--serve`: a tiny single-threaded HTTP endpoint for dashboards (cargo feature `serve`).
+//!
+//! `GET /phase` answers with the `--ephemeris` JSON for now, or for `?date=` (same formats as
+//! `--ephemeris`). Everything else gets a short plain-text error.
+
+use crate::{Observer, ephemeris_json, parse_datetime_arg};
+use ascii_moon::Precision;
+use chrono::{DateTime, Utc};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::time::Duration;
+
+// Requests are handled one at a time, so a client that stalls mid-request is dropped after this.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+// More header lines than any real client sends; the rest of such a request is ignored.
+const MAX_HEADER_LINES: usize = 100;
+// Cap on the request line plus headers, so a client trickling bytes without a newline can't
+// make the server buffer without bound.
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// Serve requests on `addr`:`port` until the process is stopped.
+pub fn serve(addr: IpAddr, port: u16, observer: Option<Observer>, precision: Precision) -> io::Result<()> {
+    let addr = SocketAddr::new(addr, port);
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("serving moon phase JSON on http://{addr}/phase");
+    for stream in listener.incoming() {
+        // One bad client mustn't stop the server.
+        if let Err(err) = stream.and_then(|s| handle(s, observer, precision)) {
+            eprintln!("request failed: {err}");
+        }
+    }
+    Ok(())
+}
+
+fn handle(stream: TcpStream, observer: Option<Observer>, precision: Precision) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let (status, content_type, body) = match read_request(&stream)? {
+        Some(request_line) => respond(&request_line, observer, precision, Utc::now()),
+        None => ("431 Request Header Fields Too Large", "text/plain; charset=utf-8", "request too large\n".to_string()),
+    };
+    write!(
+        &stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    (&stream).flush()
+}
+
+/// The request line, after reading past the headers; `None` if the request ran past
+/// `MAX_REQUEST_BYTES` before its headers ended.
+fn read_request<R: Read>(input: R) -> io::Result<Option<String>> {
+    let mut reader = BufReader::new(input.take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Closing with unread input makes some clients see a reset instead of the response.
+    let ended = skip_headers(&mut reader)?;
+    Ok((ended || reader.get_ref().limit() > 0).then_some(request_line))
+}
+
+/// Read past the request headers, up to and including the blank line that ends them.
+/// Whether that blank line was seen (rather than end of input or `MAX_HEADER_LINES`).
+fn skip_headers<R: BufRead>(reader: &mut R) -> io::Result<bool> {
+    let mut line = String::new();
+    for _ in 0..MAX_HEADER_LINES {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(false);
+        }
+        if line.trim_end().is_empty() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Status line, content type and body for one request line such as `GET /phase?date=2025-12-13 HTTP/1.1`.
+fn respond(
+    request_line: &str,
+    observer: Option<Observer>,
+    precision: Precision,
+    now: DateTime<Utc>,
+) -> (&'static str, &'static str, String) {
+    const TEXT: &str = "text/plain; charset=utf-8";
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return ("400 Bad Request", TEXT, "malformed request\n".to_string());
+    };
+    if method != "GET" {
+        return ("405 Method Not Allowed", TEXT, "only GET is supported\n".to_string());
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != "/phase" {
+        return ("404 Not Found", TEXT, "try GET /phase?date=YYYY-MM-DD\n".to_string());
+    }
+    let date = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "date")
+        .map(|(_, value)| percent_decode(value));
+    let at = match date.as_deref() {
+        None | Some("") => now,
+        Some(date) => match parse_datetime_arg(date) {
+            Ok(at) => at,
+            Err(err) => return ("400 Bad Request", TEXT, format!("{err}\n")),
+        },
+    };
+    ("200 OK", "application/json", ephemeris_json(at, observer, precision) + "\n")
+}
+
+/// Undo `%XX` escapes (e.g. `%3A` for `:`) and `+` for space; malformed escapes stay as they are.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                out.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn routes_phase_requests() {
+        let now = Utc.with_ymd_and_hms(2025, 12, 4, 23, 14, 0).unwrap();
+        let (status, content_type, body) = respond("GET /phase HTTP/1.1", None, Precision::Standard, now);
+        assert_eq!((status, content_type), ("200 OK", "application/json"));
+        assert_eq!(body, ephemeris_json(now, None, Precision::Standard) + "\n");
+
+        let (status, _, body) = respond("GET /phase?date=2025-12-13T04%3A00 HTTP/1.1", None, Precision::Standard, now);
+        assert_eq!(status, "200 OK");
+        assert!(body.contains("\"date\":\"2025-12-13T04:00:00Z\""), "{body}");
+
+        assert_eq!(respond("GET /phase?date=soon HTTP/1.1", None, Precision::Standard, now).0, "400 Bad Request");
+        assert_eq!(respond("GET /phase?date=2500-01-01 HTTP/1.1", None, Precision::Standard, now).0, "400 Bad Request");
+        assert_eq!(respond("GET / HTTP/1.1", None, Precision::Standard, now).0, "404 Not Found");
+        assert_eq!(respond("POST /phase HTTP/1.1", None, Precision::Standard, now).0, "405 Method Not Allowed");
+        assert_eq!(respond("", None, Precision::Standard, now).0, "400 Bad Request");
+    }
+
+    #[test]
+    fn answers_over_a_socket_after_the_headers() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(b"GET /phase?date=2025-12-13 HTTP/1.1\r\nHost: x\r\nAccept: */*\r\n\r\n").unwrap();
+            let mut response = String::new();
+            io::Read::read_to_string(&mut stream, &mut response).unwrap();
+            response
+        });
+        let (stream, _) = listener.accept().unwrap();
+        handle(stream, None, Precision::Standard).unwrap();
+        let response = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+        assert!(response.contains("\"date\":\"2025-12-13T12:00:00Z\""), "{response}");
+    }
+
+    #[test]
+    fn listens_on_localhost_unless_told_otherwise() {
+        use clap::Parser;
+        let args = crate::Args::parse_from(["ascii_moon", "--serve", "8080"]);
+        assert_eq!(args.bind, IpAddr::from([127, 0, 0, 1]));
+        let args = crate::Args::parse_from(["ascii_moon", "--serve", "8080", "--bind", "0.0.0.0"]);
+        assert_eq!(args.bind, IpAddr::from([0, 0, 0, 0]));
+        assert!(crate::Args::try_parse_from(["ascii_moon", "--bind", "0.0.0.0"]).is_err());
+    }
+
+    #[test]
+    fn skips_headers_up_to_the_blank_line() {
+        let mut input = io::Cursor::new("Host: x\r\nAccept: */*\r\n\r\nbody");
+        assert!(skip_headers(&mut input).unwrap());
+        let mut rest = String::new();
+        io::Read::read_to_string(&mut input, &mut rest).unwrap();
+        assert_eq!(rest, "body");
+        // A client that hangs up early just ends the headers.
+        assert!(!skip_headers(&mut io::Cursor::new("Host: x\r\n")).unwrap());
+    }
+
+    #[test]
+    fn oversized_requests_are_cut_off() {
+        let request = read_request(io::Cursor::new("GET /phase HTTP/1.1\r\nHost: x\r\n\r\n")).unwrap();
+        assert_eq!(request.as_deref(), Some("GET /phase HTTP/1.1\r\n"));
+        // A client that hangs up early is still answered.
+        assert!(read_request(io::Cursor::new("GET /phase HTTP/1.1\r\n")).unwrap().is_some());
+
+        // An endless line (or endless headers) stops at the cap instead of growing forever.
+        let endless = io::repeat(b'a');
+        assert_eq!(read_request(endless).unwrap(), None);
+        let endless = io::Cursor::new("GET /phase HTTP/1.1\r\n").chain(io::repeat(b'a'));
+        assert_eq!(read_request(endless).unwrap(), None);
+    }
+
+    #[test]
+    fn percent_decoding_keeps_malformed_escapes() {
+        assert_eq!(percent_decode("2025-12-13T04%3a00"), "2025-12-13T04:00");
+        assert_eq!(percent_decode("a+b%2"), "a b%2");
+    }
+}